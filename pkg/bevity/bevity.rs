@@ -12,8 +12,26 @@ pub use bevity_editor::BEVITY_EDITOR_SCENE;
 pub use bevity_editor::BEVITY_EDITOR_SCENE_GUID;
 pub use bevity_editor::ENABLE_BEVITY_EDITOR;
 pub use bevity_generator::exported_component_list;
+pub use bevity_generator::ScriptableObject;
+pub use bevity_scene::AnimatorParameter;
+pub use bevity_scene::AudioMixers;
+pub use bevity_scene::BuiltinMonoBehaviour;
+pub use bevity_scene::CameraAntialiasing;
+pub use bevity_scene::CameraImportSettings;
 pub use bevity_scene::MonoBehaviour;
+pub use bevity_scene::RegisterUnityShader;
+pub use bevity_scene::ScriptableObject;
+pub use bevity_scene::ScriptableObjectPlugin;
+pub use bevity_scene::ScriptableResourcePlugin;
+pub use bevity_scene::ShaderContext;
+pub use bevity_scene::ShaderHandlerRegistry;
+pub use bevity_scene::ShaderKey;
+pub use bevity_scene::UnityAnimation;
+pub use bevity_scene::UnityAnimatorController;
+pub use bevity_scene::UnityAssets;
+pub use bevity_scene::UnityModelInstance;
 pub use bevity_scene::UnitySceneObject;
+pub use bevity_scene::UnitySubmesh;
 
 #[derive(Default)]
 pub struct BevityPlugin<T> {
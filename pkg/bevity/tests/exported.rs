@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Component, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Speed {
+    pub speed: f32,
+}
+
+bevity::exported_component_list!((Speed,));
+
+fn parse(yaml: &str) -> BevityExported {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+#[test]
+fn test_unknown_script() {
+    // no BEVITY_SCRIPT_GUID_Speed at compile time, so matching fields alone
+    // don't make it a Speed
+    let exported = parse(
+        "m_Script: {fileID: 11500000, guid: 0123456789abcdef0123456789abcdef, type: 3}\nspeed: 4\n",
+    );
+    assert!(matches!(
+        exported,
+        BevityExported::Unknown(guid) if guid == "0123456789abcdef0123456789abcdef"
+    ));
+}
+
+#[test]
+fn test_no_script() {
+    assert!(matches!(parse("speed: 4\n"), BevityExported::DontCare));
+    assert!(matches!(
+        parse("m_Script: {fileID: 0}\nspeed: 4\n"),
+        BevityExported::DontCare
+    ));
+}
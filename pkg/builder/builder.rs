@@ -1,4 +1,10 @@
 use genco::prelude::*;
+use std::path::Path;
+
+/// Prefix of the compile time env vars holding the `m_Script` guid of every
+/// generated script. `exported_component_list!` and `#[derive(ScriptableObject)]`
+/// read these back.
+const SCRIPT_GUID_ENV_PREFIX: &str = "BEVITY_SCRIPT_GUID_";
 
 pub fn build(file_path: &str, output_path: &str) -> anyhow::Result<()> {
     println!("cargo:rerun-if-changed={}", file_path);
@@ -16,12 +22,28 @@ pub fn build(file_path: &str, output_path: &str) -> anyhow::Result<()> {
             syn::Item::Struct(s) => Some(s),
             _ => None,
         })
-        .filter(|item| {
-            item.attrs
+        .filter_map(|item| {
+            let derives = item
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("derive"))
+                .collect::<Vec<_>>();
+
+            if derives
                 .iter()
-                .any(|attr| attr.path().is_ident("derive") && attribute_is_component(attr))
+                .any(|attr| attribute_derives(attr, "ScriptableObject"))
+            {
+                Some((item, UnityClass::ScriptableObject))
+            } else if derives
+                .iter()
+                .any(|attr| attribute_derives(attr, "Component"))
+            {
+                Some((item, UnityClass::MonoBehaviour))
+            } else {
+                None
+            }
         })
-        .map(|strukt| {
+        .map(|(strukt, class)| {
             let name = &strukt.ident.to_string();
             let class_name = quote! { $name };
             let fields = strukt.fields.iter().map(|field| -> csharp::Tokens {
@@ -34,30 +56,112 @@ pub fn build(file_path: &str, output_path: &str) -> anyhow::Result<()> {
                 }
             });
 
-            let tokens: csharp::Tokens = quote! {
-                using UnityEngine;
+            let tokens: csharp::Tokens = match class {
+                UnityClass::MonoBehaviour => quote! {
+                    using UnityEngine;
+
+                    public class $class_name: MonoBehaviour
+                    {
+                        $(for field in fields => $field)
+                    }
+                },
+                UnityClass::ScriptableObject => {
+                    let menu_name = format!("Bevity/{}", name);
+                    quote! {
+                        using UnityEngine;
 
-                public class $class_name: MonoBehaviour
-                {
-                    $(for field in fields => $field)
+                        [CreateAssetMenu(menuName = $(quoted(menu_name)))]
+                        public class $class_name: ScriptableObject
+                        {
+                            $(for field in fields => $field)
+                        }
+                    }
                 }
             };
 
             (name.clone(), tokens)
         })
-        .try_for_each(|(name, contents)| {
+        .try_for_each(|(name, contents)| -> anyhow::Result<()> {
             std::fs::write(
                 format!("{}/{}.cs", output_path, name),
                 contents.to_file_string().unwrap(),
-            )
+            )?;
+
+            let guid = ensure_script_meta(output_path, &name)?;
+            println!(
+                "cargo:rustc-env={}{}={}",
+                SCRIPT_GUID_ENV_PREFIX, name, guid
+            );
+
+            Ok(())
         })?;
 
     Ok(())
 }
 
-fn attribute_is_component(attr: &syn::Attribute) -> bool {
+/// Returns the guid unity uses for the generated script, writing the `.cs.meta`
+/// ourselves when unity hasn't imported the script yet so both sides agree on it.
+fn ensure_script_meta(output_path: &str, name: &str) -> anyhow::Result<String> {
+    let meta_path = Path::new(output_path).join(format!("{}.cs.meta", name));
+    println!("cargo:rerun-if-changed={}", meta_path.display());
+
+    if meta_path.exists() {
+        let meta = std::fs::read_to_string(&meta_path)?;
+        let guid = meta
+            .lines()
+            .find_map(|line| line.strip_prefix("guid:"))
+            .map(|guid| guid.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("missing guid in {}", meta_path.display()))?;
+
+        return Ok(guid);
+    }
+
+    let guid = script_guid(name);
+    std::fs::write(
+        &meta_path,
+        format!(
+            r#"fileFormatVersion: 2
+guid: {}
+MonoImporter:
+  externalObjects: {{}}
+  serializedVersion: 2
+  defaultReferences: []
+  executionOrder: 0
+  icon: {{instanceID: 0}}
+  userData: 
+  assetBundleName: 
+  assetBundleVariant: 
+"#,
+            guid
+        ),
+    )?;
+
+    Ok(guid)
+}
+
+// stable across builds and machines, unlike std's hasher
+fn script_guid(name: &str) -> String {
+    let fnv = |offset: u64| {
+        name.bytes().fold(offset, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    };
+
+    format!(
+        "{:016x}{:016x}",
+        fnv(0xcbf29ce484222325),
+        fnv(0x84222325cbf29ce4)
+    )
+}
+
+enum UnityClass {
+    MonoBehaviour,
+    ScriptableObject,
+}
+
+fn attribute_derives(attr: &syn::Attribute, derive: &str) -> bool {
     match &attr.meta {
-        syn::Meta::List(list) => list.tokens.to_string().contains("Component"),
+        syn::Meta::List(list) => list.tokens.to_string().contains(derive),
         _ => false,
     }
 }
@@ -85,3 +189,80 @@ fn map_type_to_csharp(ty: &syn::Type) -> String {
     }
     .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// An output directory of its own for each test, removed afterwards.
+    struct OutputDir(PathBuf);
+
+    impl OutputDir {
+        fn new(name: &str) -> anyhow::Result<Self> {
+            let dir = std::env::temp_dir().join(format!(
+                "bevity_builder_{}_{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir)?;
+            Ok(Self(dir))
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for OutputDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_script_guid() {
+        // written into .cs.meta files, so it must never change between builds
+        assert_eq!(
+            script_guid("PlayerController"),
+            "49614875a12ac3e8c68af408c5b2c3ed"
+        );
+        assert_eq!(script_guid("Settings"), script_guid("Settings"));
+        assert_ne!(script_guid("Settings"), script_guid("Setting"));
+    }
+
+    #[test]
+    fn test_new_script_meta() -> anyhow::Result<()> {
+        let output = OutputDir::new("new_meta")?;
+
+        let guid = ensure_script_meta(output.path(), "PlayerController")?;
+        assert_eq!(guid, script_guid("PlayerController"));
+
+        let meta = std::fs::read_to_string(output.0.join("PlayerController.cs.meta"))?;
+        assert!(meta.contains(&format!("\nguid: {}\n", guid)));
+        assert!(meta.contains("MonoImporter:"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_script_meta() -> anyhow::Result<()> {
+        let output = OutputDir::new("existing_meta")?;
+
+        // imported by unity before, with a guid of its own
+        let meta_path = output.0.join("PlayerController.cs.meta");
+        let meta = "fileFormatVersion: 2\nguid: 0123456789abcdef0123456789abcdef\nMonoImporter:\n  executionOrder: 100\n";
+        std::fs::write(&meta_path, meta)?;
+
+        let guid = ensure_script_meta(output.path(), "PlayerController")?;
+        assert_eq!(guid, "0123456789abcdef0123456789abcdef");
+        assert_eq!(std::fs::read_to_string(&meta_path)?, meta);
+
+        std::fs::write(&meta_path, "fileFormatVersion: 2\n")?;
+        assert!(ensure_script_meta(output.path(), "PlayerController").is_err());
+
+        Ok(())
+    }
+}
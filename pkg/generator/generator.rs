@@ -1,20 +1,52 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, Ident};
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Ident, Token,
+};
+
+/// An entry of [`inbuilt_component_list!`]: `Name` holding a `UnityName`, or
+/// `Name(Type)` when the type is named otherwise.
+struct InbuiltComponent {
+    ident: Ident,
+    ty: syn::Type,
+}
+
+impl Parse for InbuiltComponent {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+        let ty = if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            content.parse()?
+        } else {
+            let inbuilt_ident = format_ident!("Unity{}", ident);
+            syn::parse_quote!(#inbuilt_ident)
+        };
+
+        Ok(Self { ident, ty })
+    }
+}
+
+struct InbuiltComponents(Punctuated<InbuiltComponent, Token![,]>);
+
+impl Parse for InbuiltComponents {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        parenthesized!(content in input);
+        Ok(Self(Punctuated::parse_terminated(&content)?))
+    }
+}
 
 #[proc_macro]
 pub fn inbuilt_component_list(item: TokenStream) -> TokenStream {
-    // (GameObject -> UnityGameObject)
-    let tuples = parse_macro_input!(item as syn::TypeTuple);
+    // (GameObject -> UnityGameObject, Animation(UnityLegacyAnimation))
+    let list = parse_macro_input!(item as InbuiltComponents);
 
-    let filtered = tuples
-        .elems
-        .iter()
-        .filter_map(|v| match v {
-            syn::Type::Path(p) => Some(p),
-            _ => None,
-        })
-        .filter_map(|p| p.path.get_ident());
+    let filtered = list.0.iter().map(|component| &component.ident);
 
     let components = filtered.clone().map(|ident| {
         let ident = format_ident!("Unity{}", ident);
@@ -31,10 +63,9 @@ pub fn inbuilt_component_list(item: TokenStream) -> TokenStream {
         }
     });
 
-    let enums = filtered.clone().map(|ident| {
-        let inbuilt_ident = format_ident!("Unity{}", ident);
+    let enums = list.0.iter().map(|InbuiltComponent { ident, ty }| {
         quote! {
-            #ident(#inbuilt_ident)
+            #ident(#ty)
         }
     });
 
@@ -171,20 +202,74 @@ pub fn exported_component_list(item: TokenStream) -> TokenStream {
         }
     });
 
+    // monobehaviours are matched on their m_Script guid, which bevity::build
+    // exports for every generated script. field shapes alone can collide.
+    let dispatch = filtered.clone().map(|ident| {
+        let env_name = script_guid_env(ident);
+        quote! {
+            if option_env!(#env_name) == Some(guid.as_str()) {
+                return serde_json::from_value(value)
+                    .map(BevityExported::#ident)
+                    .map_err(|e| D::Error::custom(format!("failed to parse {}: {}", stringify!(#ident), e)));
+            }
+        }
+    });
+
+    let missing_guids = filtered.clone().map(|ident| {
+        let env_name = script_guid_env(ident);
+        quote! {
+            if option_env!(#env_name).is_none() {
+                bevy::log::warn!("no script guid for {}, is bevity::build called from build.rs?", stringify!(#ident));
+            }
+        }
+    });
+
     quote! {
         #(#generated)*
 
-        #[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+        #[derive(serde::Serialize, Clone, Default)]
         #[serde(untagged)]
         pub enum BevityExported {
             #[default]
             DontCare,
 
+            #[serde(skip)]
+            Unknown(String),
+
+            #[serde(skip)]
+            Builtin(bevity::BuiltinMonoBehaviour),
+
             #(#exported),*
         }
 
+        impl<'de> serde::Deserialize<'de> for BevityExported {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                use serde::de::Error;
+
+                let value = serde_json::Value::deserialize(deserializer)?;
+                let Some(guid) = value
+                    .get("m_Script")
+                    .and_then(|script| script.get("guid"))
+                    .and_then(|guid| guid.as_str())
+                    .map(|guid| guid.to_string())
+                else {
+                    return Ok(BevityExported::DontCare);
+                };
+
+                #(#dispatch)*
+
+                if let Some(builtin) = bevity::BuiltinMonoBehaviour::from_script(&guid, value).map_err(D::Error::custom)? {
+                    return Ok(BevityExported::Builtin(builtin));
+                }
+
+                Ok(BevityExported::Unknown(guid))
+            }
+        }
+
         impl Plugin for BevityExported {
             fn build(&self, app: &mut App) {
+                #(#missing_guids)*
+
                 if std::env::var_os(bevity::ENABLE_BEVITY_EDITOR).is_none() || std::env::var_os(bevity::BEVITY_EDITOR_SCENE_GUID).is_none() {
                     return;
                 }
@@ -201,6 +286,12 @@ pub fn exported_component_list(item: TokenStream) -> TokenStream {
                     BevityExported::DontCare => {
                         println!("found dont care component");
                     }
+
+                    BevityExported::Builtin(builtin) => builtin.add_to_entity(cmd),
+
+                    BevityExported::Unknown(guid) => {
+                        bevy::log::warn!("unknown monobehaviour script {} on object {}", guid, object_id);
+                    }
                 };
             }
 
@@ -211,6 +302,12 @@ pub fn exported_component_list(item: TokenStream) -> TokenStream {
                     BevityExported::DontCare => {
                         println!("found dont care component");
                     }
+
+                    BevityExported::Builtin(builtin) => builtin.update_entity(cmd),
+
+                    BevityExported::Unknown(guid) => {
+                        bevy::log::warn!("unknown monobehaviour script {} in editor update", guid);
+                    }
                 };
             }
         }
@@ -218,3 +315,23 @@ pub fn exported_component_list(item: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+#[proc_macro_derive(ScriptableObject)]
+pub fn derive_scriptable_object(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::DeriveInput);
+    let ident = &input.ident;
+    let env_name = script_guid_env(ident);
+
+    quote! {
+        impl bevity::ScriptableObject for #ident {
+            fn script_guid() -> Option<&'static str> {
+                option_env!(#env_name)
+            }
+        }
+    }
+    .into()
+}
+
+fn script_guid_env(ident: &Ident) -> String {
+    format!("BEVITY_SCRIPT_GUID_{}", ident)
+}
@@ -0,0 +1,416 @@
+use bevy::prelude::*;
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+
+/// An `.anim` file's clip, with curves as authored in the editor.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct UnityAnimationClip {
+    #[serde(alias = "m_Name")]
+    pub name: String,
+    /// Clips for the legacy `Animation` component.
+    #[serde(default, alias = "m_Legacy")]
+    pub legacy: u8,
+    #[serde(default = "default_sample_rate", alias = "m_SampleRate")]
+    pub sample_rate: f32,
+    /// Loops legacy clips when 2.
+    #[serde(default, alias = "m_WrapMode")]
+    pub wrap_mode: i32,
+    #[serde(default, alias = "m_AnimationClipSettings")]
+    pub settings: UnityAnimationClipSettings,
+
+    #[serde(default, alias = "m_PositionCurves")]
+    pub position_curves: Vec<UnityTransformCurve<CurveVector3>>,
+    #[serde(default, alias = "m_RotationCurves")]
+    pub rotation_curves: Vec<UnityTransformCurve<CurveQuaternion>>,
+    /// Rotations in degrees, written instead of quaternions for clips
+    /// recorded in the editor.
+    #[serde(default, alias = "m_EulerCurves")]
+    pub euler_curves: Vec<UnityTransformCurve<CurveVector3>>,
+    #[serde(default, alias = "m_ScaleCurves")]
+    pub scale_curves: Vec<UnityTransformCurve<CurveVector3>>,
+    #[serde(default, alias = "m_FloatCurves")]
+    pub float_curves: Vec<UnityFloatCurve>,
+}
+
+fn default_sample_rate() -> f32 {
+    60.0
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct UnityAnimationClipSettings {
+    #[serde(default, alias = "m_StartTime")]
+    pub start_time: f32,
+    #[serde(default, alias = "m_StopTime")]
+    pub stop_time: f32,
+    #[serde(default, alias = "m_LoopTime")]
+    pub loop_time: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityTransformCurve<T> {
+    pub curve: UnityAnimationCurve<T>,
+    /// Path from the animator, empty for the animator's own transform.
+    #[serde(default, deserialize_with = "deserialize_path")]
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityFloatCurve {
+    pub curve: UnityAnimationCurve<CurveFloat>,
+    /// The animated field, e.g. `m_Intensity` or `material._Color.r`.
+    pub attribute: String,
+    #[serde(default, deserialize_with = "deserialize_path")]
+    pub path: String,
+    /// Unity class id of the animated component.
+    #[serde(default, alias = "classID")]
+    pub class_id: i32,
+}
+
+/// Unity writes an empty path as a null value.
+fn deserialize_path<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Hermite keyframes. Weighted tangents are read as regular ones.
+/// Name of a child the player of `.anim` clips needs, holding only a `Name`.
+/// Clips of only float curves key it to get their duration, bevy finds it but
+/// has no transform to change.
+pub const CLIP_DURATION_NODE: &str = "UnityClipDuration";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAnimationCurve<T> {
+    #[serde(alias = "m_Curve")]
+    pub keys: Vec<UnityKeyframe<T>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityKeyframe<T> {
+    pub time: f32,
+    pub value: T,
+    #[serde(alias = "inSlope")]
+    pub in_slope: T,
+    #[serde(alias = "outSlope")]
+    pub out_slope: T,
+}
+
+/// A value whose components are interpolated separately.
+pub trait CurveValue: Copy {
+    fn map_components(values: [Self; 4], f: impl Fn([f32; 4]) -> f32) -> Self;
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
+pub struct CurveFloat(#[serde(deserialize_with = "deserialize_curve_float")] pub f32);
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
+pub struct CurveVector3 {
+    #[serde(deserialize_with = "deserialize_curve_float")]
+    pub x: f32,
+    #[serde(deserialize_with = "deserialize_curve_float")]
+    pub y: f32,
+    #[serde(deserialize_with = "deserialize_curve_float")]
+    pub z: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
+pub struct CurveQuaternion {
+    #[serde(deserialize_with = "deserialize_curve_float")]
+    pub x: f32,
+    #[serde(deserialize_with = "deserialize_curve_float")]
+    pub y: f32,
+    #[serde(deserialize_with = "deserialize_curve_float")]
+    pub z: f32,
+    #[serde(deserialize_with = "deserialize_curve_float")]
+    pub w: f32,
+}
+
+impl CurveValue for CurveFloat {
+    fn map_components(v: [Self; 4], f: impl Fn([f32; 4]) -> f32) -> Self {
+        CurveFloat(f([v[0].0, v[1].0, v[2].0, v[3].0]))
+    }
+}
+
+impl CurveValue for CurveVector3 {
+    fn map_components(v: [Self; 4], f: impl Fn([f32; 4]) -> f32) -> Self {
+        CurveVector3 {
+            x: f(v.map(|v| v.x)),
+            y: f(v.map(|v| v.y)),
+            z: f(v.map(|v| v.z)),
+        }
+    }
+}
+
+impl CurveValue for CurveQuaternion {
+    fn map_components(v: [Self; 4], f: impl Fn([f32; 4]) -> f32) -> Self {
+        CurveQuaternion {
+            x: f(v.map(|v| v.x)),
+            y: f(v.map(|v| v.y)),
+            z: f(v.map(|v| v.z)),
+            w: f(v.map(|v| v.w)),
+        }
+    }
+}
+
+/// Stepped keys have infinite slopes, which unity writes as `Infinity`.
+fn deserialize_curve_float<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct CurveFloatVisitor;
+
+    impl<'de> Visitor<'de> for CurveFloatVisitor {
+        type Value = f32;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a float or infinity")
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+            Ok(value as f32)
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+            Ok(value as f32)
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+            Ok(value as f32)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            value
+                .parse()
+                .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+        }
+    }
+
+    deserializer.deserialize_any(CurveFloatVisitor)
+}
+
+impl<T: CurveValue> UnityAnimationCurve<T> {
+    /// The curve's value at `time`, held constant outside of its keys.
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = self.keys.first()?;
+        let last = self.keys.last()?;
+        if time <= first.time {
+            return Some(first.value);
+        }
+        if time >= last.time {
+            return Some(last.value);
+        }
+
+        let next = self.keys.iter().position(|key| key.time > time)?;
+        let (from, to) = (&self.keys[next - 1], &self.keys[next]);
+        let dt = to.time - from.time;
+        let t = (time - from.time) / dt;
+
+        Some(T::map_components(
+            [from.value, from.out_slope, to.value, to.in_slope],
+            |[p0, m0, p1, m1]| {
+                if !m0.is_finite() || !m1.is_finite() {
+                    return p0;
+                }
+
+                let t2 = t * t;
+                let t3 = t2 * t;
+                (2.0 * t3 - 3.0 * t2 + 1.0) * p0
+                    + (t3 - 2.0 * t2 + t) * m0 * dt
+                    + (-2.0 * t3 + 3.0 * t2) * p1
+                    + (t3 - t2) * m1 * dt
+            },
+        ))
+    }
+
+    fn end_time(&self) -> f32 {
+        self.keys.last().map(|key| key.time).unwrap_or_default()
+    }
+}
+
+// unity class ids of animated components
+const CLASS_GAME_OBJECT: i32 = 1;
+const CLASS_MESH_RENDERER: i32 = 23;
+const CLASS_LIGHT: i32 = 108;
+const CLASS_SKINNED_MESH_RENDERER: i32 = 137;
+
+/// Component fields float curves can drive.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnityAnimatedProperty {
+    /// `GameObject.m_IsActive`, as the entity's visibility.
+    Active,
+    LightIntensity,
+    /// Channel of the light's colour, red to alpha.
+    LightColor(usize),
+    /// Channel of a colour in the renderer's materials, e.g. `_Color`.
+    MaterialColor {
+        name: String,
+        channel: usize,
+    },
+}
+
+impl UnityAnimatedProperty {
+    pub fn from_curve(curve: &UnityFloatCurve) -> Option<Self> {
+        let channel = |name: &str| match name {
+            "r" => Some(0),
+            "g" => Some(1),
+            "b" => Some(2),
+            "a" => Some(3),
+            _ => None,
+        };
+        let attribute = curve.attribute.as_str();
+
+        match curve.class_id {
+            CLASS_GAME_OBJECT if attribute == "m_IsActive" => Some(Self::Active),
+            CLASS_LIGHT if attribute == "m_Intensity" => Some(Self::LightIntensity),
+            CLASS_LIGHT => Some(Self::LightColor(channel(
+                attribute.strip_prefix("m_Color.")?,
+            )?)),
+            CLASS_MESH_RENDERER | CLASS_SKINNED_MESH_RENDERER => {
+                let (name, suffix) = attribute.strip_prefix("material.")?.rsplit_once('.')?;
+                Some(Self::MaterialColor {
+                    name: name.to_string(),
+                    channel: channel(suffix)?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl UnityAnimationClip {
+    pub fn loops(&self) -> bool {
+        self.settings.loop_time != 0 || self.wrap_mode == 2
+    }
+
+    /// Length in seconds, from the clip settings or else the last key.
+    pub fn duration(&self) -> f32 {
+        let settings = &self.settings;
+        if settings.stop_time > settings.start_time {
+            return settings.stop_time - settings.start_time;
+        }
+
+        let transforms = self
+            .position_curves
+            .iter()
+            .chain(&self.euler_curves)
+            .chain(&self.scale_curves)
+            .map(|c| c.curve.end_time());
+        let rotations = self.rotation_curves.iter().map(|c| c.curve.end_time());
+        let floats = self.float_curves.iter().map(|c| c.curve.end_time());
+        transforms
+            .chain(rotations)
+            .chain(floats)
+            .fold(0.0, f32::max)
+    }
+
+    /// Samples the transform curves into a bevy clip for an `AnimationPlayer`
+    /// on the entity named `root`, converting to bevy's coordinates like
+    /// [`crate::UnityTransform`].
+    pub fn to_animation_clip(&self, root: &Name) -> AnimationClip {
+        let duration = self.duration();
+        let start = self.settings.start_time;
+        let rate = self.sample_rate.max(1.0);
+        let count = (duration * rate).ceil() as usize + 1;
+        let times: Vec<f32> = (0..count)
+            .map(|i| (i as f32 / rate).min(duration))
+            .collect();
+
+        let path = |path: &str| EntityPath {
+            parts: std::iter::once(root.clone())
+                .chain(
+                    path.split('/')
+                        .filter(|part| !part.is_empty())
+                        .map(|part| Name::new(part.to_string())),
+                )
+                .collect(),
+        };
+        let sample = |curve: &UnityAnimationCurve<CurveVector3>| -> Vec<Vec3> {
+            times
+                .iter()
+                .map(|t| {
+                    let value = curve.sample(start + t).unwrap_or_default();
+                    Vec3::new(value.x, value.y, value.z)
+                })
+                .collect()
+        };
+        let to_bevy =
+            |rotation: Quat| Quat::from_xyzw(rotation.x, rotation.y, -rotation.z, -rotation.w);
+
+        let mut clip = AnimationClip::default();
+        for curve in &self.position_curves {
+            let keyframes = sample(&curve.curve)
+                .into_iter()
+                .map(|p| Vec3::new(p.x, p.y, -p.z));
+            clip.add_curve_to_path(
+                path(&curve.path),
+                VariableCurve {
+                    keyframe_timestamps: times.clone(),
+                    keyframes: Keyframes::Translation(keyframes.collect()),
+                },
+            );
+        }
+        for curve in &self.rotation_curves {
+            let keyframes = times.iter().map(|t| {
+                let q = curve.curve.sample(start + t).unwrap_or_default();
+                to_bevy(Quat::from_xyzw(q.x, q.y, q.z, q.w).normalize())
+            });
+            clip.add_curve_to_path(
+                path(&curve.path),
+                VariableCurve {
+                    keyframe_timestamps: times.clone(),
+                    keyframes: Keyframes::Rotation(keyframes.collect()),
+                },
+            );
+        }
+        for curve in &self.euler_curves {
+            // unity rotates around z, then x, then y
+            let keyframes = sample(&curve.curve).into_iter().map(|degrees| {
+                let radians = degrees * std::f32::consts::PI / 180.0;
+                to_bevy(Quat::from_euler(
+                    EulerRot::YXZ,
+                    radians.y,
+                    radians.x,
+                    radians.z,
+                ))
+            });
+            clip.add_curve_to_path(
+                path(&curve.path),
+                VariableCurve {
+                    keyframe_timestamps: times.clone(),
+                    keyframes: Keyframes::Rotation(keyframes.collect()),
+                },
+            );
+        }
+        for curve in &self.scale_curves {
+            clip.add_curve_to_path(
+                path(&curve.path),
+                VariableCurve {
+                    keyframe_timestamps: times.clone(),
+                    keyframes: Keyframes::Scale(sample(&curve.curve)),
+                },
+            );
+        }
+
+        // transform curves are sampled over the whole duration, clips with
+        // only float curves get a key on the transformless duration node to
+        // last as long without animating anything
+        let transformed = !self.position_curves.is_empty()
+            || !self.rotation_curves.is_empty()
+            || !self.euler_curves.is_empty()
+            || !self.scale_curves.is_empty();
+        if !transformed {
+            clip.add_curve_to_path(
+                path(CLIP_DURATION_NODE),
+                VariableCurve {
+                    keyframe_timestamps: vec![0.0, duration],
+                    keyframes: Keyframes::Scale(vec![Vec3::ONE; 2]),
+                },
+            );
+        }
+
+        clip
+    }
+}
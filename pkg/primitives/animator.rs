@@ -0,0 +1,76 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::FileReference;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct UnityAnimator {
+    #[serde(default = "default_one", alias = "m_Enabled")]
+    pub enabled: u8,
+
+    /// The `.controller` asset, without one unity plays nothing.
+    #[serde(default, alias = "m_Controller")]
+    pub controller: FileReference,
+
+    #[serde(default, alias = "m_Avatar")]
+    pub avatar: FileReference,
+
+    #[serde(default, alias = "m_ApplyRootMotion")]
+    pub apply_root_motion: u8,
+}
+
+fn default_one() -> u8 {
+    1
+}
+
+impl UnityAnimator {
+    pub fn add_animator(&self, commands: &mut EntityCommands) {
+        commands.insert(UnityAnimatorExtra {
+            animator: self.clone(),
+        });
+        commands.insert(UnityAnimatorRequiresSetup);
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct UnityAnimatorExtra {
+    pub animator: UnityAnimator,
+}
+
+#[derive(Component, Debug)]
+pub struct UnityAnimatorRequiresSetup;
+
+/// The legacy `Animation` component, playing `m_Legacy` clips without a
+/// controller.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct UnityLegacyAnimation {
+    #[serde(default = "default_one", alias = "m_Enabled")]
+    pub enabled: u8,
+
+    /// The default clip.
+    #[serde(default, alias = "m_Animation")]
+    pub animation: FileReference,
+
+    #[serde(default, alias = "m_Animations")]
+    pub animations: Vec<FileReference>,
+
+    #[serde(default = "default_one", alias = "m_PlayAutomatically")]
+    pub play_automatically: u8,
+}
+
+impl UnityLegacyAnimation {
+    pub fn add_legacy_animation(&self, commands: &mut EntityCommands) {
+        commands.insert(UnityLegacyAnimationExtra {
+            animation: self.clone(),
+        });
+        commands.insert(UnityLegacyAnimationRequiresSetup);
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct UnityLegacyAnimationExtra {
+    pub animation: UnityLegacyAnimation,
+}
+
+#[derive(Component, Debug)]
+pub struct UnityLegacyAnimationRequiresSetup;
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::FileReference;
+
+/// The objects of a `.controller` file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "object_type")]
+pub enum UnityAnimatorControllerObject {
+    AnimatorController(UnityAnimatorControllerAsset),
+    AnimatorStateMachine(UnityAnimatorStateMachine),
+    AnimatorState(UnityAnimatorState),
+    AnimatorStateTransition(UnityAnimatorTransition),
+    /// Entry transitions, always followed to the default state.
+    AnimatorTransition(UnityAnimatorTransition),
+    #[serde(other)]
+    DontCare,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAnimatorControllerAsset {
+    #[serde(alias = "m_Name")]
+    pub name: String,
+    #[serde(default, alias = "m_AnimatorParameters")]
+    pub parameters: Vec<UnityAnimatorParameter>,
+    #[serde(default, alias = "m_AnimatorLayers")]
+    pub layers: Vec<UnityAnimatorLayer>,
+}
+
+// UnityEngine.AnimatorControllerParameterType
+pub const PARAMETER_FLOAT: u8 = 1;
+pub const PARAMETER_INT: u8 = 3;
+pub const PARAMETER_BOOL: u8 = 4;
+pub const PARAMETER_TRIGGER: u8 = 9;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAnimatorParameter {
+    #[serde(alias = "m_Name")]
+    pub name: String,
+    #[serde(alias = "m_Type")]
+    pub parameter_type: u8,
+    #[serde(default, alias = "m_DefaultFloat")]
+    pub default_float: f32,
+    #[serde(default, alias = "m_DefaultInt")]
+    pub default_int: i32,
+    #[serde(default, alias = "m_DefaultBool")]
+    pub default_bool: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAnimatorLayer {
+    #[serde(alias = "m_Name")]
+    pub name: String,
+    #[serde(alias = "m_StateMachine")]
+    pub state_machine: FileReference,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAnimatorStateMachine {
+    #[serde(alias = "m_Name")]
+    pub name: String,
+    #[serde(default, alias = "m_ChildStates")]
+    pub child_states: Vec<UnityChildAnimatorState>,
+    #[serde(default, alias = "m_ChildStateMachines")]
+    pub child_state_machines: Vec<UnityChildAnimatorStateMachine>,
+    #[serde(default, alias = "m_AnyStateTransitions")]
+    pub any_state_transitions: Vec<FileReference>,
+    #[serde(default, alias = "m_DefaultState")]
+    pub default_state: FileReference,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityChildAnimatorState {
+    #[serde(alias = "m_State")]
+    pub state: FileReference,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityChildAnimatorStateMachine {
+    #[serde(alias = "m_StateMachine")]
+    pub state_machine: FileReference,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAnimatorState {
+    #[serde(alias = "m_Name")]
+    pub name: String,
+    #[serde(default = "default_speed", alias = "m_Speed")]
+    pub speed: f32,
+    #[serde(default, alias = "m_Transitions")]
+    pub transitions: Vec<FileReference>,
+    /// A clip, in a model or `.anim` file, or a blend tree.
+    #[serde(default, alias = "m_Motion")]
+    pub motion: FileReference,
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAnimatorTransition {
+    #[serde(default, alias = "m_Conditions")]
+    pub conditions: Vec<UnityAnimatorCondition>,
+    #[serde(default, alias = "m_DstState")]
+    pub destination_state: FileReference,
+    #[serde(default, alias = "m_DstStateMachine")]
+    pub destination_state_machine: FileReference,
+    #[serde(default, alias = "m_Solo")]
+    pub solo: u8,
+    #[serde(default, alias = "m_Mute")]
+    pub mute: u8,
+    #[serde(default, alias = "m_IsExit")]
+    pub is_exit: u8,
+    #[serde(default, alias = "m_TransitionDuration")]
+    pub duration: f32,
+    #[serde(default, alias = "m_TransitionOffset")]
+    pub offset: f32,
+    #[serde(default, alias = "m_ExitTime")]
+    pub exit_time: f32,
+    #[serde(default, alias = "m_HasExitTime")]
+    pub has_exit_time: u8,
+    #[serde(default, alias = "m_HasFixedDuration")]
+    pub has_fixed_duration: u8,
+    #[serde(
+        default = "default_can_transition_to_self",
+        alias = "m_CanTransitionToSelf"
+    )]
+    pub can_transition_to_self: u8,
+}
+
+fn default_can_transition_to_self() -> u8 {
+    1
+}
+
+// UnityEditor.Animations.AnimatorConditionMode
+pub const CONDITION_IF: u8 = 1;
+pub const CONDITION_IF_NOT: u8 = 2;
+pub const CONDITION_GREATER: u8 = 3;
+pub const CONDITION_LESS: u8 = 4;
+pub const CONDITION_EQUALS: u8 = 6;
+pub const CONDITION_NOT_EQUAL: u8 = 7;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAnimatorCondition {
+    #[serde(alias = "m_ConditionMode")]
+    pub mode: u8,
+    /// The parameter's name.
+    #[serde(alias = "m_ConditionEvent")]
+    pub parameter: String,
+    /// Misspelled by unity.
+    #[serde(default, alias = "m_EventTreshold")]
+    pub threshold: f32,
+}
+
+/// A controller's base layer with sub state machines flattened.
+#[derive(Debug, Clone, Default)]
+pub struct AnimatorControllerGraph {
+    pub parameters: Vec<UnityAnimatorParameter>,
+    pub states: Vec<AnimatorGraphState>,
+    pub default_state: usize,
+    pub any_state_transitions: Vec<AnimatorGraphTransition>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimatorGraphState {
+    pub name: String,
+    pub speed: f32,
+    pub motion: FileReference,
+    pub transitions: Vec<AnimatorGraphTransition>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimatorGraphTransition {
+    pub destination: usize,
+    pub transition: UnityAnimatorTransition,
+}
+
+impl AnimatorControllerGraph {
+    /// Builds the graph of the controller's first layer, other layers are
+    /// ignored.
+    pub fn new(objects: &HashMap<i64, UnityAnimatorControllerObject>) -> Option<Self> {
+        let controller = objects.values().find_map(|object| match object {
+            UnityAnimatorControllerObject::AnimatorController(c) => Some(c),
+            _ => None,
+        })?;
+        let root = controller.layers.first()?.state_machine.file_id;
+        let state_machine = |id: i64| match objects.get(&id) {
+            Some(UnityAnimatorControllerObject::AnimatorStateMachine(m)) => Some(m),
+            _ => None,
+        };
+
+        // every state of every nested state machine, and where each machine
+        // enters
+        let mut state_ids = vec![];
+        let mut machine_defaults = HashMap::new();
+        let mut any_state_transitions = vec![];
+        let mut machines = vec![root];
+        while let Some(id) = machines.pop() {
+            let Some(machine) = state_machine(id) else {
+                continue;
+            };
+            state_ids.extend(machine.child_states.iter().map(|c| c.state.file_id));
+            machine_defaults.insert(id, machine.default_state.file_id);
+            any_state_transitions.extend(machine.any_state_transitions.iter().map(|t| t.file_id));
+            machines.extend(
+                machine
+                    .child_state_machines
+                    .iter()
+                    .map(|c| c.state_machine.file_id),
+            );
+        }
+
+        let index_of = |id: i64| state_ids.iter().position(|s| *s == id);
+        let default_state = machine_defaults
+            .get(&root)
+            .and_then(|id| index_of(*id))
+            .unwrap_or_default();
+
+        let transition = |id: &i64| {
+            let (UnityAnimatorControllerObject::AnimatorStateTransition(t)
+            | UnityAnimatorControllerObject::AnimatorTransition(t)) = objects.get(id)?
+            else {
+                return None;
+            };
+
+            let destination = if t.is_exit != 0 {
+                default_state
+            } else if t.destination_state.file_id != 0 {
+                index_of(t.destination_state.file_id)?
+            } else {
+                let entry = machine_defaults.get(&t.destination_state_machine.file_id)?;
+                index_of(*entry)?
+            };
+
+            Some(AnimatorGraphTransition {
+                destination,
+                transition: t.clone(),
+            })
+        };
+
+        let states = state_ids
+            .iter()
+            .filter_map(|id| match objects.get(id) {
+                Some(UnityAnimatorControllerObject::AnimatorState(s)) => Some(AnimatorGraphState {
+                    name: s.name.clone(),
+                    speed: s.speed,
+                    motion: s.motion.clone(),
+                    transitions: s
+                        .transitions
+                        .iter()
+                        .filter_map(|t| transition(&t.file_id))
+                        .collect(),
+                }),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if states.len() != state_ids.len() {
+            // indices would be off
+            return None;
+        }
+
+        Some(Self {
+            parameters: controller.parameters.clone(),
+            states,
+            default_state,
+            any_state_transitions: any_state_transitions
+                .iter()
+                .filter_map(transition)
+                .collect(),
+        })
+    }
+}
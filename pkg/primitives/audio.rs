@@ -0,0 +1,260 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    ecs::system::EntityCommands,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{CurveFloat, FileReference, UnityAnimationCurve};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAudioSource {
+    #[serde(default = "default_one_u8", alias = "m_Enabled")]
+    pub enabled: u8,
+
+    #[serde(default, alias = "m_audioClip")]
+    pub clip: FileReference,
+
+    /// Group in a `.mixer` asset the source plays through.
+    #[serde(default, alias = "OutputAudioMixerGroup")]
+    pub output_mixer_group: FileReference,
+
+    #[serde(default = "default_one_u8", alias = "m_PlayOnAwake")]
+    pub play_on_awake: u8,
+
+    #[serde(default = "default_one", alias = "m_Volume")]
+    pub volume: f32,
+
+    /// Playback speed, bevy has no separate pitch.
+    #[serde(default = "default_one", alias = "m_Pitch")]
+    pub pitch: f32,
+
+    #[serde(default, alias = "Loop")]
+    pub looping: u8,
+
+    #[serde(default, alias = "Mute")]
+    pub mute: u8,
+
+    #[serde(default = "default_one", alias = "MinDistance")]
+    pub min_distance: f32,
+
+    #[serde(default = "default_max_distance", alias = "MaxDistance")]
+    pub max_distance: f32,
+
+    /// 0 logarithmic, 1 linear. Custom curves are treated as logarithmic.
+    #[serde(default, alias = "rolloffMode")]
+    pub rolloff_mode: i32,
+
+    /// Spatial blend over distance, unity only lets the inspector set a
+    /// constant.
+    #[serde(default, alias = "panLevelCustomCurve")]
+    pub spatial_blend_curve: Option<UnityAnimationCurve<CurveFloat>>,
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn default_one_u8() -> u8 {
+    1
+}
+
+fn default_max_distance() -> f32 {
+    500.0
+}
+
+const ROLLOFF_LINEAR: i32 = 1;
+
+impl UnityAudioSource {
+    pub fn add_audio_source(&self, commands: &mut EntityCommands) {
+        commands.insert(UnityAudioSourceExtra {
+            source: self.clone(),
+        });
+        commands.insert(UnityAudioSourceRequiresLoad);
+    }
+
+    /// 0 plays the clip flat, 1 fully positioned in the world.
+    pub fn spatial_blend(&self) -> f32 {
+        self.spatial_blend_curve
+            .as_ref()
+            .and_then(|curve| curve.sample(0.0))
+            .map(|CurveFloat(blend)| blend.clamp(0.0, 1.0))
+            .unwrap_or_default()
+    }
+
+    /// Volume scale at `distance` from the listener, from the rolloff curve
+    /// blended by the spatial blend.
+    pub fn rolloff(&self, distance: f32) -> f32 {
+        let min = self.min_distance.max(f32::EPSILON);
+        let max = self.max_distance.max(min);
+        let distance = distance.clamp(min, max);
+
+        let attenuation = if self.rolloff_mode == ROLLOFF_LINEAR {
+            1.0 - (distance - min) / (max - min).max(f32::EPSILON)
+        } else {
+            min / distance
+        };
+
+        let blend = self.spatial_blend();
+        1.0 - blend + blend * attenuation
+    }
+
+    pub fn playback_settings(&self) -> PlaybackSettings {
+        let mode = if self.looping != 0 {
+            PlaybackMode::Loop
+        } else {
+            PlaybackMode::Once
+        };
+        let volume = if self.mute != 0 { 0.0 } else { self.volume };
+
+        PlaybackSettings {
+            mode,
+            volume: Volume::new_relative(volume),
+            speed: self.pitch,
+            paused: self.enabled == 0 || self.play_on_awake == 0,
+            spatial: self.spatial_blend() > 0.0,
+        }
+    }
+}
+
+/// The source's settings. Sink volumes follow `source.volume` and
+/// `source.mute`, so game code changes them here rather than on the sink.
+#[derive(Component, Debug)]
+pub struct UnityAudioSourceExtra {
+    pub source: UnityAudioSource,
+}
+
+/// Waits for the clip's path to be resolved through the asset maps.
+#[derive(Component, Debug)]
+pub struct UnityAudioSourceRequiresLoad;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct UnityAudioListener {
+    #[serde(default = "default_one_u8", alias = "m_Enabled")]
+    pub enabled: u8,
+}
+
+/// Distance between the listener's ears, roughly a head's width.
+const LISTENER_EAR_GAP: f32 = 0.2;
+
+impl UnityAudioListener {
+    pub fn add_audio_listener(&self, commands: &mut EntityCommands) {
+        if self.enabled != 0 {
+            commands.insert(SpatialListener::new(LISTENER_EAR_GAP));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UnityKeyframe;
+
+    fn source(spatial_blend: Option<f32>, rolloff_mode: i32) -> UnityAudioSource {
+        let key = |value| UnityKeyframe {
+            time: 0.0,
+            value: CurveFloat(value),
+            in_slope: CurveFloat(0.0),
+            out_slope: CurveFloat(0.0),
+        };
+
+        UnityAudioSource {
+            enabled: 1,
+            clip: default(),
+            output_mixer_group: default(),
+            play_on_awake: 1,
+            volume: 0.5,
+            pitch: 1.0,
+            looping: 0,
+            mute: 0,
+            min_distance: 2.0,
+            max_distance: 20.0,
+            rolloff_mode,
+            spatial_blend_curve: spatial_blend.map(|blend| UnityAnimationCurve {
+                keys: vec![key(blend)],
+            }),
+        }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_spatial_blend() {
+        assert_eq!(source(None, 0).spatial_blend(), 0.0);
+        assert_eq!(source(Some(0.25), 0).spatial_blend(), 0.25);
+        assert_eq!(source(Some(1.5), 0).spatial_blend(), 1.0);
+    }
+
+    #[test]
+    fn test_logarithmic_rolloff() {
+        let source = source(Some(1.0), 0);
+        assert_close(source.rolloff(0.5), 1.0);
+        assert_close(source.rolloff(2.0), 1.0);
+        assert_close(source.rolloff(4.0), 0.5);
+        assert_close(source.rolloff(20.0), 0.1);
+        // past the max distance the volume stays where it got to
+        assert_close(source.rolloff(100.0), 0.1);
+    }
+
+    #[test]
+    fn test_linear_rolloff() {
+        let source = source(Some(1.0), ROLLOFF_LINEAR);
+        assert_close(source.rolloff(2.0), 1.0);
+        assert_close(source.rolloff(11.0), 0.5);
+        assert_close(source.rolloff(20.0), 0.0);
+        assert_close(source.rolloff(100.0), 0.0);
+    }
+
+    #[test]
+    fn test_rolloff_follows_spatial_blend() {
+        // flat sources don't fade, half spatial ones fade halfway
+        for mode in [0, ROLLOFF_LINEAR] {
+            assert_close(source(None, mode).rolloff(20.0), 1.0);
+            assert_close(source(Some(0.0), mode).rolloff(20.0), 1.0);
+        }
+        assert_close(source(Some(0.5), ROLLOFF_LINEAR).rolloff(20.0), 0.5);
+        assert_close(source(Some(0.5), 0).rolloff(20.0), 0.55);
+    }
+
+    #[test]
+    fn test_playback_settings() {
+        let relative_volume = |settings: &PlaybackSettings| match settings.volume {
+            Volume::Relative(level) => level.get(),
+            Volume::Absolute(_) => panic!("source volumes are relative to the global volume"),
+        };
+
+        let settings = source(None, 0).playback_settings();
+        assert!(matches!(settings.mode, PlaybackMode::Once));
+        assert_eq!(relative_volume(&settings), 0.5);
+        assert_eq!(settings.speed, 1.0);
+        assert!(!settings.paused);
+        assert!(!settings.spatial);
+
+        let settings = UnityAudioSource {
+            looping: 1,
+            mute: 1,
+            pitch: 1.5,
+            play_on_awake: 0,
+            ..source(Some(1.0), 0)
+        }
+        .playback_settings();
+        assert!(matches!(settings.mode, PlaybackMode::Loop));
+        assert_eq!(relative_volume(&settings), 0.0);
+        assert_eq!(settings.speed, 1.5);
+        assert!(settings.paused);
+        assert!(settings.spatial);
+
+        let disabled = UnityAudioSource {
+            enabled: 0,
+            ..source(None, 0)
+        };
+        assert!(disabled.playback_settings().paused);
+    }
+}
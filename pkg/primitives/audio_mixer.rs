@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::FileReference;
+
+/// The objects of a `.mixer` file. Effects other than the group's volume are
+/// ignored.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "object_type")]
+pub enum UnityAudioMixerObject {
+    AudioMixerController(UnityAudioMixerController),
+    AudioMixerGroupController(UnityAudioMixerGroup),
+    AudioMixerSnapshotController(UnityAudioMixerSnapshot),
+    #[serde(other)]
+    DontCare,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAudioMixerController {
+    #[serde(alias = "m_Name")]
+    pub name: String,
+    #[serde(alias = "m_MasterGroup")]
+    pub master_group: FileReference,
+    #[serde(default, alias = "m_Snapshots")]
+    pub snapshots: Vec<FileReference>,
+    #[serde(default, alias = "m_StartSnapshot")]
+    pub start_snapshot: FileReference,
+    /// Parameters scripts reach by name through `AudioMixer.SetFloat`.
+    #[serde(default, alias = "m_ExposedParameters")]
+    pub exposed_parameters: Vec<UnityAudioMixerExposedParameter>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAudioMixerExposedParameter {
+    #[serde(deserialize_with = "deserialize_parameter_guid")]
+    pub guid: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAudioMixerGroup {
+    #[serde(alias = "m_Name")]
+    pub name: String,
+    #[serde(default, alias = "m_Children")]
+    pub children: Vec<FileReference>,
+    /// Guid of the parameter holding the group's volume in decibels.
+    #[serde(
+        default,
+        alias = "m_Volume",
+        deserialize_with = "deserialize_parameter_guid"
+    )]
+    pub volume: String,
+    #[serde(default, alias = "m_Mute")]
+    pub mute: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityAudioMixerSnapshot {
+    #[serde(alias = "m_Name")]
+    pub name: String,
+    /// Parameter values by parameter guid, parameters left out are at their
+    /// default.
+    #[serde(default, alias = "m_FloatValues")]
+    pub float_values: HashMap<String, f32>,
+}
+
+/// Parameter guids are hex, the odd one made of digits reads as a number.
+fn deserialize_parameter_guid<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Guid {
+        Text(String),
+        Integer(u64),
+        Other(serde::de::IgnoredAny),
+    }
+
+    Ok(match Guid::deserialize(deserializer)? {
+        Guid::Text(guid) => guid,
+        Guid::Integer(guid) => format!("{:032}", guid),
+        Guid::Other(_) => String::new(),
+    })
+}
+
+/// Unity's decibel range for mixer volumes, -80 dB is silent.
+pub const MIXER_MIN_DECIBELS: f32 = -80.0;
+pub const MIXER_MAX_DECIBELS: f32 = 20.0;
+
+pub fn decibels_to_linear(decibels: f32) -> f32 {
+    if decibels <= MIXER_MIN_DECIBELS {
+        return 0.0;
+    }
+    10f32.powf(decibels / 20.0)
+}
+
+pub fn linear_to_decibels(linear: f32) -> f32 {
+    if linear <= 0.0 {
+        return MIXER_MIN_DECIBELS;
+    }
+    (20.0 * linear.log10()).clamp(MIXER_MIN_DECIBELS, MIXER_MAX_DECIBELS)
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::FileReference;
+
+/// `unity default resources`, the built-in meshes and textures.
+pub const DEFAULT_RESOURCES_GUID: &str = "0000000000000000e000000000000000";
+/// `unity_builtin_extra`, the built-in shaders and materials.
+pub const BUILTIN_EXTRA_GUID: &str = "0000000000000000f000000000000000";
+
+impl FileReference {
+    /// Whether this points into one of unity's built-in resource files, which
+    /// don't exist in the project.
+    pub fn is_builtin(&self) -> bool {
+        matches!(
+            self.guid.as_deref(),
+            Some(DEFAULT_RESOURCES_GUID | BUILTIN_EXTRA_GUID)
+        )
+    }
+
+    /// Key for caches of assets that may be built-in, where many share a guid.
+    pub fn unique_id(&self) -> Option<String> {
+        let guid = self.guid.as_ref()?;
+        if self.is_builtin() {
+            Some(format!("{}_{}", guid, self.file_id))
+        } else {
+            Some(guid.clone())
+        }
+    }
+}
+
+/// Built-in materials new unity objects are created with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinMaterial {
+    /// Default-Particle
+    Particle,
+    /// Default-Diffuse, the legacy diffuse shader without specular.
+    Diffuse,
+    /// Default-Material, given to primitives created in the editor.
+    Default,
+    /// Default-Skybox, unity's procedural sky.
+    Skybox,
+    /// Sprites-Default
+    Sprite,
+}
+
+impl BuiltinMaterial {
+    pub fn from_reference(reference: &FileReference) -> Option<Self> {
+        if reference.guid.as_deref() != Some(BUILTIN_EXTRA_GUID) {
+            return None;
+        }
+
+        match reference.file_id {
+            10301 => Some(BuiltinMaterial::Particle),
+            10302 => Some(BuiltinMaterial::Diffuse),
+            10303 => Some(BuiltinMaterial::Default),
+            10304 => Some(BuiltinMaterial::Skybox),
+            10754 => Some(BuiltinMaterial::Sprite),
+            _ => None,
+        }
+    }
+
+    /// `None` for the skybox, which cameras fall back to a procedural sky for.
+    pub fn standard_material(&self) -> Option<StandardMaterial> {
+        match self {
+            BuiltinMaterial::Particle => Some(StandardMaterial {
+                unlit: true,
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
+            BuiltinMaterial::Diffuse => Some(StandardMaterial {
+                perceptual_roughness: 1.0,
+                ..default()
+            }),
+            BuiltinMaterial::Default => Some(StandardMaterial {
+                base_color: Color::WHITE,
+                metallic: 0.0,
+                perceptual_roughness: 0.5,
+                ..default()
+            }),
+            BuiltinMaterial::Skybox => None,
+            BuiltinMaterial::Sprite => Some(StandardMaterial {
+                unlit: true,
+                alpha_mode: AlphaMode::Blend,
+                cull_mode: None,
+                double_sided: true,
+                ..default()
+            }),
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+use crate::{FileReference, DEFAULT_RESOURCES_GUID};
+
+/// Unity's built-in primitive meshes, from `unity default resources`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinMesh {
+    Cube,
+    Cylinder,
+    Sphere,
+    Capsule,
+    Plane,
+    Quad,
+}
+
+// segments of unity's round primitives
+const SPHERE_SEGMENTS: usize = 24;
+const SPHERE_RINGS: usize = 16;
+const CYLINDER_SEGMENTS: usize = 20;
+const CAPSULE_SEGMENTS: usize = 24;
+const CAPSULE_CAP_RINGS: usize = 8;
+
+impl BuiltinMesh {
+    pub fn from_reference(reference: &FileReference) -> Option<Self> {
+        if reference.guid.as_deref() != Some(DEFAULT_RESOURCES_GUID) {
+            return None;
+        }
+
+        match reference.file_id {
+            10202 => Some(BuiltinMesh::Cube),
+            10206 => Some(BuiltinMesh::Cylinder),
+            10207 => Some(BuiltinMesh::Sphere),
+            10208 => Some(BuiltinMesh::Capsule),
+            10209 => Some(BuiltinMesh::Plane),
+            10210 => Some(BuiltinMesh::Quad),
+            _ => None,
+        }
+    }
+
+    /// Name of the primitive, as in `Resources.GetBuiltinResource`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinMesh::Cube => "Cube",
+            BuiltinMesh::Cylinder => "Cylinder",
+            BuiltinMesh::Sphere => "Sphere",
+            BuiltinMesh::Capsule => "Capsule",
+            BuiltinMesh::Plane => "Plane",
+            BuiltinMesh::Quad => "Quad",
+        }
+    }
+
+    /// A mesh with unity's dimensions and uv layout, converted to bevy's
+    /// right handed space. The round primitives approximate unity's
+    /// tessellation, see `read_builtin_mesh` for the exact ones.
+    pub fn mesh(&self) -> Mesh {
+        let mut builder = UnityMeshBuilder::default();
+        match self {
+            BuiltinMesh::Cube => cube(&mut builder),
+            BuiltinMesh::Cylinder => cylinder(&mut builder),
+            BuiltinMesh::Sphere => sphere(&mut builder),
+            BuiltinMesh::Capsule => capsule(&mut builder),
+            BuiltinMesh::Plane => plane(&mut builder),
+            BuiltinMesh::Quad => quad(&mut builder),
+        }
+
+        builder.build()
+    }
+}
+
+/// Collects vertices in unity's space and converts them on build: z is
+/// flipped, v runs top down and triangles wind counter clockwise.
+#[derive(Default)]
+struct UnityMeshBuilder {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    indices: Vec<u32>,
+}
+
+impl UnityMeshBuilder {
+    fn vertex(&mut self, position: Vec3, normal: Vec3, uv: Vec2) -> u32 {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+        (self.positions.len() - 1) as u32
+    }
+
+    /// Adds a triangle facing the way its vertex normals point.
+    fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        let [pa, pb, pc] = [a, b, c].map(|i| to_bevy(self.positions[i as usize]));
+        let normal: Vec3 = [a, b, c]
+            .iter()
+            .map(|i| to_bevy(self.normals[*i as usize]))
+            .sum();
+
+        if (pb - pa).cross(pc - pa).dot(normal) < 0.0 {
+            self.indices.extend([a, c, b]);
+        } else {
+            self.indices.extend([a, b, c]);
+        }
+    }
+
+    /// A grid of `columns + 1` vertices per row, rows laid out one after another.
+    fn grid(&mut self, first: u32, rows: usize, columns: usize) {
+        let stride = columns as u32 + 1;
+        for row in 0..rows as u32 {
+            for column in 0..columns as u32 {
+                let a = first + row * stride + column;
+                let b = a + 1;
+                let c = a + stride + 1;
+                let d = a + stride;
+
+                // skip the degenerate halves at the poles
+                if self.positions[a as usize] != self.positions[b as usize] {
+                    self.triangle(a, b, c);
+                }
+                if self.positions[c as usize] != self.positions[d as usize] {
+                    self.triangle(a, c, d);
+                }
+            }
+        }
+    }
+
+    fn build(self) -> Mesh {
+        let positions: Vec<[f32; 3]> = self
+            .positions
+            .into_iter()
+            .map(|p| to_bevy(p).to_array())
+            .collect();
+        let normals: Vec<[f32; 3]> = self
+            .normals
+            .into_iter()
+            .map(|n| to_bevy(n).to_array())
+            .collect();
+        let uvs: Vec<[f32; 2]> = self.uvs.into_iter().map(|uv| [uv.x, 1.0 - uv.y]).collect();
+
+        Mesh::new(PrimitiveTopology::TriangleList)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+            .with_indices(Some(Indices::U32(self.indices)))
+    }
+}
+
+fn to_bevy(v: Vec3) -> Vec3 {
+    Vec3::new(v.x, v.y, -v.z)
+}
+
+/// Unity's cube, every face mapped to the whole texture. The top and back
+/// faces come out rotated, like they do in unity.
+fn cube(builder: &mut UnityMeshBuilder) {
+    #[rustfmt::skip]
+    const POSITIONS: [[f32; 3]; 24] = [
+        [0.5, -0.5, 0.5], [-0.5, -0.5, 0.5], [0.5, 0.5, 0.5], [-0.5, 0.5, 0.5],
+        [0.5, 0.5, -0.5], [-0.5, 0.5, -0.5], [0.5, -0.5, -0.5], [-0.5, -0.5, -0.5],
+        [0.5, 0.5, 0.5], [-0.5, 0.5, 0.5], [0.5, 0.5, -0.5], [-0.5, 0.5, -0.5],
+        [0.5, -0.5, -0.5], [0.5, -0.5, 0.5], [-0.5, -0.5, 0.5], [-0.5, -0.5, -0.5],
+        [-0.5, -0.5, 0.5], [-0.5, 0.5, 0.5], [-0.5, 0.5, -0.5], [-0.5, -0.5, -0.5],
+        [0.5, -0.5, -0.5], [0.5, 0.5, -0.5], [0.5, 0.5, 0.5], [0.5, -0.5, 0.5],
+    ];
+    #[rustfmt::skip]
+    const UVS: [[f32; 2]; 24] = [
+        [0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0],
+        [0.0, 1.0], [1.0, 1.0], [0.0, 1.0], [1.0, 1.0],
+        [0.0, 0.0], [1.0, 0.0], [0.0, 0.0], [1.0, 0.0],
+        [0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0],
+        [0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0],
+        [0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0],
+    ];
+    // the vertices of each face and its normal
+    const FACES: [([u32; 4], [f32; 3]); 6] = [
+        ([0, 2, 3, 1], [0.0, 0.0, 1.0]),
+        ([8, 4, 5, 9], [0.0, 1.0, 0.0]),
+        ([10, 6, 7, 11], [0.0, 0.0, -1.0]),
+        ([12, 13, 14, 15], [0.0, -1.0, 0.0]),
+        ([16, 17, 18, 19], [-1.0, 0.0, 0.0]),
+        ([20, 21, 22, 23], [1.0, 0.0, 0.0]),
+    ];
+
+    let mut normals = [Vec3::ZERO; 24];
+    for (vertices, normal) in FACES {
+        for vertex in vertices {
+            normals[vertex as usize] = Vec3::from(normal);
+        }
+    }
+
+    for ((position, uv), normal) in POSITIONS.iter().zip(UVS).zip(normals) {
+        builder.vertex(Vec3::from(*position), normal, Vec2::from(uv));
+    }
+
+    for ([a, b, c, d], _) in FACES {
+        builder.triangle(a, b, c);
+        builder.triangle(a, c, d);
+    }
+}
+
+/// 10 by 10 units facing up, with the texture rotated half a turn from the
+/// quad's.
+fn plane(builder: &mut UnityMeshBuilder) {
+    const CELLS: usize = 10;
+
+    for row in 0..=CELLS {
+        for column in 0..=CELLS {
+            let uv = Vec2::new(column as f32, row as f32) / CELLS as f32;
+            let position = Vec3::new(5.0 - column as f32, 0.0, 5.0 - row as f32);
+            builder.vertex(position, Vec3::Y, uv);
+        }
+    }
+
+    builder.grid(0, CELLS, CELLS);
+}
+
+/// 1 by 1 unit facing -z.
+fn quad(builder: &mut UnityMeshBuilder) {
+    for (x, y) in [(-0.5, -0.5), (0.5, -0.5), (-0.5, 0.5), (0.5, 0.5)] {
+        let uv = Vec2::new(x + 0.5, y + 0.5);
+        builder.vertex(Vec3::new(x, y, 0.0), Vec3::NEG_Z, uv);
+    }
+
+    builder.triangle(0, 3, 1);
+    builder.triangle(3, 0, 2);
+}
+
+/// Radius 0.5, u around the equator and v from the bottom pole to the top.
+fn sphere(builder: &mut UnityMeshBuilder) {
+    for ring in 0..=SPHERE_RINGS {
+        let v = ring as f32 / SPHERE_RINGS as f32;
+        let latitude = v * PI - FRAC_PI_2;
+        for segment in 0..=SPHERE_SEGMENTS {
+            let u = segment as f32 / SPHERE_SEGMENTS as f32;
+            let normal = around(u, latitude);
+            builder.vertex(normal * 0.5, normal, Vec2::new(u, v));
+        }
+    }
+
+    builder.grid(0, SPHERE_RINGS, SPHERE_SEGMENTS);
+}
+
+/// Radius 0.5 and 2 units tall, caps mapped flat.
+fn cylinder(builder: &mut UnityMeshBuilder) {
+    for y in [-1.0, 1.0] {
+        for segment in 0..=CYLINDER_SEGMENTS {
+            let u = segment as f32 / CYLINDER_SEGMENTS as f32;
+            let normal = around(u, 0.0);
+            let position = normal * 0.5 + Vec3::Y * y;
+            builder.vertex(position, normal, Vec2::new(u, (y + 1.0) / 2.0));
+        }
+    }
+    builder.grid(0, 1, CYLINDER_SEGMENTS);
+
+    for (y, normal) in [(-1.0, Vec3::NEG_Y), (1.0, Vec3::Y)] {
+        let center = builder.vertex(Vec3::Y * y, normal, Vec2::splat(0.5));
+        for segment in 0..=CYLINDER_SEGMENTS {
+            let rim = around(segment as f32 / CYLINDER_SEGMENTS as f32, 0.0) * 0.5;
+            let uv = Vec2::new(rim.x, rim.z) + 0.5;
+            builder.vertex(rim + Vec3::Y * y, normal, uv);
+        }
+        for segment in 0..CYLINDER_SEGMENTS as u32 {
+            builder.triangle(center, center + segment + 1, center + segment + 2);
+        }
+    }
+}
+
+/// Radius 0.5 and 2 units tall, v running over the whole height.
+fn capsule(builder: &mut UnityMeshBuilder) {
+    // bottom cap from its pole up, then the top cap from its equator
+    let rings = (0..=CAPSULE_CAP_RINGS)
+        .map(|ring| {
+            (
+                -0.5,
+                (ring as f32 / CAPSULE_CAP_RINGS as f32 - 1.0) * FRAC_PI_2,
+            )
+        })
+        .chain(
+            (0..=CAPSULE_CAP_RINGS)
+                .map(|ring| (0.5, ring as f32 / CAPSULE_CAP_RINGS as f32 * FRAC_PI_2)),
+        );
+
+    for (center, latitude) in rings {
+        for segment in 0..=CAPSULE_SEGMENTS {
+            let u = segment as f32 / CAPSULE_SEGMENTS as f32;
+            let normal = around(u, latitude);
+            let position = normal * 0.5 + Vec3::Y * center;
+            builder.vertex(position, normal, Vec2::new(u, (position.y + 1.0) / 2.0));
+        }
+    }
+
+    builder.grid(0, CAPSULE_CAP_RINGS * 2 + 1, CAPSULE_SEGMENTS);
+}
+
+/// Unit direction `u` of the way around the y axis at `latitude`.
+fn around(u: f32, latitude: f32) -> Vec3 {
+    let radius = latitude.cos();
+    if radius.abs() < 1e-6 {
+        // exactly on the pole, so its triangles are recognised as degenerate
+        return Vec3::Y * latitude.signum();
+    }
+
+    let angle = u * TAU;
+    Vec3::new(radius * angle.cos(), latitude.sin(), radius * angle.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::mesh::VertexAttributeValues;
+
+    fn attributes(mesh: &Mesh) -> (Vec<Vec3>, Vec<Vec2>) {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("no positions");
+        };
+        let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+        else {
+            panic!("no uvs");
+        };
+        assert_eq!(positions.len(), uvs.len());
+
+        (
+            positions.iter().copied().map(Vec3::from).collect(),
+            uvs.iter().copied().map(Vec2::from).collect(),
+        )
+    }
+
+    fn has_vertex(mesh: &Mesh, position: Vec3, uv: Vec2) -> bool {
+        let (positions, uvs) = attributes(mesh);
+        positions
+            .iter()
+            .zip(&uvs)
+            .any(|(p, u)| p.abs_diff_eq(position, 1e-5) && u.abs_diff_eq(uv, 1e-5))
+    }
+
+    #[test]
+    fn test_round_meshes_match_unity_dimensions() {
+        // radius 0.5, v runs from the bottom pole to the top one
+        let sphere = BuiltinMesh::Sphere.mesh();
+        let (positions, _) = attributes(&sphere);
+        assert!(positions.iter().all(|p| (p.length() - 0.5).abs() < 1e-5));
+        assert!(has_vertex(&sphere, Vec3::Y * 0.5, Vec2::new(0.0, 0.0)));
+        assert!(has_vertex(&sphere, Vec3::NEG_Y * 0.5, Vec2::new(0.0, 1.0)));
+
+        // 2 units tall with radius 0.5, 20 sides and 80 triangles like unity's
+        let cylinder = BuiltinMesh::Cylinder.mesh();
+        let (positions, _) = attributes(&cylinder);
+        assert_eq!(cylinder.indices().unwrap().len(), 80 * 3);
+        assert!(positions.iter().all(|p| p.y.abs() == 1.0));
+        assert!(positions
+            .iter()
+            .all(|p| Vec2::new(p.x, p.z).length() <= 0.5 + 1e-5));
+        assert!(has_vertex(&cylinder, Vec3::Y, Vec2::splat(0.5)));
+        assert!(has_vertex(
+            &cylinder,
+            Vec3::new(0.5, -1.0, 0.0),
+            Vec2::new(0.0, 1.0)
+        ));
+
+        // 2 units tall, hemispheres of radius 0.5 half a unit from the center
+        let capsule = BuiltinMesh::Capsule.mesh();
+        let (positions, _) = attributes(&capsule);
+        assert!(positions.iter().all(|p| p.y.abs() <= 1.0 + 1e-5));
+        assert!(positions.iter().all(|p| {
+            let center = Vec3::Y * p.y.clamp(-0.5, 0.5);
+            (p.distance(center) - 0.5).abs() < 1e-5
+        }));
+        assert!(has_vertex(&capsule, Vec3::Y, Vec2::new(0.0, 0.0)));
+        assert!(has_vertex(
+            &capsule,
+            Vec3::new(0.5, 0.5, 0.0),
+            Vec2::new(0.0, 0.25)
+        ));
+    }
+
+    #[test]
+    fn test_quad_faces_bevy_forward() {
+        let mesh = BuiltinMesh::Quad.mesh();
+        let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        else {
+            panic!("no normals");
+        };
+
+        // unity's -z, towards a default camera
+        assert_eq!(normals[0], [0.0, 0.0, 1.0]);
+    }
+}
@@ -1,17 +1,16 @@
 use bevy::{
     asset::LoadState,
-    core_pipeline::Skybox,
+    core_pipeline::{clear_color::ClearColorConfig, core_3d::Camera3dDepthLoadOp, Skybox},
     ecs::system::EntityCommands,
-    pbr::ScreenSpaceAmbientOcclusionBundle,
     prelude::*,
     render::{
-        camera::ScalingMode,
+        camera::{ScalingMode, Viewport},
         render_resource::{TextureViewDescriptor, TextureViewDimension},
     },
 };
 use serde::{Deserialize, Serialize};
 
-use crate::UnityColor;
+use crate::{UnityColor, UnityLayerMask, UnityRect};
 
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
 pub struct UnityCamera {
@@ -31,8 +30,71 @@ pub struct UnityCamera {
 
     #[serde(alias = "orthographic size")]
     pub orthographic_size: f32,
+
+    #[serde(default = "default_clear_flags", alias = "m_ClearFlags")]
+    pub clear_flags: i32,
+
+    #[serde(default = "default_viewport", alias = "m_NormalizedViewPortRect")]
+    pub viewport: UnityRect,
+
+    #[serde(default, alias = "m_Depth")]
+    pub depth: f32,
+
+    #[serde(default, alias = "m_CullingMask")]
+    pub culling_mask: UnityLayerMask,
+
+    #[serde(default = "default_true", alias = "m_HDR")]
+    pub hdr: i32,
+
+    #[serde(default = "default_true", alias = "m_AllowMSAA")]
+    pub allow_msaa: i32,
+}
+
+fn default_true() -> i32 {
+    1
+}
+
+fn default_clear_flags() -> i32 {
+    CLEAR_SKYBOX
 }
 
+fn default_viewport() -> UnityRect {
+    FULL_VIEWPORT
+}
+
+// https://docs.unity3d.com/ScriptReference/CameraClearFlags.html
+const CLEAR_SKYBOX: i32 = 1;
+const CLEAR_SOLID_COLOR: i32 = 2;
+const CLEAR_DEPTH: i32 = 3;
+const CLEAR_NOTHING: i32 = 4;
+
+const FULL_VIEWPORT: UnityRect = UnityRect {
+    x: 0.0,
+    y: 0.0,
+    width: 1.0,
+    height: 1.0,
+};
+
+/// Rendering features a unity camera asks for. The scene plugin decides which
+/// ones it gets and removes this once applied.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct UnityCameraRendering {
+    pub allow_hdr: bool,
+    pub allow_msaa: bool,
+    /// Clears to a skybox but there is no skybox material to draw.
+    pub sky_fallback: bool,
+}
+
+/// Unity's camera depth, turned into bevy's camera order by
+/// [`camera_order_system`].
+#[derive(Component, Debug, Clone, Copy)]
+pub struct UnityCameraDepth(pub f32);
+
+/// Normalized unity viewport rect of a camera that doesn't cover the whole
+/// window, resolved to pixels by [`camera_viewport_system`].
+#[derive(Component, Debug, Clone)]
+pub struct UnityCameraViewport(pub UnityRect);
+
 #[derive(Component, Debug, Clone)]
 pub struct UnityCameraSkyboxCubemap {
     skybox: Option<Handle<Image>>,
@@ -47,37 +109,129 @@ impl UnityCamera {
         commands: &mut EntityCommands,
     ) {
         let projection = if self.orthographic == 1 {
+            // unity's size is half the view height
             Projection::Orthographic(OrthographicProjection {
                 near: self.near_clip_plane,
                 far: self.far_clip_plane,
-                scaling_mode: ScalingMode::WindowSize(self.orthographic_size * 3.0),
+                scaling_mode: ScalingMode::FixedVertical(self.orthographic_size * 2.0),
                 ..default()
             })
         } else {
+            // aspect ratio gets updated from the viewport by bevy
             Projection::Perspective(PerspectiveProjection {
                 fov: self.fov.to_radians(),
-                aspect_ratio: 1.0,
                 near: self.near_clip_plane,
                 far: self.far_clip_plane,
+                ..default()
             })
         };
 
+        let (clear_color, depth_load_op) = self.clear_config();
+
         commands.insert((
             Camera3dBundle {
+                camera_3d: Camera3d {
+                    clear_color,
+                    depth_load_op,
+                    ..default()
+                },
                 transform,
                 projection,
                 ..default()
             },
-            ScreenSpaceAmbientOcclusionBundle::default(),
+            self.culling_mask.render_layers(),
+            UnityCameraDepth(self.depth),
+            UnityCameraRendering {
+                allow_hdr: self.hdr != 0,
+                allow_msaa: self.allow_msaa != 0,
+                sky_fallback: skybox.is_none() && self.clears_to_skybox(),
+            },
         ));
 
-        if skybox.is_some() {
+        if self.viewport != FULL_VIEWPORT {
+            commands.insert(UnityCameraViewport(self.viewport));
+        }
+
+        if skybox.is_some() && self.clears_to_skybox() {
             commands.insert(UnityCameraSkyboxCubemap {
                 skybox: skybox.cloned(),
                 skybox_loaded: false,
             });
         }
     }
+
+    /// What the camera clears before drawing, from unity's clear flags.
+    pub fn clear_config(&self) -> (ClearColorConfig, Camera3dDepthLoadOp) {
+        match self.clear_flags {
+            CLEAR_SOLID_COLOR => (
+                ClearColorConfig::Custom(self.background_color.into()),
+                Camera3dDepthLoadOp::default(),
+            ),
+            CLEAR_DEPTH => (ClearColorConfig::None, Camera3dDepthLoadOp::default()),
+            CLEAR_NOTHING => (ClearColorConfig::None, Camera3dDepthLoadOp::Load),
+            _ => (ClearColorConfig::Default, Camera3dDepthLoadOp::default()),
+        }
+    }
+
+    pub fn clears_to_skybox(&self) -> bool {
+        self.clear_flags == CLEAR_SKYBOX
+    }
+}
+
+/// Unity depths are floats that can be less than one apart, so cameras are
+/// ordered by their rank in depth instead of the rounded depth.
+pub fn camera_order_system(
+    mut cameras: Query<(&mut Camera, &UnityCameraDepth)>,
+    added: Query<(), Added<UnityCameraDepth>>,
+) {
+    if added.is_empty() {
+        return;
+    }
+
+    let mut cameras: Vec<_> = cameras.iter_mut().collect();
+    let depths: Vec<_> = cameras.iter().map(|(_, depth)| depth.0).collect();
+    for ((camera, _), order) in cameras.iter_mut().zip(depth_order(&depths)) {
+        if camera.order != order {
+            camera.order = order;
+        }
+    }
+}
+
+/// Each depth's rank, ties keep their order.
+fn depth_order(depths: &[f32]) -> Vec<isize> {
+    let mut by_depth: Vec<_> = (0..depths.len()).collect();
+    by_depth.sort_by(|a, b| depths[*a].total_cmp(&depths[*b]));
+
+    let mut order = vec![0; depths.len()];
+    for (rank, index) in by_depth.into_iter().enumerate() {
+        order[index] = rank as isize;
+    }
+    order
+}
+
+/// Unity viewport rects start at the bottom left, bevy's at the top left.
+pub fn camera_viewport_system(mut cameras: Query<(&mut Camera, &UnityCameraViewport)>) {
+    for (mut camera, UnityCameraViewport(rect)) in &mut cameras {
+        let Some(target) = camera.physical_target_size() else {
+            continue;
+        };
+
+        let (physical_position, physical_size) = viewport_pixels(rect, target);
+
+        let unchanged = camera.viewport.as_ref().is_some_and(|viewport| {
+            viewport.physical_position == physical_position
+                && viewport.physical_size == physical_size
+        });
+        if unchanged {
+            continue;
+        }
+
+        camera.viewport = Some(Viewport {
+            physical_position,
+            physical_size,
+            ..default()
+        });
+    }
 }
 
 pub fn load_camera_skybox_system(
@@ -124,3 +278,112 @@ pub fn load_camera_skybox_system(
         commands.remove::<UnityCameraSkyboxCubemap>();
     }
 }
+
+/// Pixel position and size of a normalized unity rect, clamped to the target.
+/// A rect outside the target keeps its last pixel, wgpu rejects empty viewports.
+fn viewport_pixels(rect: &UnityRect, target: UVec2) -> (UVec2, UVec2) {
+    let last_pixel = target.max(UVec2::ONE) - UVec2::ONE;
+    let target = target.as_vec2();
+    let min = Vec2::new(rect.x, 1.0 - rect.y - rect.height).clamp(Vec2::ZERO, Vec2::ONE);
+    let max = Vec2::new(rect.x + rect.width, 1.0 - rect.y).clamp(Vec2::ZERO, Vec2::ONE);
+
+    let position = (min * target).as_uvec2().min(last_pixel);
+    let size = ((max - min) * target).as_uvec2().max(UVec2::ONE);
+    (position, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(clear_flags: i32) -> UnityCamera {
+        UnityCamera {
+            background_color: UnityColor {
+                r: 0.25,
+                g: 0.5,
+                b: 0.75,
+                a: 1.0,
+            },
+            clear_flags,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_depth_order_separates_close_depths() {
+        assert_eq!(depth_order(&[0.4, -1.0, 0.2, 3.0]), vec![2, 0, 1, 3]);
+        assert_eq!(depth_order(&[1.0, 1.0]), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_clear_flags() {
+        let (color, depth) = camera(CLEAR_SKYBOX).clear_config();
+        assert!(matches!(color, ClearColorConfig::Default));
+        assert!(matches!(depth, Camera3dDepthLoadOp::Clear(_)));
+        assert!(camera(CLEAR_SKYBOX).clears_to_skybox());
+
+        let (color, _) = camera(CLEAR_SOLID_COLOR).clear_config();
+        let ClearColorConfig::Custom(color) = color else {
+            panic!("solid color should clear to the background color");
+        };
+        assert_eq!(color, Color::from(camera(0).background_color));
+        assert!(!camera(CLEAR_SOLID_COLOR).clears_to_skybox());
+
+        let (color, depth) = camera(CLEAR_DEPTH).clear_config();
+        assert!(matches!(color, ClearColorConfig::None));
+        assert!(matches!(depth, Camera3dDepthLoadOp::Clear(_)));
+
+        let (color, depth) = camera(CLEAR_NOTHING).clear_config();
+        assert!(matches!(color, ClearColorConfig::None));
+        assert!(matches!(depth, Camera3dDepthLoadOp::Load));
+    }
+
+    #[test]
+    fn test_viewport_pixels_flip_y() {
+        let target = UVec2::new(800, 600);
+        assert_eq!(
+            viewport_pixels(&FULL_VIEWPORT, target),
+            (UVec2::ZERO, target)
+        );
+
+        // unity's bottom right quarter starts halfway down in bevy
+        let rect = UnityRect {
+            x: 0.5,
+            y: 0.0,
+            width: 0.5,
+            height: 0.5,
+        };
+        assert_eq!(
+            viewport_pixels(&rect, target),
+            (UVec2::new(400, 300), UVec2::new(400, 300))
+        );
+
+        // rects reaching past the window are clamped and never empty
+        let rect = UnityRect {
+            x: 0.9,
+            y: 0.75,
+            width: 0.5,
+            height: 0.5,
+        };
+        assert_eq!(
+            viewport_pixels(&rect, target),
+            (UVec2::new(720, 0), UVec2::new(80, 150))
+        );
+        let rect = UnityRect {
+            x: 1.0,
+            ..FULL_VIEWPORT
+        };
+        assert_eq!(
+            viewport_pixels(&rect, target),
+            (UVec2::new(799, 0), UVec2::new(1, 600))
+        );
+        let rect = UnityRect {
+            y: -1.0,
+            ..FULL_VIEWPORT
+        };
+        assert_eq!(
+            viewport_pixels(&rect, target),
+            (UVec2::new(0, 599), UVec2::new(800, 1))
+        );
+    }
+}
@@ -12,6 +12,9 @@ pub struct UnityGameObject {
 
     #[serde(default, alias = "m_Name")]
     pub name: String,
+
+    #[serde(default, alias = "m_Layer")]
+    pub layer: u8,
 }
 
 fn default_active() -> i32 {
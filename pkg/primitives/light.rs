@@ -1,7 +1,10 @@
 use bevy::{ecs::system::EntityCommands, prelude::*};
 use serde::{Deserialize, Serialize};
 
-use crate::UnityColor;
+use crate::{
+    color_temperature_to_rgb, directional_illuminance, physical_directional_illuminance,
+    physical_punctual_lumens, punctual_lumens, UnityColor, UnityVector2,
+};
 
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
 pub struct UnityLight {
@@ -22,43 +25,361 @@ pub struct UnityLight {
 
     #[serde(rename = "m_Range")]
     pub range: f32,
+
+    #[serde(default = "default_spot_angle", rename = "m_SpotAngle")]
+    pub spot_angle: f32,
+
+    #[serde(default = "default_inner_spot_angle", rename = "m_InnerSpotAngle")]
+    pub inner_spot_angle: f32,
+
+    #[serde(default, rename = "m_AreaSize")]
+    pub area_size: UnityVector2,
+
+    #[serde(default = "default_one", rename = "m_BounceIntensity")]
+    pub bounce_intensity: f32,
+
+    #[serde(default = "default_color_temperature", rename = "m_ColorTemperature")]
+    pub color_temperature: f32,
+
+    #[serde(default, rename = "m_UseColorTemperature")]
+    pub use_color_temperature: i32,
+
+    #[serde(default, rename = "m_RenderMode")]
+    pub render_mode: i32,
+
+    /// Only written by HDRP and newer unity versions, see [`crate::light_units`].
+    #[serde(default, rename = "m_LightUnit")]
+    pub light_unit: Option<i32>,
+
+    #[serde(default = "default_one", rename = "m_LuxAtDistance")]
+    pub lux_at_distance: f32,
+}
+
+fn default_spot_angle() -> f32 {
+    30.0
+}
+
+fn default_inner_spot_angle() -> f32 {
+    21.80208
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn default_color_temperature() -> f32 {
+    6570.0
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
 pub struct Shadows {
     #[serde(rename = "m_Type")]
     pub shadow_type: i32,
+
+    #[serde(default = "default_one", rename = "m_Strength")]
+    pub strength: f32,
+
+    #[serde(default = "default_shadow_bias", rename = "m_Bias")]
+    pub bias: f32,
+
+    #[serde(default = "default_shadow_normal_bias", rename = "m_NormalBias")]
+    pub normal_bias: f32,
+}
+
+// unity's defaults for a new light, bevy's biases are scaled by the ratio to these
+const UNITY_DEFAULT_SHADOW_BIAS: f32 = 0.05;
+const UNITY_DEFAULT_SHADOW_NORMAL_BIAS: f32 = 0.4;
+
+fn default_shadow_bias() -> f32 {
+    UNITY_DEFAULT_SHADOW_BIAS
+}
+
+fn default_shadow_normal_bias() -> f32 {
+    UNITY_DEFAULT_SHADOW_NORMAL_BIAS
 }
 
+// https://docs.unity3d.com/ScriptReference/LightType.html
+const SPOT: i32 = 0;
+const DIRECTIONAL: i32 = 1;
+const POINT: i32 = 2;
+const RECTANGLE: i32 = 3;
+const DISC: i32 = 4;
+
+/// Cone of the spot light standing in for an area light, just short of the
+/// hemisphere since bevy's spot shadows need an angle below 90°.
+const AREA_LIGHT_OUTER_ANGLE: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+// https://docs.unity3d.com/ScriptReference/LightRenderMode.html
+const RENDER_MODE_NOT_IMPORTANT: i32 = 2;
+
+/// Albedo of the ground a directional light's bounce comes off, roughly grass
+/// or soil.
+const BOUNCE_GROUND_ALBEDO: f32 = 0.25;
+/// Cosine of the sun's angle to the ground's normal, a sun about 50° high.
+const BOUNCE_SUN_COSINE: f32 = 0.8;
+
+/// Share of a directional light that comes back as ambient, standing in for
+/// unity's baked bounce. The ground reflects `albedo * cosine` of the light
+/// and fills half of what a surface sees. Ambient brightness 1 lights a
+/// surface with its albedo, like a unity intensity of 1, so no further
+/// conversion is needed.
+const BOUNCE_AMBIENT_FACTOR: f32 = 0.5 * BOUNCE_GROUND_ALBEDO * BOUNCE_SUN_COSINE;
+
 impl UnityLight {
+    /// Unity lights shine down their local +z, bevy lights down -z. The handedness
+    /// flip in `UnityTransform` already maps one onto the other, so the converted
+    /// transform is used as is.
     pub fn add_light_bundle(&self, transform: Transform, commands: &mut EntityCommands) {
+        let color = self.light_color();
+        let shadows_enabled = self.shadows_enabled();
+        commands.insert(UnityLightExtra { light: *self });
+
         match self.light_type {
-            1 => {
+            DIRECTIONAL => {
                 commands.insert(DirectionalLightBundle {
                     directional_light: DirectionalLight {
-                        color: self.color.into(),
-                        illuminance: self.intensity * 40000.0, // TODO: some function to convert
-                        shadows_enabled: self.shadows.shadow_type != 0,
-                        ..default()
+                        color,
+                        illuminance: self.bevy_intensity(),
+                        shadows_enabled,
+                        shadow_depth_bias: self
+                            .shadows
+                            .scaled_depth_bias(DirectionalLight::DEFAULT_SHADOW_DEPTH_BIAS),
+                        shadow_normal_bias: self
+                            .shadows
+                            .scaled_normal_bias(DirectionalLight::DEFAULT_SHADOW_NORMAL_BIAS),
                     },
                     transform,
                     ..default()
                 });
             }
-            2 => {
+            POINT => {
                 commands.insert(PointLightBundle {
                     point_light: PointLight {
-                        color: self.color.into(),
-                        intensity: self.intensity * 9000.0,
+                        color,
+                        intensity: self.punctual_lumens(1.0),
+                        range: self.range,
+                        shadows_enabled,
+                        shadow_depth_bias: self
+                            .shadows
+                            .scaled_depth_bias(PointLight::DEFAULT_SHADOW_DEPTH_BIAS),
+                        shadow_normal_bias: self
+                            .shadows
+                            .scaled_normal_bias(PointLight::DEFAULT_SHADOW_NORMAL_BIAS),
+                        ..default()
+                    },
+                    transform,
+                    ..default()
+                });
+            }
+            SPOT => {
+                // unity angles are the full cone, bevy wants the half angle
+                let outer_angle = (self.spot_angle / 2.0).to_radians();
+                let inner_angle = (self.inner_spot_angle / 2.0).to_radians().min(outer_angle);
+
+                commands.insert(SpotLightBundle {
+                    spot_light: SpotLight {
+                        color,
+                        intensity: self.punctual_lumens(1.0),
                         range: self.range,
-                        shadows_enabled: self.shadows.shadow_type != 0,
+                        shadows_enabled,
+                        shadow_depth_bias: self
+                            .shadows
+                            .scaled_depth_bias(SpotLight::DEFAULT_SHADOW_DEPTH_BIAS),
+                        shadow_normal_bias: self
+                            .shadows
+                            .scaled_normal_bias(SpotLight::DEFAULT_SHADOW_NORMAL_BIAS),
+                        outer_angle,
+                        inner_angle,
                         ..default()
                     },
                     transform,
                     ..default()
                 });
             }
-            _ => {}
+            RECTANGLE | DISC => {
+                // bevy has no area lights. a hemisphere spot light with the emitter's
+                // size as its radius gets the direction and soft falloff roughly right.
+                let (area, radius) = self.area_and_radius();
+                tracing::warn!(
+                    "area lights are not supported, approximating with a spot light of radius {}",
+                    radius
+                );
+
+                commands.insert(SpotLightBundle {
+                    spot_light: SpotLight {
+                        color,
+                        intensity: self.punctual_lumens(area),
+                        range: self.range,
+                        radius,
+                        shadows_enabled,
+                        shadow_depth_bias: self
+                            .shadows
+                            .scaled_depth_bias(SpotLight::DEFAULT_SHADOW_DEPTH_BIAS),
+                        shadow_normal_bias: self
+                            .shadows
+                            .scaled_normal_bias(SpotLight::DEFAULT_SHADOW_NORMAL_BIAS),
+                        outer_angle: AREA_LIGHT_OUTER_ANGLE,
+                        inner_angle: 0.0,
+                    },
+                    transform,
+                    ..default()
+                });
+            }
+            _ => {
+                tracing::warn!("unsupported light type: {}", self.light_type);
+            }
+        }
+    }
+
+    /// Light colour, tinted by the colour temperature when the light uses one.
+    pub fn light_color(&self) -> Color {
+        let color: Color = self.color.into();
+        if self.use_color_temperature == 0 {
+            return color;
+        }
+
+        let tint = color_temperature_to_rgb(self.color_temperature);
+        Color::rgba(
+            color.r() * tint.r(),
+            color.g() * tint.g(),
+            color.b() * tint.b(),
+            color.a(),
+        )
+    }
+
+    /// Unity keeps shadows for not important (per vertex) lights off, and a
+    /// strength of 0 hides them. Bevy has no partial shadow strength.
+    pub fn shadows_enabled(&self) -> bool {
+        self.shadows.shadow_type != 0
+            && self.shadows.strength > 0.0
+            && self.render_mode != RENDER_MODE_NOT_IMPORTANT
+    }
+
+    /// Ambient light contributed by a directional light's bounce intensity.
+    pub fn bounce_ambient(&self) -> Option<Color> {
+        if self.light_type != DIRECTIONAL || self.enabled == 0 || self.bounce_intensity <= 0.0 {
+            return None;
         }
+
+        let scale = self.intensity * self.bounce_intensity * BOUNCE_AMBIENT_FACTOR;
+        Some(self.light_color() * scale)
+    }
+
+    /// Illuminance for directional lights, luminous power otherwise.
+    pub fn bevy_intensity(&self) -> f32 {
+        match self.light_type {
+            DIRECTIONAL => match self.light_unit {
+                Some(unit) => physical_directional_illuminance(self.intensity, unit),
+                None => directional_illuminance(self.intensity),
+            },
+            RECTANGLE | DISC => self.punctual_lumens(self.area_and_radius().0),
+            _ => self.punctual_lumens(1.0),
+        }
+    }
+
+    fn area_and_radius(&self) -> (f32, f32) {
+        if self.light_type == DISC {
+            let radius = self.area_size.x;
+            (std::f32::consts::PI * radius * radius, radius)
+        } else {
+            let size = Vec2::new(self.area_size.x, self.area_size.y);
+            (size.x * size.y, size.length() / 2.0)
+        }
+    }
+
+    fn punctual_lumens(&self, area: f32) -> f32 {
+        match self.light_unit {
+            Some(unit) => {
+                physical_punctual_lumens(self.intensity, unit, self.lux_at_distance, area)
+            }
+            None => punctual_lumens(self.intensity * area),
+        }
+    }
+}
+
+/// The light as imported, kept for animating its unity properties.
+#[derive(Component, Debug)]
+pub struct UnityLightExtra {
+    pub light: UnityLight,
+}
+
+impl Shadows {
+    fn scaled_depth_bias(&self, bevy_default: f32) -> f32 {
+        bevy_default * self.bias / UNITY_DEFAULT_SHADOW_BIAS
+    }
+
+    fn scaled_normal_bias(&self, bevy_default: f32) -> f32 {
+        bevy_default * self.normal_bias / UNITY_DEFAULT_SHADOW_NORMAL_BIAS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{UnityQuaternion, UnityTransform};
+
+    #[test]
+    fn test_light_direction_matches_unity() {
+        let rotation = Quat::from_euler(
+            EulerRot::YXZ,
+            -30f32.to_radians(),
+            50f32.to_radians(),
+            10f32.to_radians(),
+        );
+        let unity = UnityTransform {
+            rotation: UnityQuaternion::from(rotation),
+            ..default()
+        };
+
+        // where the light points in unity, mirrored into bevy's right handed space
+        let unity_direction = rotation * Vec3::Z;
+        let expected = Vec3::new(unity_direction.x, unity_direction.y, -unity_direction.z);
+
+        let transform: Transform = (&unity).into();
+        assert!(transform.forward().abs_diff_eq(expected, 1e-5));
+    }
+
+    fn spawn_spot_light(light: &UnityLight) -> SpotLight {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+        let mut queue = bevy::ecs::system::CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        light.add_light_bundle(Transform::IDENTITY, &mut commands.entity(entity));
+        queue.apply(&mut world);
+        *world.get::<SpotLight>(entity).unwrap()
+    }
+
+    #[test]
+    fn test_area_lights_become_spot_lights() {
+        let rectangle = UnityLight {
+            enabled: 1,
+            light_type: RECTANGLE,
+            intensity: 3.0,
+            range: 10.0,
+            area_size: UnityVector2 { x: 2.0, y: 1.0 },
+            shadows: Shadows {
+                shadow_type: 1,
+                strength: 1.0,
+                ..default()
+            },
+            ..default()
+        };
+        let spot = spawn_spot_light(&rectangle);
+        assert_eq!(spot.intensity, punctual_lumens(6.0));
+        assert!((spot.radius - 5f32.sqrt() / 2.0).abs() < 1e-6);
+        assert!(spot.outer_angle < std::f32::consts::FRAC_PI_2);
+        assert_eq!(spot.inner_angle, 0.0);
+        assert!(spot.shadows_enabled);
+
+        let disc = UnityLight {
+            light_type: DISC,
+            area_size: UnityVector2 { x: 0.5, y: 0.0 },
+            ..rectangle
+        };
+        let spot = spawn_spot_light(&disc);
+        let area = std::f32::consts::PI * 0.25;
+        assert!((spot.intensity - punctual_lumens(3.0 * area)).abs() < 1e-3);
+        assert_eq!(spot.radius, 0.5);
+        assert!(spot.outer_angle < std::f32::consts::FRAC_PI_2);
     }
 }
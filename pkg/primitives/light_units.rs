@@ -0,0 +1,162 @@
+//! Conversions from unity light intensities to bevy's photometric units.
+//!
+//! Built-in and URP lights have a unitless intensity: a white directional
+//! light of intensity 1 lights a surface facing it with exactly its albedo,
+//! and punctual lights fall off with `intensity / d²`. Bevy divides diffuse
+//! by π, exposes directional lights with a fixed ev100 of `log2(16 / (1/250))`
+//! and converts point and spot lumens to candela with `/ 4π` without any
+//! exposure, so the conversions below undo exactly that. The built-in
+//! pipeline's legacy falloff texture is not reproduced, lights use bevy's
+//! inverse square falloff.
+//!
+//! HDRP (and newer unity versions) store `m_LightUnit` alongside the
+//! intensity, in which case the value is already physical and only needs
+//! moving into the unit bevy expects.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::Color;
+
+/// 1 / exposure bevy 0.12 applies to directional lights (ev100 of 16 / (1/250), times 1.2)
+const BEVY_DIRECTIONAL_INVERSE_EXPOSURE: f32 = 4000.0 * 1.2;
+
+/// Solid angle of a full sphere, bevy spreads point and spot lumens over it.
+const FULL_SPHERE: f32 = 4.0 * PI;
+
+// https://docs.unity3d.com/ScriptReference/LightUnit.html
+pub const LIGHT_UNIT_LUMEN: i32 = 0;
+pub const LIGHT_UNIT_CANDELA: i32 = 1;
+pub const LIGHT_UNIT_LUX: i32 = 2;
+pub const LIGHT_UNIT_NITS: i32 = 3;
+pub const LIGHT_UNIT_EV100: i32 = 4;
+
+/// Bevy `DirectionalLight::illuminance` (lux) for a built-in/URP intensity.
+pub fn directional_illuminance(intensity: f32) -> f32 {
+    intensity * PI * BEVY_DIRECTIONAL_INVERSE_EXPOSURE
+}
+
+/// Bevy point/spot `intensity` (lumens) for a built-in/URP intensity.
+pub fn punctual_lumens(intensity: f32) -> f32 {
+    intensity * FULL_SPHERE * PI
+}
+
+/// Bevy `DirectionalLight::illuminance` for an intensity in `unit`.
+pub fn physical_directional_illuminance(intensity: f32, unit: i32) -> f32 {
+    match unit {
+        LIGHT_UNIT_LUX => intensity,
+        LIGHT_UNIT_EV100 => 2.5 * 2f32.powf(intensity),
+        _ => {
+            tracing::warn!("unsupported directional light unit {}, using lux", unit);
+            intensity
+        }
+    }
+}
+
+/// Bevy point/spot `intensity` (lumens) for an intensity in `unit`.
+/// `lux_at_distance` is only used by [`LIGHT_UNIT_LUX`], `area` by [`LIGHT_UNIT_NITS`].
+pub fn physical_punctual_lumens(intensity: f32, unit: i32, lux_at_distance: f32, area: f32) -> f32 {
+    match unit {
+        LIGHT_UNIT_LUMEN => intensity,
+        LIGHT_UNIT_CANDELA => intensity * FULL_SPHERE,
+        LIGHT_UNIT_LUX => intensity * lux_at_distance * lux_at_distance * FULL_SPHERE,
+        LIGHT_UNIT_NITS => intensity * area * PI,
+        LIGHT_UNIT_EV100 => 2f32.powf(intensity - 3.0) * FULL_SPHERE,
+        _ => {
+            tracing::warn!("unknown light unit {}, using lumen", unit);
+            intensity
+        }
+    }
+}
+
+/// Approximate rgb tint of a black body at `kelvin`, normalised so the
+/// brightest channel is 1. Based on Tanner Helland's fit, which is what unity's
+/// `Mathf.CorrelatedColorTemperatureToRGB` approximates as well.
+pub fn color_temperature_to_rgb(kelvin: f32) -> Color {
+    let temperature = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+    let red = if temperature <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (temperature - 60.0).powf(-0.13320476)
+    };
+
+    let green = if temperature <= 66.0 {
+        99.4708 * temperature.ln() - 161.11957
+    } else {
+        288.12216 * (temperature - 60.0).powf(-0.075514846)
+    };
+
+    let blue = if temperature >= 66.0 {
+        255.0
+    } else if temperature <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (temperature - 10.0).ln() - 305.0448
+    };
+
+    let channel = |c: f32| (c / 255.0).clamp(0.0, 1.0);
+    let (r, g, b) = (channel(red), channel(green), channel(blue));
+    let max = r.max(g).max(b);
+
+    Color::rgb(r / max, g / max, b / max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() <= b.abs() * 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_unitless_intensities() {
+        // a white light of intensity 1 shows the albedo once bevy divides by
+        // pi and applies its directional exposure
+        assert_close(
+            directional_illuminance(1.0) / PI / BEVY_DIRECTIONAL_INVERSE_EXPOSURE,
+            1.0,
+        );
+        // intensity / d² once bevy spreads the lumens over the sphere
+        assert_close(punctual_lumens(2.0) / FULL_SPHERE / PI, 2.0);
+    }
+
+    #[test]
+    fn test_physical_units() {
+        assert_close(
+            physical_directional_illuminance(100.0, LIGHT_UNIT_LUX),
+            100.0,
+        );
+        assert_close(physical_directional_illuminance(0.0, LIGHT_UNIT_EV100), 2.5);
+
+        assert_close(
+            physical_punctual_lumens(800.0, LIGHT_UNIT_LUMEN, 1.0, 1.0),
+            800.0,
+        );
+        assert_close(
+            physical_punctual_lumens(1.0, LIGHT_UNIT_CANDELA, 1.0, 1.0),
+            FULL_SPHERE,
+        );
+        assert_close(
+            physical_punctual_lumens(1.0, LIGHT_UNIT_LUX, 2.0, 1.0),
+            4.0 * FULL_SPHERE,
+        );
+        assert_close(
+            physical_punctual_lumens(1.0, LIGHT_UNIT_NITS, 1.0, 2.0),
+            2.0 * PI,
+        );
+        assert_close(
+            physical_punctual_lumens(3.0, LIGHT_UNIT_EV100, 1.0, 1.0),
+            FULL_SPHERE,
+        );
+    }
+
+    #[test]
+    fn test_color_temperature() {
+        // daylight is close to white, candle light has no blue
+        let [r, g, b, _] = color_temperature_to_rgb(6500.0).as_rgba_f32();
+        assert!(r > 0.95 && g > 0.9 && b > 0.9);
+        let [r, _, b, _] = color_temperature_to_rgb(1000.0).as_rgba_f32();
+        assert_eq!((r, b), (1.0, 0.0));
+    }
+}
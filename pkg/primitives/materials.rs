@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
-use crate::{FileReference, UnityColor, UnityVector2};
-use bevy::prelude::*;
+use crate::{FileReference, TextureConversion, TextureConversions, UnityColor, UnityVector2};
+use bevy::{prelude::*, render::render_resource::Face};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug)]
@@ -17,8 +17,93 @@ pub struct UnityMaterial {
 
     #[serde(default, alias = "stringTagMap")]
     pub string_tags: HashMap<String, String>,
+
+    /// Space separated, older unity versions only write this one.
+    #[serde(default, alias = "m_ShaderKeywords")]
+    pub shader_keywords: Option<String>,
+
+    #[serde(default, alias = "m_ValidKeywords")]
+    pub valid_keywords: Vec<String>,
+
+    /// -1 when the shader's own queue is used.
+    #[serde(default = "default_render_queue", alias = "m_CustomRenderQueue")]
+    pub custom_render_queue: i32,
+}
+
+fn default_render_queue() -> i32 {
+    -1
+}
+
+/// Property names of a shader that converts to a `StandardMaterial`.
+#[derive(Debug)]
+struct StandardShader {
+    base_map: &'static str,
+    base_color: &'static str,
+    smoothness: &'static str,
+    /// Scales smoothness read from a map, URP reuses the smoothness slider.
+    smoothness_scale: &'static str,
+    /// URP shaders describe transparency with `_Surface`/`_Blend`, the
+    /// standard shader with `_Mode`.
+    urp_surface: bool,
+    unlit: bool,
 }
 
+// built-in shaders, all in BUILTIN_EXTRA_GUID
+const STANDARD_SHADER: i64 = 46;
+
+const STANDARD: StandardShader = StandardShader {
+    base_map: "_MainTex",
+    base_color: "_Color",
+    smoothness: "_Glossiness",
+    smoothness_scale: "_GlossMapScale",
+    urp_surface: false,
+    unlit: false,
+};
+
+const URP_LIT: StandardShader = StandardShader {
+    base_map: "_BaseMap",
+    base_color: "_BaseColor",
+    smoothness: "_Smoothness",
+    smoothness_scale: "_Smoothness",
+    urp_surface: true,
+    unlit: false,
+};
+
+const URP_UNLIT: StandardShader = StandardShader {
+    unlit: true,
+    ..URP_LIT
+};
+
+/// URP shaders by the guid of their .shader file. Simple Lit has no metallic
+/// workflow, its missing `_Metallic` falls back to 0.
+const URP_SHADERS: &[(&str, &StandardShader)] = &[
+    // Universal Render Pipeline/Lit
+    ("933532a4fcc9baf4fa0491de14d08ed7", &URP_LIT),
+    // Universal Render Pipeline/Simple Lit
+    ("8d2bb70cbf9db8d4da26e15b26e74248", &URP_LIT),
+    // Universal Render Pipeline/Unlit
+    ("650dd9526735d5b46b79224bc6e94025", &URP_UNLIT),
+];
+
+// https://github.com/Unity-Technologies/Graphics/blob/master/Packages/com.unity.render-pipelines.universal/Editor/ShaderGUI/BaseShaderGUI.cs
+const URP_SURFACE_TRANSPARENT: f32 = 1.0;
+const URP_BLEND_PREMULTIPLY: f32 = 1.0;
+const URP_BLEND_ADDITIVE: f32 = 2.0;
+const URP_BLEND_MULTIPLY: f32 = 3.0;
+
+// https://github.com/Unity-Technologies/UnityCsReference/blob/master/Editor/Mono/Inspector/StandardShaderGUI.cs
+const STANDARD_MODE_CUTOUT: f32 = 1.0;
+const STANDARD_MODE_FADE: f32 = 2.0;
+const STANDARD_MODE_TRANSPARENT: f32 = 3.0;
+
+// UnityEngine.Rendering.RenderQueue
+const RENDER_QUEUE_ALPHA_TEST: i32 = 2450;
+const RENDER_QUEUE_TRANSPARENT: i32 = 3000;
+
+// UnityEngine.Rendering.CullMode
+const CULL_OFF: f32 = 0.0;
+const CULL_FRONT: f32 = 1.0;
+
 impl UnityMaterial {
     pub fn get_skybox_texture_id(&self) -> Option<String> {
         self.properties
@@ -28,48 +113,209 @@ impl UnityMaterial {
             .and_then(|t| t.texture.guid.clone())
     }
 
-    pub fn get_standard_material(&self) -> Option<StandardMaterial> {
-        let alpha_mode = if let Some(render_type) = self.string_tags.get("RenderType") {
-            if render_type == "Transparent" {
-                AlphaMode::Blend
-            } else {
-                AlphaMode::Mask(0.1)
+    pub fn has_keyword(&self, keyword: &str) -> bool {
+        self.valid_keywords.iter().any(|k| k == keyword)
+            || self
+                .shader_keywords
+                .as_deref()
+                .is_some_and(|keywords| keywords.split_whitespace().any(|k| k == keyword))
+    }
+
+    fn standard_shader(&self) -> Option<&'static StandardShader> {
+        if self.shader.file_id == STANDARD_SHADER {
+            return Some(&STANDARD);
+        }
+
+        let guid = self.shader.guid.as_deref()?;
+        URP_SHADERS
+            .iter()
+            .find_map(|(shader_guid, shader)| (*shader_guid == guid).then_some(*shader))
+    }
+
+    /// Base map tiling and offset, when it isn't the identity. Every map of
+    /// the standard and URP shaders is sampled with it.
+    pub fn uv_transform(&self) -> Option<(Vec2, Vec2)> {
+        let base_map = self.standard_shader()?.base_map;
+        let main = self.properties.texture_info(base_map)?;
+        let scale = Vec2::new(main.scale.x, main.scale.y);
+        let offset = Vec2::new(main.offset.x, main.offset.y);
+
+        (scale != Vec2::ONE || offset != Vec2::ZERO).then_some((scale, offset))
+    }
+
+    /// Unity compiles the keywords from the surface settings, so they win when
+    /// present. Older materials may only have the settings, the render queue or
+    /// the `RenderType` tag.
+    fn alpha_mode(&self, shader: &StandardShader) -> AlphaMode {
+        let props = &self.properties;
+        let cutoff = props.float("_Cutoff").unwrap_or(0.5);
+
+        if self.has_keyword("_ALPHAPREMULTIPLY_ON") {
+            return AlphaMode::Premultiplied;
+        }
+        if self.has_keyword("_ALPHABLEND_ON") {
+            return AlphaMode::Blend;
+        }
+        if self.has_keyword("_ALPHATEST_ON") {
+            return AlphaMode::Mask(cutoff);
+        }
+
+        if shader.urp_surface {
+            if let Some(surface) = props.float("_Surface") {
+                if surface == URP_SURFACE_TRANSPARENT {
+                    return match props.float("_Blend") {
+                        Some(URP_BLEND_PREMULTIPLY) => AlphaMode::Premultiplied,
+                        Some(URP_BLEND_ADDITIVE) => AlphaMode::Add,
+                        Some(URP_BLEND_MULTIPLY) => AlphaMode::Multiply,
+                        _ => AlphaMode::Blend,
+                    };
+                }
+
+                if props.float("_AlphaClip") == Some(1.0) {
+                    return AlphaMode::Mask(cutoff);
+                }
+
+                return AlphaMode::Opaque;
             }
-        } else {
-            AlphaMode::Opaque
-        };
+        } else if let Some(mode) = props.float("_Mode") {
+            return match mode {
+                STANDARD_MODE_CUTOUT => AlphaMode::Mask(cutoff),
+                STANDARD_MODE_FADE => AlphaMode::Blend,
+                STANDARD_MODE_TRANSPARENT => AlphaMode::Premultiplied,
+                _ => AlphaMode::Opaque,
+            };
+        }
 
-        match self.shader.file_id {
-            46 => {
-                let base_color = self
-                    .properties
-                    .colors
-                    .iter()
-                    .find_map(|c| c.get("_Color"))?
-                    .into();
-                let emissive = self
-                    .properties
-                    .colors
-                    .iter()
-                    .find_map(|c| c.get("_EmissionColor"))?
-                    .into();
+        if self.custom_render_queue >= RENDER_QUEUE_TRANSPARENT {
+            return AlphaMode::Blend;
+        }
+        if self.custom_render_queue >= RENDER_QUEUE_ALPHA_TEST {
+            return AlphaMode::Mask(cutoff);
+        }
+        if self.custom_render_queue >= 0 {
+            return AlphaMode::Opaque;
+        }
 
-                let metallic = self
-                    .properties
-                    .floats
-                    .iter()
-                    .find_map(|f| f.get("_Metallic"))?
-                    .to_owned();
-                Some(StandardMaterial {
-                    base_color,
-                    emissive,
-                    metallic,
-                    alpha_mode,
-                    ..default()
-                })
+        match self.string_tags.get("RenderType").map(String::as_str) {
+            Some("Transparent") => AlphaMode::Blend,
+            Some("TransparentCutout") => AlphaMode::Mask(cutoff),
+            _ => AlphaMode::Opaque,
+        }
+    }
+
+    /// `_Cull` of URP and most custom shaders, the standard shader always culls back faces.
+    fn cull_mode(&self) -> (Option<Face>, bool) {
+        match self.properties.float("_Cull") {
+            Some(CULL_OFF) => (None, true),
+            Some(CULL_FRONT) => (Some(Face::Front), false),
+            _ => (Some(Face::Back), false),
+        }
+    }
+
+    pub fn get_standard_material(
+        &self,
+        textures: &HashMap<String, Handle<Image>>,
+        conversions: &mut TextureConversions,
+        images: &Assets<Image>,
+    ) -> Option<StandardMaterial> {
+        let shader = self.standard_shader()?;
+        let alpha_mode = self.alpha_mode(shader);
+
+        let props = &self.properties;
+        let texture = |name: &str| {
+            let guid = props.texture_info(name)?.texture.guid.as_ref()?;
+            let handle = textures.get(guid);
+            if handle.is_none() {
+                tracing::warn!("missing texture {} for {} in {}", guid, name, self.name);
             }
-            _ => None,
+            handle
+        };
+        let mut convert = |name: &str, conversion| {
+            texture(name).map(|source| conversions.convert(images, source, conversion))
+        };
+
+        let base_color = props
+            .color(shader.base_color)
+            .map_or(Color::WHITE, Color::from);
+        let base_color_texture = texture(shader.base_map).cloned();
+
+        let (cull_mode, double_sided) = self.cull_mode();
+
+        if shader.unlit {
+            return Some(StandardMaterial {
+                base_color,
+                base_color_texture,
+                unlit: true,
+                alpha_mode,
+                cull_mode,
+                double_sided,
+                ..default()
+            });
         }
+
+        let smoothness_scale = props.float(shader.smoothness_scale).unwrap_or(1.0);
+        let metallic = props.float("_Metallic").unwrap_or(0.0);
+
+        // unity takes smoothness from a map when there is one, the sliders otherwise
+        let (metallic, perceptual_roughness, metallic_roughness_texture) = if let Some(map) =
+            convert(
+                "_MetallicGlossMap",
+                TextureConversion::MetallicGloss { smoothness_scale },
+            ) {
+            (1.0, 1.0, Some(map))
+        } else if props.float("_SmoothnessTextureChannel") == Some(1.0)
+            && base_color_texture.is_some()
+        {
+            let map = convert(
+                shader.base_map,
+                TextureConversion::AlbedoSmoothness { smoothness_scale },
+            );
+            (metallic, 1.0, map)
+        } else {
+            let smoothness = props.float(shader.smoothness).unwrap_or(0.5);
+            (metallic, 1.0 - smoothness, None)
+        };
+
+        let normal_map_texture = convert(
+            "_BumpMap",
+            TextureConversion::Normal {
+                scale: props.float("_BumpScale").unwrap_or(1.0),
+            },
+        );
+        let occlusion_texture = convert(
+            "_OcclusionMap",
+            TextureConversion::Occlusion {
+                strength: props.float("_OcclusionStrength").unwrap_or(1.0),
+            },
+        );
+        let depth_map = convert("_ParallaxMap", TextureConversion::Height);
+
+        let (emissive, emissive_texture) = if self.has_keyword("_EMISSION") {
+            let emissive = props
+                .color("_EmissionColor")
+                .map_or(Color::BLACK, Color::from);
+            (emissive, texture("_EmissionMap").cloned())
+        } else {
+            (Color::BLACK, None)
+        };
+
+        Some(StandardMaterial {
+            base_color,
+            base_color_texture,
+            emissive,
+            emissive_texture,
+            metallic,
+            perceptual_roughness,
+            metallic_roughness_texture,
+            normal_map_texture,
+            occlusion_texture,
+            depth_map,
+            parallax_depth_scale: props.float("_Parallax").unwrap_or(0.02),
+            alpha_mode,
+            cull_mode,
+            double_sided,
+            ..default()
+        })
     }
 }
 
@@ -97,3 +343,124 @@ pub struct TextureInfo {
     #[serde(alias = "m_Offset")]
     pub offset: UnityVector2,
 }
+
+impl SavedProperties {
+    pub fn texture_info(&self, name: &str) -> Option<&TextureInfo> {
+        self.tex_envs.iter().find_map(|t| t.get(name))
+    }
+
+    pub fn float(&self, name: &str) -> Option<f32> {
+        self.floats.iter().find_map(|f| f.get(name)).copied()
+    }
+
+    pub fn color(&self, name: &str) -> Option<UnityColor> {
+        self.colors.iter().find_map(|c| c.get(name)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn material(keywords: &str, floats: &[(&str, f32)]) -> UnityMaterial {
+        UnityMaterial {
+            shader_keywords: Some(keywords.to_string()),
+            properties: SavedProperties {
+                floats: floats
+                    .iter()
+                    .map(|(name, value)| HashMap::from([(name.to_string(), *value)]))
+                    .collect(),
+                ..default()
+            },
+            custom_render_queue: -1,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_alpha_mode_from_keywords_and_mode() {
+        let cutout = material("_ALPHATEST_ON", &[("_Cutoff", 0.3)]);
+        assert_eq!(cutout.alpha_mode(&STANDARD), AlphaMode::Mask(0.3));
+
+        let fade = material("", &[("_Mode", STANDARD_MODE_FADE)]);
+        assert_eq!(fade.alpha_mode(&STANDARD), AlphaMode::Blend);
+
+        let mut queued = material("", &[]);
+        queued.custom_render_queue = RENDER_QUEUE_TRANSPARENT;
+        assert_eq!(queued.alpha_mode(&URP_LIT), AlphaMode::Blend);
+
+        let clipped = material(
+            "",
+            &[("_Surface", 0.0), ("_AlphaClip", 1.0), ("_Cutoff", 0.4)],
+        );
+        assert_eq!(clipped.alpha_mode(&URP_LIT), AlphaMode::Mask(0.4));
+
+        // the surface type wins over the render queue
+        let mut opaque = material("", &[("_Surface", 0.0), ("_AlphaClip", 0.0)]);
+        opaque.custom_render_queue = RENDER_QUEUE_TRANSPARENT;
+        assert_eq!(opaque.alpha_mode(&URP_LIT), AlphaMode::Opaque);
+
+        for (blend, alpha_mode) in [
+            (0.0, AlphaMode::Blend),
+            (URP_BLEND_PREMULTIPLY, AlphaMode::Premultiplied),
+            (URP_BLEND_ADDITIVE, AlphaMode::Add),
+            (URP_BLEND_MULTIPLY, AlphaMode::Multiply),
+        ] {
+            let transparent = material(
+                "",
+                &[("_Surface", URP_SURFACE_TRANSPARENT), ("_Blend", blend)],
+            );
+            assert_eq!(transparent.alpha_mode(&URP_LIT), alpha_mode);
+        }
+    }
+
+    fn urp_lit(keywords: &str, floats: &[(&str, f32)]) -> StandardMaterial {
+        let mut material = material(keywords, floats);
+        material.shader.guid = Some("933532a4fcc9baf4fa0491de14d08ed7".to_string());
+        material.properties.colors = vec![HashMap::from([(
+            "_EmissionColor".to_string(),
+            UnityColor {
+                r: 1.0,
+                g: 0.5,
+                b: 0.0,
+                a: 1.0,
+            },
+        )])];
+
+        material
+            .get_standard_material(
+                &HashMap::new(),
+                &mut TextureConversions::default(),
+                &Assets::default(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_urp_lit_conversion() {
+        let lit = urp_lit("", &[("_Smoothness", 0.75), ("_Metallic", 0.25)]);
+        assert_eq!(lit.perceptual_roughness, 0.25);
+        assert_eq!(lit.metallic, 0.25);
+        assert_eq!(lit.alpha_mode, AlphaMode::Opaque);
+
+        // _Cull
+        assert_eq!(lit.cull_mode, Some(Face::Back));
+        assert!(!lit.double_sided);
+        let both = urp_lit("", &[("_Cull", CULL_OFF)]);
+        assert_eq!(both.cull_mode, None);
+        assert!(both.double_sided);
+        let front = urp_lit("", &[("_Cull", CULL_FRONT)]);
+        assert_eq!(front.cull_mode, Some(Face::Front));
+        assert!(!front.double_sided);
+
+        let clipped = urp_lit("_ALPHATEST_ON", &[("_AlphaClip", 1.0), ("_Cutoff", 0.2)]);
+        assert_eq!(clipped.alpha_mode, AlphaMode::Mask(0.2));
+        let transparent = urp_lit("", &[("_Surface", URP_SURFACE_TRANSPARENT)]);
+        assert_eq!(transparent.alpha_mode, AlphaMode::Blend);
+
+        // _EmissionColor is ignored without the keyword
+        assert_eq!(lit.emissive, Color::BLACK);
+        let emissive = urp_lit("_EMISSION", &[]);
+        assert_eq!(emissive.emissive, Color::rgba(1.0, 0.5, 0.0, 1.0));
+    }
+}
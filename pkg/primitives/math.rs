@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, render::view::RenderLayers};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
@@ -68,3 +68,57 @@ pub struct UnityVector2 {
     pub x: f32,
     pub y: f32,
 }
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+pub struct UnityRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// A unity `LayerMask`, one bit per layer.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnityLayerMask {
+    #[serde(rename = "m_Bits")]
+    pub bits: u32,
+}
+
+impl Default for UnityLayerMask {
+    fn default() -> Self {
+        Self { bits: u32::MAX }
+    }
+}
+
+impl UnityLayerMask {
+    /// Unity's 32 layers map one to one onto bevy's render layers.
+    pub fn render_layers(&self) -> RenderLayers {
+        let layers: Vec<u8> = (0..32u8).filter(|l| self.bits & (1 << l) != 0).collect();
+        RenderLayers::from_layers(&layers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_mask_render_layers() {
+        assert_eq!(
+            UnityLayerMask::default().render_layers(),
+            RenderLayers::all()
+        );
+
+        // everything but layers 1 to 7
+        let layers = UnityLayerMask { bits: !0xfe }.render_layers();
+        assert!(layers.intersects(&RenderLayers::layer(0)));
+        assert!(!layers.intersects(&RenderLayers::layer(1)));
+        assert!(!layers.intersects(&RenderLayers::layer(7)));
+        assert!(layers.intersects(&RenderLayers::layer(31)));
+
+        assert_eq!(
+            UnityLayerMask { bits: 0 }.render_layers(),
+            RenderLayers::none()
+        );
+    }
+}
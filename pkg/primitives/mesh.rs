@@ -1,4 +1,9 @@
-use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy::{
+    ecs::system::EntityCommands,
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::view::{RenderLayers, VisibleEntities},
+};
 use serde::{Deserialize, Serialize};
 
 use crate::FileReference;
@@ -27,21 +32,202 @@ pub struct UnityMeshFilterExtra {
 pub struct UnityMeshRenderer {
     #[serde(alias = "m_Materials")]
     pub materials: Vec<FileReference>,
+
+    #[serde(default = "default_one", alias = "m_Enabled")]
+    pub enabled: u8,
+
+    #[serde(default = "default_one", alias = "m_CastShadows")]
+    pub cast_shadows: u8,
+
+    #[serde(default = "default_one", alias = "m_ReceiveShadows")]
+    pub receive_shadows: u8,
+
+    #[serde(default, alias = "m_StaticShadowCaster")]
+    pub static_shadow_caster: u8,
+
+    /// URP rendering layers, which pick the lights and decals affecting the
+    /// renderer rather than the cameras drawing it.
+    #[serde(
+        default = "default_rendering_layer_mask",
+        alias = "m_RenderingLayerMask"
+    )]
+    pub rendering_layer_mask: u32,
+}
+
+fn default_one() -> u8 {
+    1
+}
+
+/// A skinned renderer saved in the scene. Only the ones inside model files
+/// are skinned, these are drawn unskinned in their bind pose.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct UnitySkinnedMeshRenderer {
+    #[serde(flatten)]
+    pub renderer: UnityMeshRenderer,
+
+    #[serde(alias = "m_Mesh")]
+    pub mesh: FileReference,
 }
 
+impl UnitySkinnedMeshRenderer {
+    pub fn add_skinned_mesh_renderer_meta(&self, commands: &mut EntityCommands) {
+        tracing::warn!(
+            "skinned mesh renderer on {:?} is drawn in its bind pose, only models are skinned",
+            commands.id()
+        );
+        commands.insert(UnityMeshFilterExtra {
+            mesh: self.mesh.clone(),
+        });
+        self.renderer.add_mesh_renderer_meta(commands);
+    }
+}
+
+fn default_rendering_layer_mask() -> u32 {
+    1
+}
+
+// UnityEngine.Rendering.ShadowCastingMode
+const SHADOW_CASTING_OFF: u8 = 0;
+const SHADOW_CASTING_SHADOWS_ONLY: u8 = 3;
+
+/// URP rendering layer mask of a renderer that isn't on the default layer.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct UnityRenderingLayers(pub u32);
+
+/// A renderer set to cast shadows only, left out of every camera's visible
+/// entities by [`hide_shadows_only_system`] while lights still see it.
+#[derive(Component, Debug)]
+pub struct UnityShadowsOnly;
+
 impl UnityMeshRenderer {
     pub fn add_mesh_renderer_meta(&self, commands: &mut EntityCommands) {
         commands.insert(UnityMeshRendererExtra {
             materials: self.materials.clone(),
+            renderer: self.clone(),
         });
         commands.insert(UnityMeshRequiresLoad);
     }
+
+    pub fn casts_shadows(&self) -> bool {
+        self.cast_shadows != SHADOW_CASTING_OFF || self.static_shadow_caster != 0
+    }
+
+    pub fn shadows_only(&self) -> bool {
+        self.cast_shadows == SHADOW_CASTING_SHADOWS_ONLY
+    }
+
+    /// Applies the renderer's visibility and shadow settings to a spawned mesh
+    /// entity, on top of its GameObject's visibility and layers.
+    pub fn insert_render_flags(
+        &self,
+        commands: &mut EntityCommands,
+        visibility: Visibility,
+        layers: RenderLayers,
+    ) {
+        commands.insert(if self.enabled == 0 {
+            Visibility::Hidden
+        } else {
+            visibility
+        });
+
+        if layers != RenderLayers::default() {
+            commands.insert(layers);
+        }
+        if self.shadows_only() {
+            commands.insert(UnityShadowsOnly);
+        }
+        if self.rendering_layer_mask != default_rendering_layer_mask() {
+            commands.insert(UnityRenderingLayers(self.rendering_layer_mask));
+        }
+
+        if !self.casts_shadows() {
+            commands.insert(NotShadowCaster);
+        }
+        if self.receive_shadows == 0 {
+            commands.insert(NotShadowReceiver);
+        }
+    }
+}
+
+/// Bevy culls shadows with the same visibility as cameras, so shadows only
+/// renderers are dropped from the cameras' lists once both are computed.
+pub fn hide_shadows_only_system(
+    shadows_only: Query<(), With<UnityShadowsOnly>>,
+    mut cameras: Query<&mut VisibleEntities, With<Camera>>,
+) {
+    if shadows_only.is_empty() {
+        return;
+    }
+
+    for mut visible in &mut cameras {
+        visible
+            .entities
+            .retain(|entity| !shadows_only.contains(*entity));
+    }
 }
 
 #[derive(Component, Debug)]
 pub struct UnityMeshRendererExtra {
     pub materials: Vec<FileReference>,
+    pub renderer: UnityMeshRenderer,
 }
 
 #[derive(Component, Debug)]
 pub struct UnityMeshRequiresLoad;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shadows_only_hidden_from_cameras() {
+        let mut world = World::new();
+        let shown = world.spawn_empty().id();
+        let shadows_only = world.spawn(UnityShadowsOnly).id();
+        let visible = || VisibleEntities {
+            entities: vec![shown, shadows_only],
+        };
+        let camera = world.spawn((Camera::default(), visible())).id();
+        let light = world.spawn((SpotLight::default(), visible())).id();
+
+        let mut system = IntoSystem::into_system(hide_shadows_only_system);
+        system.initialize(&mut world);
+        system.run((), &mut world);
+
+        assert_eq!(
+            world.get::<VisibleEntities>(camera).unwrap().entities,
+            vec![shown]
+        );
+        assert_eq!(
+            world.get::<VisibleEntities>(light).unwrap().entities,
+            vec![shown, shadows_only]
+        );
+    }
+
+    #[test]
+    fn test_rendering_layers_kept_apart_from_culling_layers() {
+        let renderer: UnityMeshRenderer = UnityMeshRenderer {
+            enabled: 1,
+            rendering_layer_mask: 1 << 3,
+            ..default()
+        };
+
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+        let mut queue = bevy::ecs::system::CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        renderer.insert_render_flags(
+            &mut commands.entity(entity),
+            Visibility::Inherited,
+            RenderLayers::layer(2),
+        );
+        queue.apply(&mut world);
+
+        assert_eq!(
+            world.get::<RenderLayers>(entity),
+            Some(&RenderLayers::layer(2))
+        );
+        assert_eq!(world.get::<UnityRenderingLayers>(entity).unwrap().0, 1 << 3);
+        assert!(world.get::<UnityShadowsOnly>(entity).is_none());
+    }
+}
@@ -0,0 +1,523 @@
+use anyhow::{bail, ensure, Context, Result};
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+use serde::{Deserialize, Serialize};
+
+/// A `Mesh` object as unity serializes it into `.asset` files.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UnityMeshAsset {
+    #[serde(default, alias = "m_Name")]
+    pub name: String,
+
+    #[serde(default, alias = "m_SubMeshes")]
+    pub sub_meshes: Vec<UnitySubMesh>,
+
+    /// 0 for 16 bit indices, 1 for 32 bit.
+    #[serde(default, alias = "m_IndexFormat")]
+    pub index_format: u8,
+
+    /// Hex encoded.
+    #[serde(default, alias = "m_IndexBuffer")]
+    pub index_buffer: String,
+
+    #[serde(default, alias = "m_VertexData")]
+    pub vertex_data: UnityVertexData,
+
+    #[serde(default, alias = "m_MeshCompression")]
+    pub mesh_compression: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UnitySubMesh {
+    #[serde(alias = "firstByte")]
+    pub first_byte: usize,
+    #[serde(alias = "indexCount")]
+    pub index_count: usize,
+    #[serde(default)]
+    pub topology: u8,
+    #[serde(default, alias = "baseVertex")]
+    pub base_vertex: u32,
+    #[serde(default, alias = "firstVertex")]
+    pub first_vertex: u32,
+    #[serde(default, alias = "vertexCount")]
+    pub vertex_count: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UnityVertexData {
+    #[serde(default, alias = "m_VertexCount")]
+    pub vertex_count: usize,
+
+    /// Indexed by `VertexAttribute`: position, normal, tangent, color, eight
+    /// uv sets, blend weights and blend indices.
+    #[serde(default, alias = "m_Channels")]
+    pub channels: Vec<UnityVertexChannel>,
+
+    /// Hex encoded, one block of interleaved vertices per stream.
+    #[serde(default, alias = "_typelessdata")]
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+pub struct UnityVertexChannel {
+    pub stream: u8,
+    pub offset: usize,
+    pub format: u8,
+    /// Components in the low 4 bits, 0 when the channel is unused.
+    pub dimension: u8,
+}
+
+// UnityEngine.Rendering.VertexAttribute
+const CHANNEL_POSITION: usize = 0;
+const CHANNEL_NORMAL: usize = 1;
+const CHANNEL_TANGENT: usize = 2;
+const CHANNEL_COLOR: usize = 3;
+const CHANNEL_UV0: usize = 4;
+const CHANNEL_UV1: usize = 5;
+
+// UnityEngine.Rendering.MeshTopology
+const TOPOLOGY_TRIANGLES: u8 = 0;
+const TOPOLOGY_LINES: u8 = 3;
+const TOPOLOGY_LINE_STRIP: u8 = 4;
+const TOPOLOGY_POINTS: u8 = 5;
+
+impl UnityVertexChannel {
+    fn dimension(&self) -> usize {
+        (self.dimension & 0xf) as usize
+    }
+
+    /// Size of one component, by `VertexAttributeFormat`.
+    fn component_size(&self) -> Result<usize> {
+        Ok(match self.format {
+            0 | 10 | 11 => 4,
+            1 | 4 | 5 | 8 | 9 => 2,
+            2 | 3 | 6 | 7 => 1,
+            format => bail!("unknown vertex format {}", format),
+        })
+    }
+
+    fn read_component(&self, bytes: &[u8]) -> f32 {
+        match self.format {
+            // float32
+            0 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            // float16
+            1 => f16_to_f32(u16::from_le_bytes([bytes[0], bytes[1]])),
+            // unorm8, snorm8
+            2 => bytes[0] as f32 / 255.0,
+            3 => (bytes[0] as i8 as f32 / 127.0).max(-1.0),
+            // unorm16, snorm16
+            4 => u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 65535.0,
+            5 => (i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32767.0).max(-1.0),
+            // integers
+            6 => bytes[0] as f32,
+            7 => bytes[0] as i8 as f32,
+            8 => u16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+            9 => i16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+            10 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+            _ => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+        }
+    }
+}
+
+impl UnityMeshAsset {
+    /// One bevy mesh per submesh, converted to bevy's right handed space.
+    pub fn meshes(&self) -> Result<Vec<Mesh>> {
+        ensure!(
+            self.mesh_compression == 0,
+            "compressed mesh {} isn't supported, turn mesh compression off",
+            self.name
+        );
+
+        let vertices = self.vertex_data.decode()?;
+        let index_bytes = decode_hex(&self.index_buffer).context("invalid index buffer")?;
+        let index_size = if self.index_format == 0 { 2 } else { 4 };
+
+        self.sub_meshes
+            .iter()
+            .map(|sub_mesh| {
+                let start = sub_mesh.first_byte;
+                let end = start + sub_mesh.index_count * index_size;
+                let bytes = index_bytes
+                    .get(start..end)
+                    .with_context(|| format!("submesh indices out of range in {}", self.name))?;
+
+                // indices are made relative to the submesh's own vertices
+                let first = sub_mesh.first_vertex;
+                let indices = bytes
+                    .chunks_exact(index_size)
+                    .map(|b| match *b {
+                        [a, b] => u16::from_le_bytes([a, b]) as u32,
+                        [a, b, c, d] => u32::from_le_bytes([a, b, c, d]),
+                        _ => unreachable!(),
+                    })
+                    .map(|index| {
+                        (index + sub_mesh.base_vertex)
+                            .checked_sub(first)
+                            .context("submesh index before its first vertex")
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let range = first as usize..(first + sub_mesh.vertex_count) as usize;
+                vertices.mesh(sub_mesh.topology, range, indices)
+            })
+            .collect()
+    }
+}
+
+impl UnityVertexData {
+    fn decode(&self) -> Result<DecodedVertices> {
+        let data = decode_hex(&self.data).context("invalid vertex data")?;
+
+        // streams follow each other, each aligned to 16 bytes
+        let stream_count = self
+            .channels
+            .iter()
+            .map(|c| c.stream + 1)
+            .max()
+            .unwrap_or(0);
+        let mut stream_offsets = vec![];
+        let mut stream_strides = vec![];
+        let mut offset = 0;
+        for stream in 0..stream_count {
+            let stride = self
+                .channels
+                .iter()
+                .filter(|c| c.stream == stream && c.dimension() > 0)
+                .map(|c| Ok(c.offset + c.component_size()? * c.dimension()))
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .max()
+                .unwrap_or(0);
+            let stride = stride.next_multiple_of(4);
+
+            stream_offsets.push(offset);
+            stream_strides.push(stride);
+            offset = (offset + stride * self.vertex_count).next_multiple_of(16);
+        }
+
+        let channel = |index: usize| -> Result<Option<Vec<Vec4>>> {
+            let Some(channel) = self.channels.get(index).filter(|c| c.dimension() > 0) else {
+                return Ok(None);
+            };
+
+            let size = channel.component_size()?;
+            let stream = channel.stream as usize;
+            (0..self.vertex_count)
+                .map(|vertex| {
+                    let start =
+                        stream_offsets[stream] + vertex * stream_strides[stream] + channel.offset;
+                    let mut value = Vec4::ZERO;
+                    for component in 0..channel.dimension().min(4) {
+                        let at = start + component * size;
+                        let bytes = data
+                            .get(at..at + size)
+                            .context("vertex data shorter than its channels")?;
+                        value[component] = channel.read_component(bytes);
+                    }
+                    Ok(value)
+                })
+                .collect::<Result<Vec<_>>>()
+                .map(Some)
+        };
+
+        Ok(DecodedVertices {
+            positions: channel(CHANNEL_POSITION)?.context("mesh has no positions")?,
+            normals: channel(CHANNEL_NORMAL)?,
+            tangents: channel(CHANNEL_TANGENT)?,
+            colors: channel(CHANNEL_COLOR)?,
+            uv0: channel(CHANNEL_UV0)?,
+            uv1: channel(CHANNEL_UV1)?,
+        })
+    }
+}
+
+struct DecodedVertices {
+    positions: Vec<Vec4>,
+    normals: Option<Vec<Vec4>>,
+    tangents: Option<Vec<Vec4>>,
+    colors: Option<Vec<Vec4>>,
+    uv0: Option<Vec<Vec4>>,
+    uv1: Option<Vec<Vec4>>,
+}
+
+impl DecodedVertices {
+    /// Flips z like transforms do, and v to run top down. Mirroring turns
+    /// triangles around, so they're rewound too.
+    fn mesh(
+        &self,
+        topology: u8,
+        range: std::ops::Range<usize>,
+        mut indices: Vec<u32>,
+    ) -> Result<Mesh> {
+        let topology = match topology {
+            TOPOLOGY_TRIANGLES => {
+                for triangle in indices.chunks_exact_mut(3) {
+                    triangle.swap(1, 2);
+                }
+                PrimitiveTopology::TriangleList
+            }
+            TOPOLOGY_LINES => PrimitiveTopology::LineList,
+            TOPOLOGY_LINE_STRIP => PrimitiveTopology::LineStrip,
+            TOPOLOGY_POINTS => PrimitiveTopology::PointList,
+            topology => bail!("unsupported mesh topology {}", topology),
+        };
+
+        let slice = |values: &Vec<Vec4>| -> Result<Vec<Vec4>> {
+            Ok(values
+                .get(range.clone())
+                .context("submesh vertices out of range")?
+                .to_vec())
+        };
+        let flip = |v: Vec4| [v.x, v.y, -v.z];
+        let uv = |v: Vec4| [v.x, 1.0 - v.y];
+
+        let mut mesh = Mesh::new(topology).with_inserted_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            slice(&self.positions)?
+                .into_iter()
+                .map(flip)
+                .collect::<Vec<_>>(),
+        );
+        if let Some(normals) = &self.normals {
+            let normals: Vec<_> = slice(normals)?.into_iter().map(flip).collect();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        }
+        if let Some(tangents) = &self.tangents {
+            let tangents: Vec<_> = slice(tangents)?
+                .into_iter()
+                .map(|t| [t.x, t.y, -t.z, -t.w])
+                .collect();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, tangents);
+        }
+        if let Some(colors) = &self.colors {
+            let colors: Vec<_> = slice(colors)?.into_iter().map(|c| c.to_array()).collect();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+        if let Some(uv0) = &self.uv0 {
+            let uv0: Vec<_> = slice(uv0)?.into_iter().map(uv).collect();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uv0);
+        }
+        if let Some(uv1) = &self.uv1 {
+            let uv1: Vec<_> = slice(uv1)?.into_iter().map(uv).collect();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_1, uv1);
+        }
+
+        Ok(mesh.with_indices(Some(Indices::U32(indices))))
+    }
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    ensure!(hex.len() % 2 == 0, "odd number of hex digits");
+    if let Some(at) = hex.bytes().position(|b| !b.is_ascii_hexdigit()) {
+        bail!("invalid hex at {}", at);
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16).with_context(|| format!("invalid hex at {}", i))
+        })
+        .collect()
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::mesh::VertexAttributeValues;
+
+    fn hex(bytes: impl IntoIterator<Item = u8>) -> String {
+        bytes.into_iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn floats(values: &[f32]) -> String {
+        hex(values.iter().flat_map(|v| v.to_le_bytes()))
+    }
+
+    fn positions(mesh: &Mesh) -> &Vec<[f32; 3]> {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("no positions");
+        };
+        positions
+    }
+
+    #[test]
+    fn test_decode_single_triangle() -> Result<()> {
+        let asset = UnityMeshAsset {
+            sub_meshes: vec![UnitySubMesh {
+                index_count: 3,
+                vertex_count: 3,
+                ..default()
+            }],
+            index_buffer: "000001000200".to_string(),
+            vertex_data: UnityVertexData {
+                vertex_count: 3,
+                channels: vec![UnityVertexChannel {
+                    dimension: 3,
+                    ..default()
+                }],
+                data: floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0]),
+            },
+            ..default()
+        };
+
+        let meshes = asset.meshes()?;
+        assert_eq!(meshes.len(), 1);
+
+        let indices: Vec<usize> = meshes[0].indices().unwrap().iter().collect();
+        assert_eq!(indices, vec![0, 2, 1]);
+
+        assert_eq!(positions(&meshes[0])[2], [0.0, 1.0, -1.0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_two_streams() -> Result<()> {
+        // stream 0: float3 position, float3 normal and unorm8 color, a 28
+        // byte stride padded from 84 to 96 bytes. stream 1: float2 uv.
+        let mut stream0 = String::new();
+        for vertex in 0..3 {
+            let x = vertex as f32;
+            stream0 += &floats(&[x, 2.0, 3.0, 0.0, 0.0, 1.0]);
+            stream0 += &hex([255, 0, 51, 255]);
+        }
+        let padding = hex([0; 12]);
+        let stream1 = floats(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.25]);
+
+        let channel = |stream, offset, format, dimension| UnityVertexChannel {
+            stream,
+            offset,
+            format,
+            dimension,
+        };
+        let asset = UnityMeshAsset {
+            sub_meshes: vec![UnitySubMesh {
+                index_count: 3,
+                vertex_count: 3,
+                ..default()
+            }],
+            index_buffer: "000001000200".to_string(),
+            vertex_data: UnityVertexData {
+                vertex_count: 3,
+                channels: vec![
+                    channel(0, 0, 0, 3),
+                    channel(0, 12, 0, 3),
+                    UnityVertexChannel::default(),
+                    channel(0, 24, 2, 4),
+                    channel(1, 0, 0, 2),
+                ],
+                data: stream0 + &padding + &stream1,
+            },
+            ..default()
+        };
+
+        let meshes = asset.meshes()?;
+        let mesh = &meshes[0];
+        assert_eq!(positions(mesh)[1], [1.0, 2.0, -3.0]);
+        assert!(mesh.attribute(Mesh::ATTRIBUTE_TANGENT).is_none());
+
+        let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        else {
+            panic!("no normals");
+        };
+        assert_eq!(normals[2], [0.0, 0.0, -1.0]);
+
+        let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute(Mesh::ATTRIBUTE_COLOR)
+        else {
+            panic!("no colors");
+        };
+        assert_eq!(colors[0], [1.0, 0.0, 0.2, 1.0]);
+
+        let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+        else {
+            panic!("no uvs");
+        };
+        assert_eq!(uvs, &vec![[0.0, 1.0], [1.0, 1.0], [0.0, 0.75]]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_formats() -> Result<()> {
+        let submeshes = |index_size: usize| {
+            vec![
+                UnitySubMesh {
+                    index_count: 3,
+                    vertex_count: 3,
+                    ..default()
+                },
+                UnitySubMesh {
+                    first_byte: 3 * index_size,
+                    index_count: 3,
+                    first_vertex: 3,
+                    vertex_count: 3,
+                    ..default()
+                },
+            ]
+        };
+        let vertex_data = || UnityVertexData {
+            vertex_count: 6,
+            channels: vec![UnityVertexChannel {
+                dimension: 3,
+                ..default()
+            }],
+            data: floats(&(0..18).map(|v| v as f32).collect::<Vec<_>>()),
+        };
+        let indices = [0u32, 1, 2, 3, 5, 4];
+
+        let short = UnityMeshAsset {
+            sub_meshes: submeshes(2),
+            index_format: 0,
+            index_buffer: hex(indices.iter().flat_map(|i| (*i as u16).to_le_bytes())),
+            vertex_data: vertex_data(),
+            ..default()
+        };
+        let long = UnityMeshAsset {
+            sub_meshes: submeshes(4),
+            index_format: 1,
+            index_buffer: hex(indices.iter().flat_map(|i| i.to_le_bytes())),
+            vertex_data: vertex_data(),
+            ..default()
+        };
+
+        for asset in [short, long] {
+            let meshes = asset.meshes()?;
+            assert_eq!(meshes.len(), 2);
+
+            // relative to the submesh's first vertex, then rewound
+            let indices: Vec<usize> = meshes[1].indices().unwrap().iter().collect();
+            assert_eq!(indices, vec![0, 1, 2]);
+            assert_eq!(positions(&meshes[1])[0], [9.0, 10.0, -11.0]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("00ff7A").unwrap(), vec![0, 255, 122]);
+        assert!(decode_hex("abc").is_err());
+        assert!(decode_hex("+1").is_err());
+        // a multibyte character in an even length string
+        assert!(decode_hex("\u{e9}").is_err());
+        assert!(decode_hex("0\u{e9}0").is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::FileReference;
+
+/// The `.meta` file next to a model.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct UnityModelMeta {
+    /// glTF models come from a scripted importer, which only has remaps.
+    #[serde(default, rename = "ModelImporter", alias = "ScriptedImporter")]
+    pub model_importer: Option<UnityModelImporter>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityModelImporter {
+    #[serde(
+        default,
+        alias = "externalObjects",
+        alias = "m_ExternalObjects",
+        deserialize_with = "deserialize_external_objects"
+    )]
+    pub external_objects: Vec<UnityExternalObject>,
+
+    #[serde(default)]
+    pub meshes: UnityModelMeshSettings,
+
+    #[serde(default)]
+    pub animations: UnityModelAnimationSettings,
+
+    /// 0 imports no rig or animations.
+    #[serde(default = "default_animation_type", alias = "animationType")]
+    pub animation_type: u8,
+}
+
+impl Default for UnityModelImporter {
+    fn default() -> Self {
+        Self {
+            external_objects: vec![],
+            meshes: default(),
+            animations: default(),
+            animation_type: default_animation_type(),
+        }
+    }
+}
+
+/// Generic, unity's default.
+fn default_animation_type() -> u8 {
+    2
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityModelAnimationSettings {
+    #[serde(default = "default_one", alias = "importAnimation")]
+    pub import_animation: u8,
+
+    /// Path of the node root motion is read from.
+    #[serde(default, alias = "motionNodeName")]
+    pub motion_node_name: Option<String>,
+
+    /// Clips cut from the file's takes, empty when the takes are used as is.
+    #[serde(default, alias = "clipAnimations")]
+    pub clip_animations: Vec<UnityModelClip>,
+}
+
+impl Default for UnityModelAnimationSettings {
+    fn default() -> Self {
+        Self {
+            import_animation: 1,
+            motion_node_name: None,
+            clip_animations: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct UnityModelClip {
+    pub name: String,
+    #[serde(alias = "takeName")]
+    pub take_name: String,
+    #[serde(default, alias = "loopTime")]
+    pub loop_time: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityModelMeshSettings {
+    #[serde(default = "default_global_scale", alias = "globalScale")]
+    pub global_scale: f32,
+
+    /// Convert the file's units to meters.
+    #[serde(default = "default_one", alias = "useFileScale")]
+    pub use_file_scale: u8,
+
+    /// Whether unity bakes the axis conversion into the meshes or keeps it on
+    /// the nodes. The world space result is the same, so it only matters to
+    /// code reading the nodes' transforms.
+    #[serde(default, alias = "bakeAxisConversion")]
+    pub bake_axis_conversion: u8,
+
+    /// Unless set, unity collapses a model's only root node into the prefab
+    /// root, which is named after the file.
+    #[serde(default, alias = "preserveHierarchy")]
+    pub preserve_hierarchy: u8,
+
+    #[serde(default = "default_one", alias = "importCameras")]
+    pub import_cameras: u8,
+
+    #[serde(default = "default_one", alias = "importLights")]
+    pub import_lights: u8,
+}
+
+impl Default for UnityModelMeshSettings {
+    fn default() -> Self {
+        Self {
+            global_scale: default_global_scale(),
+            use_file_scale: 1,
+            bake_axis_conversion: 0,
+            preserve_hierarchy: 0,
+            import_cameras: 1,
+            import_lights: 1,
+        }
+    }
+}
+
+fn default_global_scale() -> f32 {
+    1.0
+}
+
+fn default_one() -> u8 {
+    1
+}
+
+/// A sub asset of the model replaced by a project asset.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityExternalObject {
+    pub first: UnityExternalObjectKey,
+    pub second: FileReference,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnityExternalObjectKey {
+    /// e.g. `UnityEngine:Material`.
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub name: String,
+}
+
+/// Unity writes an empty list as `{}`.
+fn deserialize_external_objects<'de, D>(
+    deserializer: D,
+) -> Result<Vec<UnityExternalObject>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ExternalObjects {
+        List(Vec<UnityExternalObject>),
+        Empty {},
+    }
+
+    Ok(match ExternalObjects::deserialize(deserializer)? {
+        ExternalObjects::List(objects) => objects,
+        ExternalObjects::Empty {} => vec![],
+    })
+}
+
+impl UnityModelImporter {
+    /// Transform between the model's scene root and the prefab instance.
+    ///
+    /// Models are right handed like bevy. Unity mirrors their x axis on import
+    /// and the scene conversion mirrors z, which together are a half turn
+    /// around y.
+    pub fn root_transform(&self) -> Transform {
+        Transform::from_rotation(Quat::from_rotation_y(std::f32::consts::PI))
+            .with_scale(Vec3::splat(self.meshes.global_scale))
+    }
+
+    pub fn imports_animations(&self) -> bool {
+        self.animation_type != 0 && self.animations.import_animation != 0
+    }
+
+    /// Project materials by the name of the model material they replace.
+    pub fn material_remaps(&self) -> HashMap<String, FileReference> {
+        self.external_objects
+            .iter()
+            .filter(|object| object.first.object_type.ends_with(":Material"))
+            .map(|object| (object.first.name.clone(), object.second.clone()))
+            .collect()
+    }
+}
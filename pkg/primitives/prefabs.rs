@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{FileReference, UnityTransform};
+use crate::{FileReference, UnityAnimator, UnityTransform};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct UnityPrefabInstance {
@@ -24,6 +24,8 @@ pub struct ModificationProperty {
     #[serde(default, rename = "propertyPath")]
     pub path: String,
     pub value: PropertyOption,
+    #[serde(default, rename = "objectReference")]
+    pub object_reference: FileReference,
 }
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
@@ -77,6 +79,37 @@ impl UnityPrefabInstance {
         (object_id ^ transform_id) & 0x7fffffffffffffff
     }
 
+    /// Materials set on the source's renderers, as the renderer's file id in
+    /// the source, the material slot and the material.
+    pub fn material_overrides(&self) -> Vec<(i64, usize, FileReference)> {
+        self.modification
+            .modifications
+            .iter()
+            .filter_map(|m| {
+                let slot = m
+                    .path
+                    .strip_prefix("m_Materials.Array.data[")?
+                    .strip_suffix(']')?
+                    .parse()
+                    .ok()?;
+                Some((m.target.file_id, slot, m.object_reference.clone()))
+            })
+            .collect()
+    }
+
+    /// Applies the instance's changes to the source's `Animator`.
+    pub fn apply_animator_modifications(&self, animator: &mut UnityAnimator) {
+        for m in &self.modification.modifications {
+            match m.path.as_str() {
+                "m_Controller" => animator.controller = m.object_reference.clone(),
+                "m_ApplyRootMotion" => {
+                    animator.apply_root_motion = m.value.get_number().unwrap_or_default() as u8
+                }
+                _ => {}
+            }
+        }
+    }
+
     pub fn get_transform_for_prefab(&self) -> UnityTransform {
         let mut transform = UnityTransform::default();
         self.modification
@@ -1,23 +1,45 @@
+mod animation_clip;
+mod animator;
+mod animator_controller;
+mod audio;
+mod audio_mixer;
+mod builtin;
+mod builtin_mesh;
 mod camera;
 mod color;
 mod gameobject;
 mod light;
+mod light_units;
 mod materials;
 mod math;
 mod mesh;
+mod mesh_asset;
+mod model_importer;
 mod physics;
 mod prefabs;
 mod reference;
+mod texture;
 mod transform;
 
+pub use animation_clip::*;
+pub use animator::*;
+pub use animator_controller::*;
+pub use audio::*;
+pub use audio_mixer::*;
+pub use builtin::*;
+pub use builtin_mesh::*;
 pub use camera::*;
 pub use color::*;
 pub use gameobject::*;
 pub use light::*;
+pub use light_units::*;
 pub use materials::*;
 pub use math::*;
 pub use mesh::*;
+pub use mesh_asset::*;
+pub use model_importer::*;
 pub use physics::*;
 pub use prefabs::*;
 pub use reference::*;
+pub use texture::*;
 pub use transform::*;
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::LoadState,
+    prelude::*,
+    render::render_resource::{TextureDimension, TextureFormat},
+};
+
+/// Repacks a unity texture into the channel layout bevy's `StandardMaterial` samples.
+/// Converted textures are always linear, like unity's non colour textures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureConversion {
+    /// Metallic in r and smoothness in a, to metallic in b and roughness in g.
+    MetallicGloss { smoothness_scale: f32 },
+    /// Smoothness from the albedo's alpha, metallic comes from the material.
+    AlbedoSmoothness { smoothness_scale: f32 },
+    /// Tangent space normals with `_BumpScale` applied.
+    Normal { scale: f32 },
+    /// Occlusion in g, to r with `_OcclusionStrength` applied.
+    Occlusion { strength: f32 },
+    /// Height in g, to depth in r.
+    Height,
+}
+
+impl TextureConversion {
+    fn convert_pixel(&self, [r, g, _, a]: [u8; 4]) -> [u8; 4] {
+        let unorm = |v: u8| v as f32 / 255.0;
+        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        match *self {
+            TextureConversion::MetallicGloss { smoothness_scale } => {
+                let roughness = 1.0 - unorm(a) * smoothness_scale;
+                [0, to_u8(roughness), r, 255]
+            }
+            TextureConversion::AlbedoSmoothness { smoothness_scale } => {
+                let roughness = 1.0 - unorm(a) * smoothness_scale;
+                [0, to_u8(roughness), 255, 255]
+            }
+            TextureConversion::Normal { scale } => {
+                // centred on 128 so a flat normal round trips unchanged
+                let snorm = |v: u8| ((v as f32 - 128.0) / 127.0).max(-1.0);
+                let to_snorm_u8 = |v: f32| (v.clamp(-1.0, 1.0) * 127.0 + 128.0).round() as u8;
+
+                let xy = Vec2::new(snorm(r), snorm(g)) * scale;
+                let z = (1.0 - xy.length_squared().clamp(0.0, 1.0)).sqrt();
+                [to_snorm_u8(xy.x), to_snorm_u8(xy.y), to_snorm_u8(z), 255]
+            }
+            TextureConversion::Occlusion { strength } => {
+                let occlusion = 1.0 + (unorm(g) - 1.0) * strength;
+                [to_u8(occlusion), 0, 0, 255]
+            }
+            TextureConversion::Height => [255 - g, 0, 0, 255],
+        }
+    }
+
+    fn convert(&self, source: &Image) -> Option<Image> {
+        if !matches!(
+            source.texture_descriptor.format,
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb
+        ) {
+            return None;
+        }
+
+        let data = source
+            .data
+            .chunks_exact(4)
+            .flat_map(|p| self.convert_pixel([p[0], p[1], p[2], p[3]]))
+            .collect();
+
+        let mut image = Image::new(
+            source.texture_descriptor.size,
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8Unorm,
+        );
+        image.sampler = source.sampler.clone();
+
+        Some(image)
+    }
+}
+
+/// Textures waiting on their source image to be converted, see [`TextureConversion`].
+#[derive(Resource, Default)]
+pub struct TextureConversions {
+    pending: Vec<(Handle<Image>, Handle<Image>, TextureConversion)>,
+    converted: HashMap<(AssetId<Image>, String), Handle<Image>>,
+}
+
+impl TextureConversions {
+    /// Handle to the converted texture, filled in once `source` has loaded.
+    pub fn convert(
+        &mut self,
+        images: &Assets<Image>,
+        source: &Handle<Image>,
+        conversion: TextureConversion,
+    ) -> Handle<Image> {
+        let key = (source.id(), format!("{:?}", conversion));
+        if let Some(converted) = self.converted.get(&key) {
+            return converted.clone();
+        }
+
+        let target = images
+            .get_handle_provider()
+            .reserve_handle()
+            .typed::<Image>();
+        self.pending
+            .push((source.clone(), target.clone(), conversion));
+        self.converted.insert(key, target.clone());
+
+        target
+    }
+}
+
+pub fn convert_textures_system(
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut conversions: ResMut<TextureConversions>,
+) {
+    if conversions.pending.is_empty() {
+        return;
+    }
+
+    let pending = std::mem::take(&mut conversions.pending);
+    conversions.pending = pending
+        .into_iter()
+        .filter(|(source, target, conversion)| {
+            let Some(image) = images.get(source) else {
+                if asset_server.get_load_state(source) == Some(LoadState::Failed) {
+                    tracing::error!("failed to load texture for {:?}", conversion);
+                    return false;
+                }
+
+                return true; // not loaded yet
+            };
+
+            let converted = conversion.convert(image).unwrap_or_else(|| {
+                tracing::warn!(
+                    "can't convert {:?} textures for {:?}, using it as is",
+                    image.texture_descriptor.format,
+                    conversion
+                );
+                image.clone()
+            });
+
+            images.insert(target, converted);
+            false
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metallic_gloss_to_metallic_roughness() {
+        let conversion = TextureConversion::MetallicGloss {
+            smoothness_scale: 1.0,
+        };
+
+        // fully metallic and smooth
+        assert_eq!(conversion.convert_pixel([255, 0, 0, 255]), [0, 0, 255, 255]);
+        // dielectric and rough
+        assert_eq!(conversion.convert_pixel([0, 0, 0, 0]), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_flat_normal_is_unchanged_by_scale() {
+        let conversion = TextureConversion::Normal { scale: 2.0 };
+        assert_eq!(
+            conversion.convert_pixel([128, 128, 255, 255]),
+            [128, 128, 255, 255]
+        );
+        assert_eq!(
+            conversion.convert_pixel([255, 128, 128, 255]),
+            [255, 128, 128, 255]
+        );
+    }
+}
@@ -0,0 +1,466 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use bevity_primitives::{
+    UnityAnimatorExtra, UnityAnimatorRequiresSetup, UnityModelClip, CLIP_DURATION_NODE,
+};
+use bevy::{animation::animation_player, gltf::Gltf, prelude::*, transform::TransformSystem};
+
+use crate::{
+    animator_controller_system, property_animation_system, UnityModelInstance,
+    UnityModelRequiresSetup,
+};
+
+/// Hooks unity `Animator`s up to an `AnimationPlayer`, the model's or one
+/// playing the controller's `.anim` clips.
+pub struct AnimatorPlugin;
+
+impl Plugin for AnimatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                setup_animator_system,
+                animator_controller_system,
+                property_animation_system,
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            root_motion_system
+                .after(animation_player)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// An `Animator` whose model has been loaded, or one playing `.anim` clips on
+/// its own hierarchy.
+#[derive(Component, Debug)]
+pub struct UnityAnimation {
+    /// Guid of the animated model, empty without one.
+    pub model: String,
+    /// The model's or `.anim` clips by their unity clip name.
+    pub clips: HashMap<String, Handle<AnimationClip>>,
+    /// Clips imported without `loopTime`.
+    pub once: HashSet<String>,
+    /// Played when the animator has no controller, like glTF models imported
+    /// with legacy animation.
+    pub default_clip: Option<String>,
+    /// Entity with the model's `AnimationPlayer`.
+    pub player: Entity,
+    /// Model nodes by their path below the animator, as used by unity's
+    /// `Transform.Find` and animation curves.
+    pub bones: HashMap<String, Entity>,
+}
+
+impl UnityAnimation {
+    pub fn clip(&self, name: &str) -> Option<&Handle<AnimationClip>> {
+        self.clips.get(name)
+    }
+
+    pub fn bone(&self, path: &str) -> Option<Entity> {
+        self.bones.get(path).copied()
+    }
+
+    /// Starts a clip by its unity name, returns false when there's no such clip.
+    /// Clips loop unless they were imported without `loopTime`.
+    pub fn play(&self, name: &str, players: &mut Query<&mut AnimationPlayer>) -> bool {
+        let (Some(clip), Ok(mut player)) = (self.clips.get(name), players.get_mut(self.player))
+        else {
+            return false;
+        };
+
+        let player = player.play(clip.clone());
+        if !self.once.contains(name) {
+            player.repeat();
+        }
+        true
+    }
+
+    /// Cross fades to a clip, starting `offset` seconds in. Clips loop unless
+    /// they were imported without `loopTime`.
+    pub fn play_with_transition(
+        &self,
+        name: &str,
+        duration: Duration,
+        offset: f32,
+        speed: f32,
+        players: &mut Query<&mut AnimationPlayer>,
+    ) -> bool {
+        let (Some(clip), Ok(mut player)) = (self.clips.get(name), players.get_mut(self.player))
+        else {
+            return false;
+        };
+
+        let player = player.play_with_transition(clip.clone(), duration);
+        if !self.once.contains(name) {
+            player.repeat();
+        }
+        player.seek_to(offset).set_speed(speed);
+        true
+    }
+}
+
+/// Moves the animator by its motion node's horizontal movement instead of
+/// letting the node walk away from it, for `m_ApplyRootMotion`.
+#[derive(Component, Debug)]
+pub struct UnityRootMotion {
+    pub node: Entity,
+    rest: Option<Vec3>,
+    last: Option<(Vec3, f32)>,
+}
+
+#[allow(clippy::type_complexity)]
+fn setup_animator_system(
+    animators: Query<(Entity, &UnityAnimatorExtra), With<UnityAnimatorRequiresSetup>>,
+    models: Query<(&UnityModelInstance, Has<UnityModelRequiresSetup>)>,
+    nodes: Query<(
+        Option<&Children>,
+        Option<&Name>,
+        Has<AnimationPlayer>,
+        Has<UnityAnimatorExtra>,
+    )>,
+    parents: Query<&Parent>,
+    mut players: Query<&mut AnimationPlayer>,
+    gltfs: Res<Assets<Gltf>>,
+    mut commands: Commands,
+) {
+    for (entity, extra) in &animators {
+        // an animator higher up drives the whole hierarchy
+        if parents
+            .iter_ancestors(entity)
+            .any(|ancestor| nodes.get(ancestor).is_ok_and(|(.., animator)| animator))
+        {
+            commands
+                .entity(entity)
+                .remove::<UnityAnimatorRequiresSetup>();
+            continue;
+        }
+
+        let children = |entity: Entity| -> Vec<Entity> {
+            nodes
+                .get(entity)
+                .ok()
+                .and_then(|(children, ..)| children)
+                .map(|children| children.to_vec())
+                .unwrap_or_default()
+        };
+        let mut descendants = vec![];
+        let mut stack = vec![entity];
+        while let Some(next) = stack.pop() {
+            descendants.push(next);
+            stack.extend(children(next));
+        }
+
+        let bone_paths = |roots: Vec<Entity>| {
+            node_paths(roots, children, |node| {
+                Some(nodes.get(node).ok()?.1?.to_string())
+            })
+        };
+
+        let Some((model_root, (model, loading))) = descendants
+            .iter()
+            .find_map(|e| Some((*e, models.get(*e).ok()?)))
+        else {
+            commands
+                .entity(entity)
+                .remove::<UnityAnimatorRequiresSetup>();
+            if extra.animator.controller.guid.is_none() {
+                tracing::warn!("animator without a model or controller");
+                continue;
+            }
+
+            // the controller's `.anim` clips animate the animator's hierarchy
+            commands
+                .entity(entity)
+                .insert((
+                    AnimationPlayer::default(),
+                    UnityAnimation {
+                        model: String::new(),
+                        clips: HashMap::new(),
+                        once: HashSet::new(),
+                        default_clip: None,
+                        player: entity,
+                        bones: bone_paths(children(entity)),
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(Name::new(CLIP_DURATION_NODE));
+                });
+            continue;
+        };
+
+        if loading {
+            continue;
+        }
+
+        let Some(gltf) = model.gltf.as_ref().and_then(|gltf| gltfs.get(gltf)) else {
+            if model.gltf.is_none() {
+                tracing::warn!("model {} has no glTF animations to play", model.guid);
+                commands
+                    .entity(entity)
+                    .remove::<UnityAnimatorRequiresSetup>();
+            }
+            continue;
+        };
+        commands
+            .entity(entity)
+            .remove::<UnityAnimatorRequiresSetup>();
+
+        let Some(player) = descendants
+            .iter()
+            .copied()
+            .find(|e| nodes.get(*e).is_ok_and(|(_, _, player, _)| player))
+        else {
+            // nothing animated
+            continue;
+        };
+
+        // the model root holds the scene's root, whose children are the file's
+        // top level nodes
+        let bones = bone_paths(
+            children(model_root)
+                .into_iter()
+                .flat_map(children)
+                .collect(),
+        );
+
+        let takes = &model.importer.animations.clip_animations;
+        let (clips, once) = model_clips(takes, &gltf.named_animations);
+        let default_clip = default_clip(&clips, gltf.animations.first());
+
+        let animation = UnityAnimation {
+            model: model.guid.clone(),
+            clips,
+            once,
+            default_clip,
+            player,
+            bones,
+        };
+
+        let animator = &extra.animator;
+        if animator.enabled != 0 && animator.controller.guid.is_none() {
+            if let Some(default_clip) = &animation.default_clip {
+                animation.play(default_clip, &mut players);
+            }
+        }
+
+        if animator.apply_root_motion != 0 {
+            let motion_node = model
+                .importer
+                .animations
+                .motion_node_name
+                .as_ref()
+                .and_then(|path| animation.bone(path))
+                .or_else(|| children(player).first().copied());
+
+            if let Some(node) = motion_node {
+                commands.entity(entity).insert(UnityRootMotion {
+                    node,
+                    rest: None,
+                    last: None,
+                });
+            }
+        }
+
+        commands.entity(entity).insert(animation);
+    }
+}
+
+/// The model's clips by their unity name, and the ones that don't loop.
+/// Unity's clip settings rename and pick the file's takes, without any the
+/// takes are used as they are.
+fn model_clips(
+    takes: &[UnityModelClip],
+    named_animations: &bevy::utils::HashMap<String, Handle<AnimationClip>>,
+) -> (HashMap<String, Handle<AnimationClip>>, HashSet<String>) {
+    let clips = if takes.is_empty() {
+        named_animations.clone().into_iter().collect()
+    } else {
+        takes
+            .iter()
+            .filter_map(|clip| {
+                let take = named_animations.get(&clip.take_name)?;
+                Some((clip.name.clone(), take.clone()))
+            })
+            .collect()
+    };
+    let once = takes
+        .iter()
+        .filter(|clip| clip.loop_time == 0)
+        .map(|clip| clip.name.clone())
+        .collect();
+
+    (clips, once)
+}
+
+/// Name of the clip made from the file's first animation, which legacy
+/// animation plays by default.
+fn default_clip(
+    clips: &HashMap<String, Handle<AnimationClip>>,
+    first: Option<&Handle<AnimationClip>>,
+) -> Option<String> {
+    let first = first?;
+    clips
+        .iter()
+        .find(|(_, clip)| *clip == first)
+        .map(|(name, _)| name.clone())
+}
+
+/// Node paths below `roots` as used by unity's `Transform.Find` and
+/// animation curves.
+pub(crate) fn node_paths(
+    roots: Vec<Entity>,
+    children: impl Fn(Entity) -> Vec<Entity>,
+    name: impl Fn(Entity) -> Option<String>,
+) -> HashMap<String, Entity> {
+    let mut paths = HashMap::new();
+    let mut stack: Vec<_> = roots.into_iter().map(|e| (e, String::new())).collect();
+    while let Some((node, parent_path)) = stack.pop() {
+        let Some(name) = name(node) else {
+            continue;
+        };
+        let path = if parent_path.is_empty() {
+            name
+        } else {
+            format!("{}/{}", parent_path, name)
+        };
+        stack.extend(children(node).into_iter().map(|c| (c, path.clone())));
+        paths.insert(path, node);
+    }
+    paths
+}
+
+fn root_motion_system(
+    mut animators: Query<(Entity, &UnityAnimation, &mut UnityRootMotion)>,
+    players: Query<&AnimationPlayer>,
+    mut transforms: Query<&mut Transform>,
+    globals: Query<&GlobalTransform>,
+    parents: Query<&Parent>,
+) {
+    for (entity, animation, mut motion) in &mut animators {
+        let Ok(player) = players.get(animation.player) else {
+            continue;
+        };
+        let Ok(mut node) = transforms.get_mut(motion.node) else {
+            continue;
+        };
+
+        let animated = node.translation;
+        let rest = *motion.rest.get_or_insert(animated);
+        node.translation.x = rest.x;
+        node.translation.z = rest.z;
+
+        let elapsed = player.elapsed();
+        let last = motion.last.replace((animated, elapsed));
+        // nothing moved yet, or the clip looped back to its start
+        let Some((last, _)) = last.filter(|(_, time)| *time < elapsed) else {
+            continue;
+        };
+
+        let delta = animated - last;
+        let delta = Vec3::new(delta.x, 0.0, delta.z);
+        let to_world = |entity: Entity| {
+            parents
+                .get(entity)
+                .ok()
+                .and_then(|parent| globals.get(parent.get()).ok())
+                .map(|global| global.affine())
+                .unwrap_or_default()
+        };
+
+        let world_delta = to_world(motion.node).transform_vector3(delta);
+        let local_delta = to_world(entity).inverse().transform_vector3(world_delta);
+        if let Ok(mut transform) = transforms.get_mut(entity) {
+            transform.translation += local_delta;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(id: u128) -> Handle<AnimationClip> {
+        Handle::weak_from_u128(id)
+    }
+
+    fn clip(name: &str, take_name: &str, loop_time: u8) -> UnityModelClip {
+        UnityModelClip {
+            name: name.to_string(),
+            take_name: take_name.to_string(),
+            loop_time,
+        }
+    }
+
+    #[test]
+    fn test_node_paths() {
+        // 0 has children 1 and 2, 2 has child 3, 4 is a second root
+        let entities: Vec<_> = (0..5).map(Entity::from_raw).collect();
+        let children = |entity: Entity| match entity.index() {
+            0 => vec![entities[1], entities[2]],
+            2 => vec![entities[3]],
+            _ => vec![],
+        };
+        let names = ["Hips", "LeftLeg", "Spine", "Head", "Camera"];
+        let paths = node_paths(vec![entities[0], entities[4]], children, |entity| {
+            Some(names[entity.index() as usize].to_string())
+        });
+
+        assert_eq!(paths.len(), 5);
+        assert_eq!(paths["Hips"], entities[0]);
+        assert_eq!(paths["Hips/LeftLeg"], entities[1]);
+        assert_eq!(paths["Hips/Spine/Head"], entities[3]);
+        assert_eq!(paths["Camera"], entities[4]);
+
+        // unnamed nodes and what's below them can't be found
+        let paths = node_paths(vec![entities[0]], children, |entity| {
+            (entity.index() != 2).then(|| names[entity.index() as usize].to_string())
+        });
+        assert_eq!(paths.len(), 2);
+        assert!(!paths.contains_key("Hips/Spine/Head"));
+    }
+
+    #[test]
+    fn test_model_clips_use_takes_without_clip_settings() {
+        let takes = bevy::utils::HashMap::from([
+            ("Take 001".to_string(), handle(1)),
+            ("Run".to_string(), handle(2)),
+        ]);
+
+        let (clips, once) = model_clips(&[], &takes);
+        assert_eq!(clips.len(), 2);
+        assert_eq!(clips["Run"], handle(2));
+        assert!(once.is_empty());
+    }
+
+    #[test]
+    fn test_model_clips_renamed_by_clip_settings() {
+        let takes = bevy::utils::HashMap::from([
+            ("mixamo.com".to_string(), handle(1)),
+            ("Armature|Jump".to_string(), handle(2)),
+        ]);
+        let settings = [
+            clip("Walk", "mixamo.com", 1),
+            clip("Jump", "Armature|Jump", 0),
+            clip("Missing", "Armature|Missing", 1),
+        ];
+
+        let (clips, once) = model_clips(&settings, &takes);
+        assert_eq!(clips.len(), 2);
+        assert_eq!(clips["Walk"], handle(1));
+        assert_eq!(clips["Jump"], handle(2));
+        assert_eq!(once, HashSet::from(["Jump".to_string()]));
+
+        // the file's first animation, under its unity name
+        assert_eq!(
+            default_clip(&clips, Some(&handle(2))),
+            Some("Jump".to_string())
+        );
+        assert_eq!(default_clip(&clips, Some(&handle(3))), None);
+        assert_eq!(default_clip(&clips, None), None);
+    }
+}
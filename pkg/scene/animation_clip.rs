@@ -0,0 +1,428 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use bevity_primitives::{
+    CurveFloat, UnityAnimatedProperty, UnityAnimationClip, UnityAnimationCurve,
+    UnityLegacyAnimationExtra, UnityLegacyAnimationRequiresSetup, UnityLightExtra,
+    CLIP_DURATION_NODE,
+};
+use bevity_yaml::parse_unity_yaml;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{node_paths, UnityAnimation, UnityResource};
+
+pub fn read_animation_clip(path: &Path) -> Result<UnityAnimationClip> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read animation clip {}", path.display()))?;
+
+    read_single_animation_clip(&contents)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "object_type")]
+enum AnimationClipContainer {
+    AnimationClip(Box<UnityAnimationClip>),
+    #[serde(other)]
+    DontCare,
+}
+
+fn read_single_animation_clip(contents: &str) -> Result<UnityAnimationClip> {
+    let map = parse_unity_yaml(contents)?;
+
+    let Some(AnimationClipContainer::AnimationClip(clip)) = map.into_values().next() else {
+        bail!("invalid animation clip file");
+    };
+
+    Ok(*clip)
+}
+
+/// Float curves of an animator's `.anim` clips, applied while the clip plays.
+#[derive(Component, Debug, Default)]
+pub struct UnityPropertyAnimation {
+    pub clips: HashMap<AssetId<AnimationClip>, Vec<UnityPropertyCurve>>,
+}
+
+#[derive(Debug)]
+pub struct UnityPropertyCurve {
+    pub target: Entity,
+    pub property: UnityAnimatedProperty,
+    pub curve: UnityAnimationCurve<CurveFloat>,
+    /// Clip time the player's time starts at.
+    pub start_time: f32,
+}
+
+impl UnityPropertyAnimation {
+    /// Adds the clip's float curves, with paths resolved through the
+    /// animation's bones.
+    pub fn add_clip(
+        &mut self,
+        handle: &Handle<AnimationClip>,
+        clip: &UnityAnimationClip,
+        animator: Entity,
+        animation: &UnityAnimation,
+    ) {
+        let curves = clip
+            .float_curves
+            .iter()
+            .filter_map(|curve| {
+                let Some(property) = UnityAnimatedProperty::from_curve(curve) else {
+                    tracing::warn!(
+                        "unsupported animated property {} in clip {}",
+                        curve.attribute,
+                        clip.name
+                    );
+                    return None;
+                };
+                let target = if curve.path.is_empty() {
+                    animator
+                } else {
+                    animation.bone(&curve.path)?
+                };
+
+                Some(UnityPropertyCurve {
+                    target,
+                    property,
+                    curve: curve.curve.clone(),
+                    start_time: clip.settings.start_time,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if !curves.is_empty() {
+            self.clips.insert(handle.id(), curves);
+        }
+    }
+}
+
+/// Material copies owned by an animated renderer, so animating one doesn't
+/// change every renderer sharing the material.
+#[derive(Component)]
+pub struct UnityAnimatedMaterial;
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn property_animation_system(
+    animators: Query<(&UnityAnimation, &UnityPropertyAnimation)>,
+    players: Query<&AnimationPlayer>,
+    mut targets: Query<(
+        Option<&mut Visibility>,
+        Option<&mut UnityLightExtra>,
+        Option<&mut DirectionalLight>,
+        Option<&mut PointLight>,
+        Option<&mut SpotLight>,
+        Option<&Children>,
+    )>,
+    mut renderers: Query<(&mut Handle<StandardMaterial>, Has<UnityAnimatedMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    // several curves can animate one material in the same frame
+    let mut copied = HashSet::new();
+    for (animation, properties) in &animators {
+        let Ok(player) = players.get(animation.player) else {
+            continue;
+        };
+        let Some(curves) = properties.clips.get(&player.animation_clip().id()) else {
+            continue;
+        };
+
+        for curve in curves {
+            let Some(CurveFloat(value)) = curve.curve.sample(curve.start_time + player.seek_time())
+            else {
+                continue;
+            };
+            let Ok((visibility, light, directional, point, spot, children)) =
+                targets.get_mut(curve.target)
+            else {
+                continue;
+            };
+
+            match &curve.property {
+                UnityAnimatedProperty::Active => {
+                    if let Some(mut visibility) = visibility {
+                        *visibility = if value > 0.5 {
+                            Visibility::Inherited
+                        } else {
+                            Visibility::Hidden
+                        };
+                    }
+                }
+                UnityAnimatedProperty::LightIntensity | UnityAnimatedProperty::LightColor(_) => {
+                    let Some(mut extra) = light else {
+                        continue;
+                    };
+                    let light = &mut extra.light;
+                    match curve.property {
+                        UnityAnimatedProperty::LightColor(channel) => {
+                            let color = &mut light.color;
+                            match channel {
+                                0 => color.r = value,
+                                1 => color.g = value,
+                                2 => color.b = value,
+                                _ => color.a = value,
+                            }
+                        }
+                        _ => light.intensity = value,
+                    }
+
+                    let (color, intensity) = (light.light_color(), light.bevy_intensity());
+                    if let Some(mut directional) = directional {
+                        directional.color = color;
+                        directional.illuminance = intensity;
+                    }
+                    if let Some(mut point) = point {
+                        point.color = color;
+                        point.intensity = intensity;
+                    }
+                    if let Some(mut spot) = spot {
+                        spot.color = color;
+                        spot.intensity = intensity;
+                    }
+                }
+                UnityAnimatedProperty::MaterialColor { name, channel } => {
+                    // renderers with several materials keep them on children
+                    let submeshes = children.map(|c| c.to_vec()).unwrap_or_default();
+                    for entity in std::iter::once(curve.target).chain(submeshes) {
+                        let Ok((mut handle, owned)) = renderers.get_mut(entity) else {
+                            continue;
+                        };
+                        if !owned && copied.insert(entity) {
+                            let Some(copy) = materials.get(handle.id()).cloned() else {
+                                continue;
+                            };
+                            *handle = materials.add(copy);
+                            commands.entity(entity).insert(UnityAnimatedMaterial);
+                        }
+                        let Some(material) = materials.get_mut(handle.id()) else {
+                            continue;
+                        };
+
+                        let color = match name.as_str() {
+                            "_Color" | "_BaseColor" | "_MainColor" => &mut material.base_color,
+                            "_EmissionColor" => &mut material.emissive,
+                            _ => continue,
+                        };
+                        match channel {
+                            0 => color.set_r(value),
+                            1 => color.set_g(value),
+                            2 => color.set_b(value),
+                            _ => color.set_a(value),
+                        };
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Plays a legacy `Animation` component's clips on its own hierarchy.
+#[allow(clippy::type_complexity)]
+pub(crate) fn setup_legacy_animation_system<T: Sync + Send + 'static + Default>(
+    animations: Query<
+        (Entity, &UnityLegacyAnimationExtra, &Name),
+        With<UnityLegacyAnimationRequiresSetup>,
+    >,
+    nodes: Query<(Option<&Children>, Option<&Name>)>,
+    mut unity_res: ResMut<UnityResource<T>>,
+    mut clip_assets: ResMut<Assets<AnimationClip>>,
+    mut commands: Commands,
+) {
+    for (entity, extra, name) in &animations {
+        let legacy = &extra.animation;
+        let children = |node: Entity| -> Vec<Entity> {
+            nodes
+                .get(node)
+                .ok()
+                .and_then(|(children, _)| children)
+                .map(|children| children.to_vec())
+                .unwrap_or_default()
+        };
+
+        let mut animation = UnityAnimation {
+            model: String::new(),
+            clips: HashMap::new(),
+            once: HashSet::new(),
+            default_clip: None,
+            player: entity,
+            bones: node_paths(children(entity), children, |node| {
+                Some(nodes.get(node).ok()?.1?.to_string())
+            }),
+        };
+        let mut properties = UnityPropertyAnimation::default();
+
+        for reference in legacy.animations.iter().chain([&legacy.animation]) {
+            let Some(guid) = &reference.guid else {
+                continue;
+            };
+            let Some(clip) = unity_res.animation_clip(guid) else {
+                tracing::warn!("no animation clip with guid {}", guid);
+                continue;
+            };
+            if animation.clips.contains_key(&clip.name) {
+                continue;
+            }
+            if clip.legacy == 0 {
+                tracing::warn!(
+                    "clip {} isn't legacy, so Animation can't play it",
+                    clip.name
+                );
+                continue;
+            }
+
+            let handle = clip_assets.add(clip.to_animation_clip(name));
+            properties.add_clip(&handle, clip, entity, &animation);
+            if !clip.loops() {
+                animation.once.insert(clip.name.clone());
+            }
+            if legacy.animation.guid.as_ref() == Some(guid) {
+                animation.default_clip = Some(clip.name.clone());
+            }
+            animation.clips.insert(clip.name.clone(), handle);
+        }
+
+        let mut player = AnimationPlayer::default();
+        if legacy.enabled != 0 && legacy.play_automatically != 0 {
+            if let Some(default_clip) = &animation.default_clip {
+                let playing = player.play(animation.clips[default_clip].clone());
+                if !animation.once.contains(default_clip) {
+                    playing.repeat();
+                }
+            }
+        }
+
+        let mut cmd = commands.entity(entity);
+        cmd.remove::<UnityLegacyAnimationRequiresSetup>();
+        cmd.insert((player, animation)).with_children(|parent| {
+            parent.spawn(Name::new(CLIP_DURATION_NODE));
+        });
+        if !properties.clips.is_empty() {
+            cmd.insert(properties);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIP: &str = r#"%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!74 &7400000
+AnimationClip:
+  m_ObjectHideFlags: 0
+  m_Name: Door
+  serializedVersion: 7
+  m_Legacy: 0
+  m_RotationCurves: []
+  m_EulerCurves:
+  - curve:
+      serializedVersion: 2
+      m_Curve:
+      - serializedVersion: 3
+        time: 0
+        value: {x: 0, y: 0, z: 0}
+        inSlope: {x: 0, y: 0, z: 0}
+        outSlope: {x: 0, y: 0, z: 0}
+        tangentMode: 0
+        weightedMode: 0
+      - serializedVersion: 3
+        time: 1
+        value: {x: 0, y: 90, z: 0}
+        inSlope: {x: 0, y: 0, z: 0}
+        outSlope: {x: 0, y: 0, z: 0}
+        tangentMode: 0
+        weightedMode: 0
+      m_PreInfinity: 2
+      m_PostInfinity: 2
+      m_RotationOrder: 4
+    path: Hinge
+  m_PositionCurves: []
+  m_ScaleCurves: []
+  m_FloatCurves:
+  - curve:
+      serializedVersion: 2
+      m_Curve:
+      - serializedVersion: 3
+        time: 0
+        value: 1
+        inSlope: 0
+        outSlope: Infinity
+        tangentMode: 103
+        weightedMode: 0
+      - serializedVersion: 3
+        time: 0.5
+        value: 0
+        inSlope: Infinity
+        outSlope: 0
+        tangentMode: 103
+        weightedMode: 0
+      m_PreInfinity: 2
+      m_PostInfinity: 2
+      m_RotationOrder: 4
+    attribute: m_Intensity
+    path:
+    classID: 108
+    script: {fileID: 0}
+  m_SampleRate: 60
+  m_WrapMode: 0
+  m_AnimationClipSettings:
+    serializedVersion: 2
+    m_StartTime: 0
+    m_StopTime: 1
+    m_LoopTime: 1
+"#;
+
+    #[test]
+    fn test_read_animation_clip() -> Result<()> {
+        let clip = read_single_animation_clip(CLIP)?;
+        assert_eq!(clip.name, "Door");
+        assert!(clip.loops());
+        assert_eq!(clip.duration(), 1.0);
+
+        // smooth keys ease in and out, stepped ones hold
+        let hinge = &clip.euler_curves[0];
+        assert_eq!(hinge.path, "Hinge");
+        assert_eq!(hinge.curve.sample(0.5).map(|v| v.y), Some(45.0));
+        let intensity = &clip.float_curves[0];
+        assert_eq!(intensity.path, "");
+        assert_eq!(
+            UnityAnimatedProperty::from_curve(intensity),
+            Some(UnityAnimatedProperty::LightIntensity)
+        );
+        assert_eq!(intensity.curve.sample(0.25).map(|v| v.0), Some(1.0));
+
+        let bevy_clip = clip.to_animation_clip(&Name::new("Door"));
+        assert_eq!(bevy_clip.duration(), 1.0);
+        let path = EntityPath {
+            parts: vec![Name::new("Door"), Name::new("Hinge")],
+        };
+        let curves = bevy_clip.get_curves_by_path(&path).context("no hinge")?;
+        let Keyframes::Rotation(rotations) = &curves[0].keyframes else {
+            bail!("not a rotation");
+        };
+        // a quarter turn around y, mirrored into bevy's coordinates
+        let end = Quat::from_rotation_y(-std::f32::consts::FRAC_PI_2);
+        let last = rotations.last().context("no keys")?;
+        assert!(last.dot(end).abs() > 0.9999);
+
+        // clips of only float curves last as long through the duration node,
+        // leaving the root's transform alone
+        let mut light_only = clip.clone();
+        light_only.euler_curves.clear();
+        let bevy_clip = light_only.to_animation_clip(&Name::new("Door"));
+        assert_eq!(bevy_clip.duration(), 1.0);
+        let root = EntityPath {
+            parts: vec![Name::new("Door")],
+        };
+        assert!(bevy_clip.get_curves_by_path(&root).is_none());
+        let duration_node = EntityPath {
+            parts: vec![Name::new("Door"), Name::new(CLIP_DURATION_NODE)],
+        };
+        assert!(bevy_clip.get_curves_by_path(&duration_node).is_some());
+
+        Ok(())
+    }
+}
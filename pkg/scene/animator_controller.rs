@@ -0,0 +1,506 @@
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use bevity_primitives::{
+    AnimatorControllerGraph, AnimatorGraphTransition, UnityAnimatorControllerObject,
+    UnityAnimatorExtra, CONDITION_EQUALS, CONDITION_GREATER, CONDITION_IF, CONDITION_IF_NOT,
+    CONDITION_LESS, CONDITION_NOT_EQUAL, PARAMETER_BOOL, PARAMETER_INT, PARAMETER_TRIGGER,
+};
+use bevity_yaml::parse_unity_yaml;
+use bevy::prelude::*;
+
+use crate::{UnityAnimation, UnityPropertyAnimation, UnityResource};
+
+pub fn read_animator_controller(path: &Path) -> Result<AnimatorControllerGraph> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read controller {}", path.display()))?;
+    let objects = parse_unity_yaml::<UnityAnimatorControllerObject>(&contents)?;
+
+    AnimatorControllerGraph::new(&objects)
+        .with_context(|| format!("invalid animator controller {}", path.display()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimatorParameter {
+    Float(f32),
+    Int(i32),
+    Bool(bool),
+    /// Set until a transition consumes it.
+    Trigger(bool),
+}
+
+/// Runs an `AnimatorController`'s base layer, cross fading the animator's
+/// clips as it changes state.
+#[derive(Component, Debug, Clone)]
+pub struct UnityAnimatorController {
+    graph: Arc<AnimatorControllerGraph>,
+    parameters: HashMap<String, AnimatorParameter>,
+    /// Clip name of each state, see [`UnityAnimation::clips`].
+    clips: Vec<Option<String>>,
+    state: usize,
+    /// Normalized time in the current state, before and after this frame.
+    previous_time: f32,
+    time: f32,
+    /// Seconds left of the current transition, which isn't interrupted.
+    transition_left: f32,
+    started: bool,
+}
+
+/// A state change for the animation player.
+#[derive(Debug, PartialEq)]
+pub struct AnimatorStateChange {
+    pub state: usize,
+    /// Cross fade in seconds.
+    pub duration: f32,
+    /// Normalized start time in the new state.
+    pub offset: f32,
+}
+
+impl UnityAnimatorController {
+    pub fn new(graph: Arc<AnimatorControllerGraph>, clips: Vec<Option<String>>) -> Self {
+        let parameters = graph
+            .parameters
+            .iter()
+            .map(|p| {
+                let value = match p.parameter_type {
+                    PARAMETER_INT => AnimatorParameter::Int(p.default_int),
+                    PARAMETER_BOOL => AnimatorParameter::Bool(p.default_bool != 0),
+                    PARAMETER_TRIGGER => AnimatorParameter::Trigger(false),
+                    _ => AnimatorParameter::Float(p.default_float),
+                };
+                (p.name.clone(), value)
+            })
+            .collect();
+
+        Self {
+            state: graph.default_state,
+            graph,
+            parameters,
+            clips,
+            previous_time: 0.0,
+            time: 0.0,
+            transition_left: 0.0,
+            started: false,
+        }
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<AnimatorParameter> {
+        self.parameters.get(name).copied()
+    }
+
+    fn set(&mut self, name: &str, value: AnimatorParameter) {
+        match self.parameters.get_mut(name) {
+            Some(parameter)
+                if std::mem::discriminant(parameter) == std::mem::discriminant(&value) =>
+            {
+                *parameter = value
+            }
+            Some(_) => tracing::warn!("animator parameter {} has another type", name),
+            None => tracing::warn!("no animator parameter {}", name),
+        }
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        self.set(name, AnimatorParameter::Float(value));
+    }
+
+    pub fn set_integer(&mut self, name: &str, value: i32) {
+        self.set(name, AnimatorParameter::Int(value));
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.set(name, AnimatorParameter::Bool(value));
+    }
+
+    pub fn set_trigger(&mut self, name: &str) {
+        self.set(name, AnimatorParameter::Trigger(true));
+    }
+
+    pub fn reset_trigger(&mut self, name: &str) {
+        self.set(name, AnimatorParameter::Trigger(false));
+    }
+
+    pub fn current_state(&self) -> &str {
+        &self.graph.states[self.state].name
+    }
+
+    pub fn normalized_time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn is_in_transition(&self) -> bool {
+        self.transition_left > 0.0
+    }
+
+    fn condition_met(&self, transition: &AnimatorGraphTransition) -> bool {
+        transition.transition.conditions.iter().all(|condition| {
+            let threshold = condition.threshold;
+            match (condition.mode, self.parameter(&condition.parameter)) {
+                (
+                    CONDITION_IF,
+                    Some(AnimatorParameter::Bool(v) | AnimatorParameter::Trigger(v)),
+                ) => v,
+                (CONDITION_IF_NOT, Some(AnimatorParameter::Bool(v))) => !v,
+                (CONDITION_GREATER, Some(AnimatorParameter::Float(v))) => v > threshold,
+                (CONDITION_GREATER, Some(AnimatorParameter::Int(v))) => v as f32 > threshold,
+                (CONDITION_LESS, Some(AnimatorParameter::Float(v))) => v < threshold,
+                (CONDITION_LESS, Some(AnimatorParameter::Int(v))) => (v as f32) < threshold,
+                (CONDITION_EQUALS, Some(AnimatorParameter::Int(v))) => v == threshold as i32,
+                (CONDITION_NOT_EQUAL, Some(AnimatorParameter::Int(v))) => v != threshold as i32,
+                _ => false,
+            }
+        })
+    }
+
+    /// Whether the exit time was passed this frame. Below 1 it's passed on
+    /// every loop.
+    fn exit_time_passed(&self, exit_time: f32) -> bool {
+        if exit_time < 1.0 {
+            let loops = (self.time - exit_time).floor();
+            loops >= 0.0 && exit_time + loops > self.previous_time
+        } else {
+            self.previous_time < exit_time && self.time >= exit_time
+        }
+    }
+
+    fn can_take(&self, transition: &AnimatorGraphTransition, any_state: bool) -> bool {
+        let settings = &transition.transition;
+        if settings.mute != 0 {
+            return false;
+        }
+        if any_state && settings.can_transition_to_self == 0 && transition.destination == self.state
+        {
+            return false;
+        }
+        // unity ignores transitions that could never end
+        if settings.has_exit_time == 0 && settings.conditions.is_empty() {
+            return false;
+        }
+        if settings.has_exit_time != 0 && !self.exit_time_passed(settings.exit_time) {
+            return false;
+        }
+
+        self.condition_met(transition)
+    }
+
+    /// Advances by `delta` seconds, with `clip_length` the length of a state's
+    /// clip in seconds.
+    pub fn update(
+        &mut self,
+        delta: f32,
+        clip_length: impl Fn(usize) -> Option<f32>,
+    ) -> Option<AnimatorStateChange> {
+        if !self.started {
+            self.started = true;
+            return Some(AnimatorStateChange {
+                state: self.state,
+                duration: 0.0,
+                offset: 0.0,
+            });
+        }
+
+        let length = |state: usize| clip_length(state).filter(|l| *l > 0.0).unwrap_or(1.0);
+        let state = &self.graph.states[self.state];
+        self.previous_time = self.time;
+        self.time += delta * state.speed / length(self.state);
+
+        if self.transition_left > 0.0 {
+            self.transition_left -= delta;
+            if self.transition_left > 0.0 {
+                return None;
+            }
+        }
+
+        // any state transitions go first, solo transitions hide the others
+        let pick = |transitions: &[AnimatorGraphTransition], any_state: bool| {
+            let solo = transitions.iter().any(|t| t.transition.solo != 0);
+            transitions
+                .iter()
+                .filter(|t| !solo || t.transition.solo != 0)
+                .find(|t| self.can_take(t, any_state))
+                .cloned()
+        };
+        let transition = pick(&self.graph.any_state_transitions, true)
+            .or_else(|| pick(&state.transitions, false))?;
+
+        let settings = &transition.transition;
+        for condition in &settings.conditions {
+            if let Some(AnimatorParameter::Trigger(true)) = self.parameter(&condition.parameter) {
+                self.reset_trigger(&condition.parameter);
+            }
+        }
+
+        let duration = if settings.has_fixed_duration != 0 {
+            settings.duration
+        } else {
+            settings.duration * length(self.state)
+        };
+
+        self.state = transition.destination;
+        self.previous_time = settings.offset;
+        self.time = settings.offset;
+        self.transition_left = duration;
+
+        Some(AnimatorStateChange {
+            state: self.state,
+            duration,
+            offset: settings.offset,
+        })
+    }
+}
+
+/// Gives animators with a controller their state machine once their model's
+/// clips are known, loading the `.anim` clips of animators without a model.
+pub(crate) fn setup_animator_controller_system<T: Sync + Send + 'static + Default>(
+    mut animators: Query<
+        (Entity, &UnityAnimatorExtra, &mut UnityAnimation, &Name),
+        Added<UnityAnimation>,
+    >,
+    mut unity_res: ResMut<UnityResource<T>>,
+    mut clip_assets: ResMut<Assets<AnimationClip>>,
+    mut commands: Commands,
+) {
+    for (entity, extra, mut animation, name) in &mut animators {
+        let Some(guid) = &extra.animator.controller.guid else {
+            continue;
+        };
+        let Some(graph) = unity_res.animator_controller(guid) else {
+            continue;
+        };
+
+        // motions are clips in the model, or .anim files
+        let mut properties = UnityPropertyAnimation::default();
+        let clips = graph
+            .states
+            .iter()
+            .map(|state| {
+                let motion = &state.motion;
+                let Some(motion_guid) = &motion.guid else {
+                    // blend trees live in the controller itself
+                    if motion.file_id != 0 {
+                        tracing::warn!("unsupported local motion in animator state {}", state.name);
+                    }
+                    return None;
+                };
+                if *motion_guid == animation.model {
+                    let objects = unity_res.model_objects.get(&animation.model);
+                    return objects.and_then(|o| o.get(&motion.file_id)).cloned();
+                }
+
+                // .anim paths start at the animator, which holds the player
+                // only when there's no model
+                let clip = if animation.player == entity {
+                    unity_res.animation_clip(motion_guid)
+                } else {
+                    None
+                };
+                let Some(clip) = clip else {
+                    tracing::warn!("unsupported motion in animator state {}", state.name);
+                    return None;
+                };
+
+                if !animation.clips.contains_key(&clip.name) {
+                    let handle = clip_assets.add(clip.to_animation_clip(name));
+                    properties.add_clip(&handle, clip, entity, &animation);
+                    if !clip.loops() {
+                        animation.once.insert(clip.name.clone());
+                    }
+                    animation.clips.insert(clip.name.clone(), handle);
+                }
+                Some(clip.name.clone())
+            })
+            .collect();
+
+        let mut cmd = commands.entity(entity);
+        cmd.insert(UnityAnimatorController::new(graph, clips));
+        if !properties.clips.is_empty() {
+            cmd.insert(properties);
+        }
+    }
+}
+
+pub(crate) fn animator_controller_system(
+    mut animators: Query<(&UnityAnimation, &mut UnityAnimatorController)>,
+    mut players: Query<&mut AnimationPlayer>,
+    clips: Res<Assets<AnimationClip>>,
+    time: Res<Time>,
+) {
+    for (animation, mut controller) in &mut animators {
+        let clip_length = |state: usize| {
+            let name = controller_clip(&controller, state)?;
+            Some(clips.get(animation.clip(name)?)?.duration())
+        };
+        let lengths: Vec<_> = (0..controller.graph.states.len())
+            .map(clip_length)
+            .collect();
+
+        let Some(change) = controller.update(time.delta_seconds(), |state| lengths[state]) else {
+            continue;
+        };
+
+        let speed = controller.graph.states[change.state].speed;
+        let Some(name) = controller_clip(&controller, change.state) else {
+            continue;
+        };
+        let offset = change.offset * lengths[change.state].unwrap_or_default();
+        animation.play_with_transition(
+            name,
+            Duration::from_secs_f32(change.duration.max(0.0)),
+            offset,
+            speed,
+            &mut players,
+        );
+    }
+}
+
+fn controller_clip(controller: &UnityAnimatorController, state: usize) -> Option<&str> {
+    controller.clips.get(state)?.as_deref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTROLLER: &str = r#"%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!91 &9100000
+AnimatorController:
+  m_Name: Player
+  m_AnimatorParameters:
+  - m_Name: Speed
+    m_Type: 1
+    m_DefaultFloat: 0
+    m_DefaultInt: 0
+    m_DefaultBool: 0
+  - m_Name: Jump
+    m_Type: 9
+    m_DefaultFloat: 0
+    m_DefaultInt: 0
+    m_DefaultBool: 0
+  m_AnimatorLayers:
+  - serializedVersion: 5
+    m_Name: Base Layer
+    m_StateMachine: {fileID: 1107000000000000001}
+--- !u!1107 &1107000000000000001
+AnimatorStateMachine:
+  m_Name: Base Layer
+  m_ChildStates:
+  - serializedVersion: 1
+    m_State: {fileID: 1102000000000000001}
+  - serializedVersion: 1
+    m_State: {fileID: 1102000000000000002}
+  - serializedVersion: 1
+    m_State: {fileID: 1102000000000000003}
+  m_ChildStateMachines: []
+  m_AnyStateTransitions:
+  - {fileID: 1101000000000000003}
+  m_DefaultState: {fileID: 1102000000000000001}
+--- !u!1102 &1102000000000000001
+AnimatorState:
+  m_Name: Idle
+  m_Speed: 1
+  m_Transitions:
+  - {fileID: 1101000000000000001}
+  m_Motion: {fileID: 0}
+--- !u!1102 &1102000000000000002
+AnimatorState:
+  m_Name: Run
+  m_Speed: 1
+  m_Transitions: []
+  m_Motion: {fileID: 0}
+--- !u!1102 &1102000000000000003
+AnimatorState:
+  m_Name: Jump
+  m_Speed: 1
+  m_Transitions:
+  - {fileID: 1101000000000000002}
+  m_Motion: {fileID: 0}
+--- !u!1101 &1101000000000000001
+AnimatorStateTransition:
+  m_Conditions:
+  - m_ConditionMode: 3
+    m_ConditionEvent: Speed
+    m_EventTreshold: 0.1
+  m_DstStateMachine: {fileID: 0}
+  m_DstState: {fileID: 1102000000000000002}
+  m_Solo: 0
+  m_Mute: 0
+  m_IsExit: 0
+  m_TransitionDuration: 0.25
+  m_TransitionOffset: 0
+  m_ExitTime: 0.75
+  m_HasExitTime: 0
+  m_HasFixedDuration: 1
+  m_CanTransitionToSelf: 1
+--- !u!1101 &1101000000000000002
+AnimatorStateTransition:
+  m_Conditions: []
+  m_DstStateMachine: {fileID: 0}
+  m_DstState: {fileID: 1102000000000000001}
+  m_Solo: 0
+  m_Mute: 0
+  m_IsExit: 0
+  m_TransitionDuration: 0.5
+  m_TransitionOffset: 0
+  m_ExitTime: 0.9
+  m_HasExitTime: 1
+  m_HasFixedDuration: 0
+  m_CanTransitionToSelf: 1
+--- !u!1101 &1101000000000000003
+AnimatorStateTransition:
+  m_Conditions:
+  - m_ConditionMode: 1
+    m_ConditionEvent: Jump
+    m_EventTreshold: 0
+  m_DstStateMachine: {fileID: 0}
+  m_DstState: {fileID: 1102000000000000003}
+  m_Solo: 0
+  m_Mute: 0
+  m_IsExit: 0
+  m_TransitionDuration: 0
+  m_TransitionOffset: 0
+  m_ExitTime: 0
+  m_HasExitTime: 0
+  m_HasFixedDuration: 1
+  m_CanTransitionToSelf: 0
+"#;
+
+    #[test]
+    fn test_animator_controller_transitions() -> Result<()> {
+        let objects = parse_unity_yaml::<UnityAnimatorControllerObject>(CONTROLLER)?;
+        let graph = AnimatorControllerGraph::new(&objects).context("no graph")?;
+        let mut controller = UnityAnimatorController::new(Arc::new(graph), vec![None; 3]);
+        let length = |_| Some(2.0);
+
+        assert!(controller.update(0.1, length).is_some());
+        assert_eq!(controller.current_state(), "Idle");
+        assert_eq!(controller.update(0.1, length), None);
+
+        controller.set_float("Speed", 1.0);
+        let change = controller.update(0.1, length).context("no transition")?;
+        assert_eq!(controller.current_state(), "Run");
+        assert_eq!(change.duration, 0.25);
+
+        // transitions aren't interrupted
+        controller.set_trigger("Jump");
+        controller.update(0.1, length);
+        assert_eq!(controller.current_state(), "Run");
+
+        // the trigger is consumed by the any state transition
+        controller.update(0.2, length);
+        assert_eq!(controller.current_state(), "Jump");
+        assert_eq!(
+            controller.parameter("Jump"),
+            Some(AnimatorParameter::Trigger(false))
+        );
+
+        // back to idle after 90% of the 2 second clip
+        controller.update(1.0, length);
+        assert_eq!(controller.current_state(), "Jump");
+        let change = controller.update(1.0, length).context("no exit")?;
+        assert_eq!(controller.current_state(), "Idle");
+        assert_eq!(change.duration, 1.0);
+
+        Ok(())
+    }
+}
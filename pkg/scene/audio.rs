@@ -0,0 +1,145 @@
+use bevity_primitives::{UnityAudioSourceExtra, UnityAudioSourceRequiresLoad};
+use bevy::{audio::SpatialScale, prelude::*};
+
+use crate::{
+    audio_mixer_system, load_audio_mixers_system, AudioMixers, UnityAudioMixerOutput, UnityResource,
+};
+
+/// Attenuates unity `AudioSource`s by their rolloff settings and the
+/// `AudioMixer` groups they play through.
+pub struct AudioImportPlugin;
+
+impl Plugin for AudioImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioMixers>()
+            .add_systems(Startup, load_audio_mixers_system)
+            .add_systems(Update, (audio_mixer_system, audio_volume_system).chain());
+    }
+}
+
+/// Starts audio sources once their clip's path is known.
+pub(crate) fn load_audio_source_system<T: Sync + Send + 'static + Default>(
+    sources: Query<(Entity, &UnityAudioSourceExtra), With<UnityAudioSourceRequiresLoad>>,
+    unity_res: Res<UnityResource<T>>,
+    mixers: Res<AudioMixers>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for (entity, extra) in &sources {
+        let mut cmd = commands.entity(entity);
+        cmd.remove::<UnityAudioSourceRequiresLoad>();
+
+        let source = &extra.source;
+        if let Some(mixer) = &source.output_mixer_group.guid {
+            let file_id = source.output_mixer_group.file_id;
+            match mixers
+                .mixers
+                .get(mixer)
+                .and_then(|m| m.group_by_file_id(file_id))
+            {
+                Some(group) => {
+                    cmd.insert(UnityAudioMixerOutput {
+                        mixer: mixer.clone(),
+                        group,
+                    });
+                }
+                None => tracing::warn!("no audio mixer group {} in {}", file_id, mixer),
+            }
+        }
+
+        let Some(guid) = &source.clip.guid else {
+            // sources without a clip are only played from code
+            continue;
+        };
+        let Some(path) = unity_res
+            .audio_paths
+            .get(guid)
+            .or_else(|| unity_res.all_map.get(guid))
+        else {
+            tracing::warn!("no audio clip with guid {}", guid);
+            continue;
+        };
+
+        cmd.insert(AudioBundle {
+            source: asset_server.load(unity_res.base_path.join("..").join(path)),
+            settings: source.playback_settings(),
+        });
+    }
+}
+
+/// Sets every source's sink volume each frame from its
+/// `UnityAudioSourceExtra`, mixer group and distance to the listener. Game
+/// code changes volume and mute on the `UnityAudioSourceExtra`, anything set
+/// on the sink directly is overwritten.
+#[allow(clippy::type_complexity)]
+fn audio_volume_system(
+    sources: Query<(
+        &UnityAudioSourceExtra,
+        &GlobalTransform,
+        Option<&UnityAudioMixerOutput>,
+        Option<&AudioSink>,
+        Option<&SpatialAudioSink>,
+    )>,
+    listeners: Query<&GlobalTransform, With<SpatialListener>>,
+    mixers: Res<AudioMixers>,
+    spatial_scale: Res<SpatialScale>,
+    global_volume: Res<GlobalVolume>,
+) {
+    let listener = listeners.iter().next();
+
+    for (extra, transform, output, sink, spatial_sink) in &sources {
+        let source = &extra.source;
+        let mixer = output
+            .and_then(|o| Some(mixers.mixers.get(&o.mixer)?.output_volume(o.group)))
+            .unwrap_or(1.0);
+        let volume = if source.mute != 0 {
+            0.0
+        } else {
+            global_volume.volume.get() * source.volume * mixer
+        };
+
+        if let Some(sink) = sink {
+            sink.set_volume(volume);
+        }
+        let (Some(sink), Some(listener)) = (spatial_sink, listener) else {
+            continue;
+        };
+
+        let offset = transform.translation() - listener.translation();
+        let rolloff = source.rolloff(offset.length());
+        sink.set_volume(spatial_sink_volume(
+            volume,
+            rolloff,
+            offset,
+            spatial_scale.0,
+        ));
+    }
+}
+
+/// Rodio's spatial sinks fall off with the squared distance, which is divided
+/// back out so the unity rolloff is what's heard.
+fn spatial_sink_volume(volume: f32, rolloff: f32, offset: Vec3, spatial_scale: Vec3) -> f32 {
+    let rodio_falloff = (1.0 / (offset * spatial_scale).length_squared()).min(1.0);
+    volume * rolloff / rodio_falloff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spatial_sink_volume_undoes_rodio_falloff() {
+        // rodio plays this 4m away source at 1 / 4², which is made up for
+        let offset = Vec3::new(0.0, 0.0, 4.0);
+        let volume = spatial_sink_volume(0.8, 0.25, offset, Vec3::ONE);
+        assert!((volume - 0.8 * 0.25 * 16.0).abs() < 1e-5);
+
+        // the spatial scale shrinks the distance rodio sees
+        let volume = spatial_sink_volume(1.0, 0.5, offset, Vec3::splat(0.5));
+        assert!((volume - 0.5 * 4.0).abs() < 1e-5);
+
+        // within a unit rodio doesn't attenuate, so neither is anything undone
+        let volume = spatial_sink_volume(1.0, 1.0, Vec3::new(0.5, 0.0, 0.0), Vec3::ONE);
+        assert_eq!(volume, 1.0);
+    }
+}
@@ -0,0 +1,410 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{Context, Result};
+use bevity_primitives::{
+    decibels_to_linear, UnityAudioMixerObject, UnityAudioMixerSnapshot, MIXER_MIN_DECIBELS,
+};
+use bevity_yaml::parse_unity_yaml;
+use bevy::prelude::*;
+
+use crate::read_guid_path_map;
+
+pub fn read_audio_mixer(path: &Path) -> Result<AudioMixer> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read audio mixer {}", path.display()))?;
+    let objects = parse_unity_yaml::<UnityAudioMixerObject>(&contents)?;
+
+    AudioMixer::new(&objects).with_context(|| format!("invalid audio mixer {}", path.display()))
+}
+
+/// Every `.mixer` asset in the project keyed by guid, loaded through
+/// `mixers.json` at startup.
+#[derive(Resource, Default)]
+pub struct AudioMixers {
+    pub mixers: HashMap<String, AudioMixer>,
+}
+
+impl AudioMixers {
+    /// A mixer by its asset name.
+    pub fn get(&self, name: &str) -> Option<&AudioMixer> {
+        self.mixers.values().find(|mixer| mixer.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut AudioMixer> {
+        self.mixers.values_mut().find(|mixer| mixer.name == name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioMixerGroup {
+    pub name: String,
+    file_id: i64,
+    parent: Option<usize>,
+    /// Guid of the group's volume parameter.
+    volume: String,
+    pub mute: bool,
+}
+
+#[derive(Debug, Clone)]
+struct SnapshotTransition {
+    from: HashMap<String, f32>,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// A mixer's groups and snapshots. Volumes are in decibels like unity's, set
+/// values win over snapshots until they're cleared.
+#[derive(Debug, Clone)]
+pub struct AudioMixer {
+    pub name: String,
+    groups: Vec<AudioMixerGroup>,
+    snapshots: Vec<UnityAudioMixerSnapshot>,
+    snapshot: usize,
+    transition: Option<SnapshotTransition>,
+    /// Parameter guids by exposed name.
+    exposed: HashMap<String, String>,
+    overrides: HashMap<String, f32>,
+}
+
+impl AudioMixer {
+    pub fn new(objects: &HashMap<i64, UnityAudioMixerObject>) -> Option<Self> {
+        let controller = objects.values().find_map(|object| match object {
+            UnityAudioMixerObject::AudioMixerController(c) => Some(c),
+            _ => None,
+        })?;
+
+        // groups from the master down, children after their parent
+        let mut groups: Vec<AudioMixerGroup> = vec![];
+        let mut stack = vec![(controller.master_group.file_id, None)];
+        while let Some((file_id, parent)) = stack.pop() {
+            let Some(UnityAudioMixerObject::AudioMixerGroupController(group)) =
+                objects.get(&file_id)
+            else {
+                continue;
+            };
+
+            let index = groups.len();
+            stack.extend(group.children.iter().map(|c| (c.file_id, Some(index))));
+            groups.push(AudioMixerGroup {
+                name: group.name.clone(),
+                file_id,
+                parent,
+                volume: group.volume.clone(),
+                mute: group.mute != 0,
+            });
+        }
+
+        let (ids, snapshots): (Vec<_>, Vec<_>) = controller
+            .snapshots
+            .iter()
+            .filter_map(|s| match objects.get(&s.file_id) {
+                Some(UnityAudioMixerObject::AudioMixerSnapshotController(snapshot)) => {
+                    Some((s.file_id, snapshot.clone()))
+                }
+                _ => None,
+            })
+            .unzip();
+        let snapshot = ids
+            .iter()
+            .position(|&id| id == controller.start_snapshot.file_id)
+            .unwrap_or_default();
+
+        Some(Self {
+            name: controller.name.clone(),
+            groups,
+            snapshots,
+            snapshot,
+            transition: None,
+            exposed: controller
+                .exposed_parameters
+                .iter()
+                .map(|p| (p.name.clone(), p.guid.clone()))
+                .collect(),
+            overrides: HashMap::new(),
+        })
+    }
+
+    pub fn groups(&self) -> impl Iterator<Item = &AudioMixerGroup> {
+        self.groups.iter()
+    }
+
+    fn group_index(&self, name: &str) -> Option<usize> {
+        self.groups.iter().position(|g| g.name == name)
+    }
+
+    pub(crate) fn group_by_file_id(&self, file_id: i64) -> Option<usize> {
+        self.groups.iter().position(|g| g.file_id == file_id)
+    }
+
+    /// A parameter's value from the current snapshot, or on its way there.
+    fn snapshot_value(&self, guid: &str) -> f32 {
+        let value = |snapshot: &UnityAudioMixerSnapshot| {
+            snapshot.float_values.get(guid).copied().unwrap_or_default()
+        };
+        let target = self
+            .snapshots
+            .get(self.snapshot)
+            .map(value)
+            .unwrap_or_default();
+
+        match &self.transition {
+            Some(transition) => {
+                let from = transition.from.get(guid).copied().unwrap_or_default();
+                let t = (transition.elapsed / transition.duration).min(1.0);
+                from + (target - from) * t
+            }
+            None => target,
+        }
+    }
+
+    fn parameter(&self, guid: &str) -> f32 {
+        self.overrides
+            .get(guid)
+            .copied()
+            .unwrap_or_else(|| self.snapshot_value(guid))
+    }
+
+    /// The group's volume in decibels.
+    pub fn volume(&self, group: &str) -> Option<f32> {
+        let index = self.group_index(group)?;
+        Some(self.parameter(&self.groups[index].volume))
+    }
+
+    /// Sets the group's volume in decibels, -80 is silent.
+    pub fn set_volume(&mut self, group: &str, decibels: f32) -> bool {
+        let Some(index) = self.group_index(group) else {
+            return false;
+        };
+        let guid = self.groups[index].volume.clone();
+        self.overrides.insert(guid, decibels);
+        true
+    }
+
+    /// Hands the group's volume back to the snapshots.
+    pub fn clear_volume(&mut self, group: &str) {
+        if let Some(index) = self.group_index(group) {
+            self.overrides.remove(&self.groups[index].volume);
+        }
+    }
+
+    pub fn set_mute(&mut self, group: &str, mute: bool) -> bool {
+        let Some(index) = self.group_index(group) else {
+            return false;
+        };
+        self.groups[index].mute = mute;
+        true
+    }
+
+    /// An exposed parameter's value, like `AudioMixer.GetFloat`.
+    pub fn get_float(&self, name: &str) -> Option<f32> {
+        Some(self.parameter(self.exposed.get(name)?))
+    }
+
+    /// Sets an exposed parameter, like `AudioMixer.SetFloat`.
+    pub fn set_float(&mut self, name: &str, value: f32) -> bool {
+        let Some(guid) = self.exposed.get(name) else {
+            return false;
+        };
+        self.overrides.insert(guid.clone(), value);
+        true
+    }
+
+    /// Hands an exposed parameter back to the snapshots, like
+    /// `AudioMixer.ClearFloat`.
+    pub fn clear_float(&mut self, name: &str) {
+        if let Some(guid) = self.exposed.get(name) {
+            self.overrides.remove(guid);
+        }
+    }
+
+    pub fn current_snapshot(&self) -> Option<&str> {
+        Some(&self.snapshots.get(self.snapshot)?.name)
+    }
+
+    /// Blends every parameter to the snapshot's values over `seconds`, like
+    /// `AudioMixerSnapshot.TransitionTo`.
+    pub fn transition_to_snapshot(&mut self, name: &str, seconds: f32) -> bool {
+        let Some(index) = self.snapshots.iter().position(|s| s.name == name) else {
+            return false;
+        };
+
+        let from = self
+            .snapshots
+            .iter()
+            .flat_map(|s| s.float_values.keys())
+            .map(|guid| (guid.clone(), self.snapshot_value(guid)))
+            .collect();
+        self.snapshot = index;
+        self.transition = (seconds > 0.0).then_some(SnapshotTransition {
+            from,
+            duration: seconds,
+            elapsed: 0.0,
+        });
+        true
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        if let Some(transition) = &mut self.transition {
+            transition.elapsed += delta;
+            if transition.elapsed >= transition.duration {
+                self.transition = None;
+            }
+        }
+    }
+
+    /// Linear volume of everything played through the group, including its
+    /// parents.
+    pub fn output_volume(&self, group: usize) -> f32 {
+        let mut volume = 1.0;
+        let mut next = Some(group);
+        while let Some(index) = next {
+            let group = &self.groups[index];
+            if group.mute {
+                return 0.0;
+            }
+            volume *= decibels_to_linear(self.parameter(&group.volume).max(MIXER_MIN_DECIBELS));
+            next = group.parent;
+        }
+        volume
+    }
+}
+
+/// Which mixer group an `AudioSource` plays through.
+#[derive(Component, Debug)]
+pub struct UnityAudioMixerOutput {
+    pub mixer: String,
+    pub group: usize,
+}
+
+pub(crate) fn load_audio_mixers_system(mut mixers: ResMut<AudioMixers>) {
+    let base = std::env::current_dir().unwrap_or_default();
+    // only written by newer versions of the unity sdk
+    let Ok(paths) = read_guid_path_map(&base.join("mixers.json")) else {
+        return;
+    };
+
+    for (guid, path) in paths {
+        match read_audio_mixer(&base.join("..").join(path)) {
+            Ok(mixer) => {
+                mixers.mixers.insert(guid, mixer);
+            }
+            Err(e) => tracing::error!("failed to load audio mixer: {:?}", e),
+        }
+    }
+}
+
+pub(crate) fn audio_mixer_system(mut mixers: ResMut<AudioMixers>, time: Res<Time>) {
+    for mixer in mixers.mixers.values_mut() {
+        mixer.update(time.delta_seconds());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIXER: &str = r#"%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!241 &24100000
+AudioMixerController:
+  m_ObjectHideFlags: 0
+  m_Name: MainMixer
+  m_OutputGroup: {fileID: 0}
+  m_MasterGroup: {fileID: 24300002}
+  m_Snapshots:
+  - {fileID: 24500006}
+  - {fileID: 245000010}
+  m_StartSnapshot: {fileID: 24500006}
+  m_SuspendThreshold: -80
+  m_EnableSuspend: 1
+  m_UpdateMode: 0
+  m_ExposedParameters:
+  - guid: 9c3f0e7a4b1d2c8e5f6a7b8c9d0e1f2a
+    name: MusicVolume
+  m_AudioMixerGroupViews: []
+  m_CurrentViewIndex: 0
+  m_TargetSnapshot: {fileID: 24500006}
+--- !u!243 &24300002
+AudioMixerGroupController:
+  m_ObjectHideFlags: 0
+  m_Name: Master
+  m_AudioMixer: {fileID: 24100000}
+  m_GroupID: 4a5b6c7d8e9f0a1b2c3d4e5f6a7b8c9d
+  m_Children:
+  - {fileID: 243000012}
+  m_Volume: 1f2e3d4c5b6a79880716253443526170
+  m_Pitch: 0a1b2c3d4e5f60718293a4b5c6d7e8f9
+  m_Send: 00000000000000000000000000000000
+  m_Effects: []
+  m_UserColorIndex: 0
+  m_Mute: 0
+  m_Solo: 0
+  m_BypassEffects: 0
+--- !u!243 &243000012
+AudioMixerGroupController:
+  m_ObjectHideFlags: 0
+  m_Name: Music
+  m_AudioMixer: {fileID: 24100000}
+  m_GroupID: 5b6c7d8e9f0a1b2c3d4e5f6a7b8c9d0e
+  m_Children: []
+  m_Volume: 9c3f0e7a4b1d2c8e5f6a7b8c9d0e1f2a
+  m_Pitch: 1b2c3d4e5f60718293a4b5c6d7e8f90a
+  m_Send: 00000000000000000000000000000000
+  m_Effects: []
+  m_UserColorIndex: 0
+  m_Mute: 0
+  m_Solo: 0
+  m_BypassEffects: 0
+--- !u!245 &24500006
+AudioMixerSnapshotController:
+  m_ObjectHideFlags: 0
+  m_Name: Snapshot
+  m_AudioMixer: {fileID: 24100000}
+  m_SnapshotID: 6c7d8e9f0a1b2c3d4e5f6a7b8c9d0e1f
+  m_FloatValues:
+    1f2e3d4c5b6a79880716253443526170: -6
+  m_TransitionOverrides: {}
+--- !u!245 &245000010
+AudioMixerSnapshotController:
+  m_ObjectHideFlags: 0
+  m_Name: Paused
+  m_AudioMixer: {fileID: 24100000}
+  m_SnapshotID: 7d8e9f0a1b2c3d4e5f6a7b8c9d0e1f2a
+  m_FloatValues:
+    1f2e3d4c5b6a79880716253443526170: -6
+    9c3f0e7a4b1d2c8e5f6a7b8c9d0e1f2a: -20
+  m_TransitionOverrides: {}
+"#;
+
+    #[test]
+    fn test_audio_mixer_snapshots() -> Result<()> {
+        let objects = parse_unity_yaml::<UnityAudioMixerObject>(MIXER)?;
+        let mut mixer = AudioMixer::new(&objects).context("no mixer")?;
+        let music = mixer
+            .group_by_file_id(243000012)
+            .context("no music group")?;
+
+        assert_eq!(mixer.current_snapshot(), Some("Snapshot"));
+        assert_eq!(mixer.volume("Master"), Some(-6.0));
+        assert_eq!(mixer.volume("Music"), Some(0.0));
+        assert!((mixer.output_volume(music) - decibels_to_linear(-6.0)).abs() < 1e-6);
+
+        assert!(mixer.transition_to_snapshot("Paused", 2.0));
+        mixer.update(1.0);
+        assert_eq!(mixer.get_float("MusicVolume"), Some(-10.0));
+        mixer.update(1.0);
+        assert_eq!(mixer.volume("Music"), Some(-20.0));
+
+        // a settings slider wins over snapshots until it's cleared
+        mixer.set_float("MusicVolume", -3.0);
+        mixer.transition_to_snapshot("Snapshot", 0.0);
+        assert_eq!(mixer.volume("Music"), Some(-3.0));
+        mixer.clear_float("MusicVolume");
+        assert_eq!(mixer.volume("Music"), Some(0.0));
+
+        mixer.set_mute("Master", true);
+        assert_eq!(mixer.output_volume(music), 0.0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::ecs::{system::EntityCommands, world::EntityWorldMut};
+use serde::de::DeserializeOwned;
+
+use crate::{UnityVolume, UniversalAdditionalCameraData};
+
+// m_Script guids of unity package scripts bevity understands
+const UNIVERSAL_ADDITIONAL_CAMERA_DATA: &str = "a79441f348de89743a2939f4d699eac1";
+const VOLUME: &str = "172515602e62fb746b5d573b38a5fe58";
+
+/// MonoBehaviours from unity packages, matched on their m_Script guid by the
+/// generated `BevityExported` before it gives up on a script.
+#[derive(Debug, Clone)]
+pub enum BuiltinMonoBehaviour {
+    UniversalAdditionalCameraData(UniversalAdditionalCameraData),
+    Volume(UnityVolume),
+}
+
+impl BuiltinMonoBehaviour {
+    pub fn from_script(guid: &str, value: serde_json::Value) -> Result<Option<Self>, String> {
+        match guid {
+            UNIVERSAL_ADDITIONAL_CAMERA_DATA => {
+                parse(value).map(|v| Some(Self::UniversalAdditionalCameraData(v)))
+            }
+            VOLUME => parse(value).map(|v| Some(Self::Volume(v))),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn add_to_entity(&self, cmd: &mut EntityCommands) {
+        match self {
+            Self::UniversalAdditionalCameraData(data) => cmd.insert(*data),
+            Self::Volume(volume) => cmd.insert(volume.clone()),
+        };
+    }
+
+    pub fn update_entity(&self, cmd: &mut EntityWorldMut) {
+        match self {
+            Self::UniversalAdditionalCameraData(data) => cmd.insert(*data),
+            Self::Volume(volume) => cmd.insert(volume.clone()),
+        };
+    }
+}
+
+fn parse<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, String> {
+    serde_json::from_value(value)
+        .map_err(|e| format!("failed to parse {}: {}", std::any::type_name::<T>(), e))
+}
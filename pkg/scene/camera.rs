@@ -0,0 +1,279 @@
+use bevity_primitives::{UnityCameraRendering, UnityLayerMask};
+use bevy::{
+    core_pipeline::{
+        experimental::taa::{TemporalAntiAliasBundle, TemporalAntiAliasPlugin},
+        fxaa::Fxaa,
+    },
+    pbr::ScreenSpaceAmbientOcclusionBundle,
+    prelude::*,
+};
+use bevy_atmosphere::prelude::AtmosphereCamera;
+use serde::{Deserialize, Serialize};
+
+/// Project wide defaults for the rendering features of imported cameras.
+/// Insert before adding the bevity plugins to override them.
+#[derive(Resource, Debug, Clone)]
+pub struct CameraImportSettings {
+    /// Screen space ambient occlusion on cameras with post processing. Bevy
+    /// only supports it with msaa off.
+    pub ssao: bool,
+    /// Procedural sky for cameras clearing to a skybox without a skybox material.
+    pub atmosphere_fallback: bool,
+    /// Lets cameras render in HDR when unity has `allowHDR` on.
+    pub hdr: bool,
+    /// Bevy only has a global msaa setting, unity's per camera toggle can only turn it off.
+    pub msaa: Msaa,
+    /// Used for cameras without URP camera data.
+    pub antialiasing: CameraAntialiasing,
+}
+
+impl Default for CameraImportSettings {
+    fn default() -> Self {
+        Self {
+            ssao: true,
+            atmosphere_fallback: true,
+            hdr: true,
+            msaa: Msaa::Off,
+            antialiasing: CameraAntialiasing::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraAntialiasing {
+    None,
+    Fxaa,
+    Taa,
+}
+
+// https://docs.unity3d.com/Packages/com.unity.render-pipelines.universal@latest/index.html?subfolder=/api/UnityEngine.Rendering.Universal.AntialiasingMode.html
+const URP_ANTIALIASING_FXAA: i32 = 1;
+const URP_ANTIALIASING_SMAA: i32 = 2;
+const URP_ANTIALIASING_TAA: i32 = 3;
+
+/// URP's per camera settings, which unity keeps in a MonoBehaviour next to the camera.
+#[derive(Component, Serialize, Deserialize, Debug, Default, Clone, Copy)]
+pub struct UniversalAdditionalCameraData {
+    #[serde(default = "default_true", alias = "m_RenderPostProcessing")]
+    pub render_post_processing: i32,
+
+    #[serde(default, alias = "m_Antialiasing")]
+    pub antialiasing: i32,
+
+    #[serde(default, alias = "m_VolumeLayerMask")]
+    pub volume_layer_mask: UnityLayerMask,
+}
+
+fn default_true() -> i32 {
+    1
+}
+
+impl UniversalAdditionalCameraData {
+    fn antialiasing(&self) -> CameraAntialiasing {
+        match self.antialiasing {
+            URP_ANTIALIASING_FXAA => CameraAntialiasing::Fxaa,
+            URP_ANTIALIASING_SMAA => {
+                tracing::warn!("SMAA is not supported, using FXAA instead");
+                CameraAntialiasing::Fxaa
+            }
+            URP_ANTIALIASING_TAA => CameraAntialiasing::Taa,
+            _ => CameraAntialiasing::None,
+        }
+    }
+}
+
+pub struct CameraImportPlugin;
+
+impl Plugin for CameraImportPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<TemporalAntiAliasPlugin>() {
+            app.add_plugins(TemporalAntiAliasPlugin);
+        }
+
+        app.init_resource::<CameraImportSettings>().add_systems(
+            Update,
+            (apply_msaa_settings_system, camera_rendering_system),
+        );
+    }
+}
+
+fn apply_msaa_settings_system(settings: Res<CameraImportSettings>, mut commands: Commands) {
+    if settings.is_changed() {
+        commands.insert_resource(settings.msaa);
+    }
+}
+
+fn camera_rendering_system(
+    mut cameras: Query<(
+        Entity,
+        &mut Camera,
+        &UnityCameraRendering,
+        Option<&UniversalAdditionalCameraData>,
+    )>,
+    settings: Res<CameraImportSettings>,
+    mut commands: Commands,
+) {
+    for (entity, mut camera, rendering, urp) in &mut cameras {
+        let mut commands = commands.entity(entity);
+        commands.remove::<UnityCameraRendering>();
+
+        camera.hdr = settings.hdr && rendering.allow_hdr;
+
+        if settings.atmosphere_fallback && rendering.sky_fallback {
+            commands.insert(AtmosphereCamera::default());
+        }
+
+        if settings.msaa != Msaa::Off && !rendering.allow_msaa {
+            tracing::warn!("msaa is global in bevy, it can't be turned off for a single camera");
+        }
+
+        let post_processing = urp.map_or(true, |urp| urp.render_post_processing != 0);
+        if settings.ssao && post_processing && settings.msaa == Msaa::Off {
+            commands.insert(ScreenSpaceAmbientOcclusionBundle::default());
+        }
+
+        let antialiasing = urp.map_or(settings.antialiasing, |urp| urp.antialiasing());
+        match antialiasing {
+            CameraAntialiasing::None => {}
+            CameraAntialiasing::Fxaa => {
+                commands.insert(Fxaa::default());
+            }
+            CameraAntialiasing::Taa if settings.msaa != Msaa::Off => {
+                tracing::warn!("TAA needs msaa off, leaving the camera without antialiasing");
+            }
+            CameraAntialiasing::Taa => {
+                commands.insert(TemporalAntiAliasBundle::default());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        core_pipeline::experimental::taa::TemporalAntiAliasSettings,
+        pbr::ScreenSpaceAmbientOcclusionSettings, render::view::RenderLayers,
+    };
+
+    use super::*;
+
+    fn urp_data(yaml: &str) -> UniversalAdditionalCameraData {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    /// Runs `camera_rendering_system` on a camera and returns it.
+    fn render_camera(
+        settings: CameraImportSettings,
+        urp: Option<UniversalAdditionalCameraData>,
+    ) -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(settings);
+
+        let mut camera = world.spawn((
+            Camera::default(),
+            UnityCameraRendering {
+                allow_hdr: true,
+                allow_msaa: true,
+                sky_fallback: false,
+            },
+        ));
+        if let Some(urp) = urp {
+            camera.insert(urp);
+        }
+        let camera = camera.id();
+
+        let mut system = IntoSystem::into_system(camera_rendering_system);
+        system.initialize(&mut world);
+        system.run((), &mut world);
+        system.apply_deferred(&mut world);
+
+        (world, camera)
+    }
+
+    #[test]
+    fn test_urp_camera_data() {
+        let data = urp_data(
+            "m_RenderPostProcessing: 0\n\
+             m_Antialiasing: 2\n\
+             m_VolumeLayerMask:\n  serializedVersion: 2\n  m_Bits: 5\n",
+        );
+        assert_eq!(data.render_post_processing, 0);
+        assert_eq!(data.antialiasing(), CameraAntialiasing::Fxaa);
+        assert_eq!(
+            data.volume_layer_mask.render_layers(),
+            RenderLayers::from_layers(&[0, 2])
+        );
+
+        // missing fields keep unity's defaults
+        let data = urp_data("m_RequiresDepthTexture: 0\n");
+        assert_eq!(data.render_post_processing, 1);
+        assert_eq!(data.antialiasing(), CameraAntialiasing::None);
+        assert_eq!(data.volume_layer_mask.render_layers(), RenderLayers::all());
+
+        assert_eq!(
+            urp_data("m_Antialiasing: 1").antialiasing(),
+            CameraAntialiasing::Fxaa
+        );
+        assert_eq!(
+            urp_data("m_Antialiasing: 3").antialiasing(),
+            CameraAntialiasing::Taa
+        );
+    }
+
+    #[test]
+    fn test_camera_post_processing() {
+        let (world, camera) = render_camera(CameraImportSettings::default(), None);
+        let camera = world.entity(camera);
+        assert!(camera.contains::<ScreenSpaceAmbientOcclusionSettings>());
+        assert!(!camera.contains::<UnityCameraRendering>());
+
+        let urp = urp_data("m_RenderPostProcessing: 0");
+        let (world, camera) = render_camera(CameraImportSettings::default(), Some(urp));
+        assert!(!world
+            .entity(camera)
+            .contains::<ScreenSpaceAmbientOcclusionSettings>());
+
+        // bevy's ssao doesn't work with msaa
+        let settings = CameraImportSettings {
+            msaa: Msaa::Sample4,
+            ..default()
+        };
+        let (world, camera) = render_camera(settings, None);
+        assert!(!world
+            .entity(camera)
+            .contains::<ScreenSpaceAmbientOcclusionSettings>());
+    }
+
+    #[test]
+    fn test_camera_antialiasing() {
+        let (world, camera) = render_camera(
+            CameraImportSettings::default(),
+            Some(urp_data("m_Antialiasing: 2")),
+        );
+        assert!(world.entity(camera).contains::<Fxaa>());
+
+        let (world, camera) = render_camera(
+            CameraImportSettings::default(),
+            Some(urp_data("m_Antialiasing: 3")),
+        );
+        assert!(world.entity(camera).contains::<TemporalAntiAliasSettings>());
+
+        // taa needs msaa off
+        let settings = CameraImportSettings {
+            msaa: Msaa::Sample4,
+            ..default()
+        };
+        let (world, camera) = render_camera(settings, Some(urp_data("m_Antialiasing: 3")));
+        assert!(!world.entity(camera).contains::<TemporalAntiAliasSettings>());
+
+        // the project default only applies without URP data
+        let settings = CameraImportSettings {
+            antialiasing: CameraAntialiasing::Fxaa,
+            ..default()
+        };
+        let (world, camera) = render_camera(settings.clone(), None);
+        assert!(world.entity(camera).contains::<Fxaa>());
+        let (world, camera) = render_camera(settings, Some(urp_data("m_Antialiasing: 0")));
+        assert!(!world.entity(camera).contains::<Fxaa>());
+    }
+}
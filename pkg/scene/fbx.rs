@@ -0,0 +1,1267 @@
+use std::{collections::HashMap, io::Read, path::Path};
+
+use anyhow::{bail, ensure, Context, Result};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{build_model_scene, ModelMesh, ModelNode, TriangleBuilder};
+
+/// Loads binary and ASCII FBX 7 files as a `Scene`.
+#[derive(Default)]
+pub struct FbxLoader;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FbxLoaderSettings {
+    /// Scale the file's units to meters, unity's `useFileScale`.
+    pub use_file_scale: bool,
+}
+
+impl Default for FbxLoaderSettings {
+    fn default() -> Self {
+        Self {
+            use_file_scale: true,
+        }
+    }
+}
+
+impl AssetLoader for FbxLoader {
+    type Asset = Scene;
+    type Settings = FbxLoaderSettings;
+    type Error = anyhow::Error;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        settings: &'a FbxLoaderSettings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Scene>> {
+        Box::pin(async move {
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes).await?;
+
+            let document = parse_fbx(&bytes)
+                .with_context(|| format!("failed to parse {}", load_context.path().display()))?;
+            let directory = load_context
+                .path()
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+
+            let model = FbxModel::new(&document)?;
+            let materials = model.materials(|file| load_context.load(directory.join(file)));
+            let nodes = model.nodes()?;
+
+            Ok(build_model_scene(
+                load_context,
+                model.root_transform(settings.use_file_scale),
+                nodes,
+                materials,
+            ))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["fbx"]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FbxProperty {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Raw(Vec<u8>),
+    IntArray(Vec<i64>),
+    FloatArray(Vec<f64>),
+}
+
+impl FbxProperty {
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            FbxProperty::Int(v) => Some(*v),
+            FbxProperty::Bool(v) => Some(*v as i64),
+            FbxProperty::Float(v) => Some(*v as i64),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            FbxProperty::Int(v) => Some(*v as f64),
+            FbxProperty::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            FbxProperty::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn as_f64_array(&self) -> Option<Vec<f64>> {
+        match self {
+            FbxProperty::FloatArray(v) => Some(v.clone()),
+            FbxProperty::IntArray(v) => Some(v.iter().map(|v| *v as f64).collect()),
+            _ => None,
+        }
+    }
+
+    fn as_i64_array(&self) -> Option<Vec<i64>> {
+        match self {
+            FbxProperty::IntArray(v) => Some(v.clone()),
+            FbxProperty::FloatArray(v) => Some(v.iter().map(|v| *v as i64).collect()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct FbxNode {
+    pub name: String,
+    pub properties: Vec<FbxProperty>,
+    pub children: Vec<FbxNode>,
+}
+
+impl FbxNode {
+    fn child(&self, name: &str) -> Option<&FbxNode> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a FbxNode> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    fn property(&self, index: usize) -> Option<&FbxProperty> {
+        self.properties.get(index)
+    }
+
+    /// Value of a `Properties70` entry, after its name, type, label and flags.
+    fn property70(&self, name: &str) -> Option<&[FbxProperty]> {
+        self.child("Properties70")?
+            .children_named("P")
+            .find(|p| p.property(0).and_then(FbxProperty::as_str) == Some(name))
+            .map(|p| p.properties.get(4..).unwrap_or_default())
+    }
+
+    fn property70_f64(&self, name: &str) -> Option<f64> {
+        self.property70(name)?.first()?.as_f64()
+    }
+
+    fn property70_vec3(&self, name: &str) -> Option<Vec3> {
+        match self.property70(name)? {
+            [x, y, z, ..] => Some(Vec3::new(
+                x.as_f64()? as f32,
+                y.as_f64()? as f32,
+                z.as_f64()? as f32,
+            )),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn parse_fbx(bytes: &[u8]) -> Result<Vec<FbxNode>> {
+    if bytes.starts_with(BINARY_MAGIC) {
+        parse_binary(bytes)
+    } else {
+        parse_ascii(std::str::from_utf8(bytes).context("fbx is neither binary nor text")?)
+    }
+}
+
+const BINARY_MAGIC: &[u8] = b"Kaydara FBX Binary  \0";
+
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + len)
+            .context("unexpected end of fbx")?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    /// Offsets and counts are 64 bit from version 7500.
+    fn offset(&mut self, wide: bool) -> Result<u64> {
+        if wide {
+            Ok(u64::from_le_bytes(self.array()?))
+        } else {
+            Ok(self.u32()? as u64)
+        }
+    }
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Vec<FbxNode>> {
+    let mut reader = BinaryReader {
+        bytes,
+        position: BINARY_MAGIC.len() + 2,
+    };
+    let version = reader.u32()?;
+    let wide = version >= 7500;
+
+    let mut nodes = vec![];
+    while let Some(node) = read_binary_node(&mut reader, wide)? {
+        nodes.push(node);
+    }
+
+    Ok(nodes)
+}
+
+fn read_binary_node(reader: &mut BinaryReader, wide: bool) -> Result<Option<FbxNode>> {
+    let end = reader.offset(wide)? as usize;
+    let property_count = reader.offset(wide)?;
+    let _property_list_len = reader.offset(wide)?;
+    let name_len = reader.take(1)?[0] as usize;
+
+    // a null record ends a list of nodes
+    if end == 0 {
+        return Ok(None);
+    }
+
+    let name = String::from_utf8_lossy(reader.take(name_len)?).into_owned();
+    let properties = (0..property_count)
+        .map(|_| read_binary_property(reader))
+        .collect::<Result<_>>()?;
+
+    let mut children = vec![];
+    while reader.position < end {
+        match read_binary_node(reader, wide)? {
+            Some(child) => children.push(child),
+            None => break,
+        }
+    }
+    reader.position = end;
+
+    Ok(Some(FbxNode {
+        name,
+        properties,
+        children,
+    }))
+}
+
+fn read_binary_property(reader: &mut BinaryReader) -> Result<FbxProperty> {
+    let kind = reader.take(1)?[0];
+    Ok(match kind {
+        b'C' => FbxProperty::Bool(reader.take(1)?[0] != 0),
+        b'Y' => FbxProperty::Int(i16::from_le_bytes(reader.array()?) as i64),
+        b'I' => FbxProperty::Int(i32::from_le_bytes(reader.array()?) as i64),
+        b'L' => FbxProperty::Int(i64::from_le_bytes(reader.array()?)),
+        b'F' => FbxProperty::Float(f32::from_le_bytes(reader.array()?) as f64),
+        b'D' => FbxProperty::Float(f64::from_le_bytes(reader.array()?)),
+        b'S' | b'R' => {
+            let len = reader.u32()? as usize;
+            let bytes = reader.take(len)?;
+            if kind == b'S' {
+                FbxProperty::String(String::from_utf8_lossy(bytes).into_owned())
+            } else {
+                FbxProperty::Raw(bytes.to_vec())
+            }
+        }
+        b'f' | b'd' | b'i' | b'l' | b'b' => {
+            let count = reader.u32()? as usize;
+            let encoding = reader.u32()?;
+            let len = reader.u32()? as usize;
+            let data = reader.take(len)?;
+
+            let data = if encoding == 1 {
+                let mut inflated = vec![];
+                flate2::read::ZlibDecoder::new(data)
+                    .read_to_end(&mut inflated)
+                    .context("failed to inflate fbx array")?;
+                inflated
+            } else {
+                data.to_vec()
+            };
+
+            let size = match kind {
+                b'd' | b'l' => 8,
+                b'b' => 1,
+                _ => 4,
+            };
+            ensure!(
+                data.len() >= count * size,
+                "fbx array shorter than its count"
+            );
+
+            let elements = data.chunks_exact(size).take(count);
+            match kind {
+                b'f' => FbxProperty::FloatArray(
+                    elements
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+                        .collect(),
+                ),
+                b'd' => FbxProperty::FloatArray(
+                    elements
+                        .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+                        .collect(),
+                ),
+                b'i' => FbxProperty::IntArray(
+                    elements
+                        .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as i64)
+                        .collect(),
+                ),
+                b'l' => FbxProperty::IntArray(
+                    elements
+                        .map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+                        .collect(),
+                ),
+                _ => FbxProperty::IntArray(elements.map(|b| b[0] as i64).collect()),
+            }
+        }
+        kind => bail!("unknown fbx property type {}", kind as char),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// `Name:`
+    Key(String),
+    Ident(String),
+    String(String),
+    Number(String),
+    /// `*count`, ahead of an array's braces.
+    Count,
+    Comma,
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            ';' => while chars.next_if(|c| *c != '\n').is_some() {},
+            ',' => {
+                chars.next();
+                tokens.push(Token::Comma);
+            }
+            '{' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            '}' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '*' => {
+                chars.next();
+                while chars.next_if(|c| c.is_ascii_digit()).is_some() {}
+                tokens.push(Token::Count);
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                while let Some(c) = chars.next_if(|c| *c != '"') {
+                    value.push(c);
+                }
+                chars.next();
+                tokens.push(Token::String(value));
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let mut value = String::new();
+                while let Some(c) = chars
+                    .next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+                {
+                    value.push(c);
+                }
+                tokens.push(Token::Number(value));
+            }
+            _ => {
+                let mut value = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '|')
+                {
+                    value.push(c);
+                }
+
+                if value.is_empty() {
+                    // unknown punctuation
+                    chars.next();
+                } else if chars.next_if_eq(&':').is_some() {
+                    tokens.push(Token::Key(value));
+                } else {
+                    tokens.push(Token::Ident(value));
+                }
+            }
+        }
+    }
+
+    tokens
+}
+
+fn parse_ascii(text: &str) -> Result<Vec<FbxNode>> {
+    let tokens = tokenize(text);
+    let mut position = 0;
+    let nodes = parse_ascii_nodes(&tokens, &mut position)?;
+    ensure!(position == tokens.len(), "unexpected token in fbx");
+
+    Ok(nodes)
+}
+
+fn parse_ascii_nodes(tokens: &[Token], position: &mut usize) -> Result<Vec<FbxNode>> {
+    let mut nodes = vec![];
+    while let Some(Token::Key(name)) = tokens.get(*position) {
+        *position += 1;
+
+        let mut node = FbxNode {
+            name: name.clone(),
+            ..default()
+        };
+
+        if tokens.get(*position) == Some(&Token::Count) {
+            // *count { a: values }
+            *position += 1;
+            ensure!(
+                tokens.get(*position) == Some(&Token::Open),
+                "expected array"
+            );
+            *position += 1;
+            let mut array = vec![];
+            if let Some(Token::Key(_)) = tokens.get(*position) {
+                *position += 1;
+                array = parse_ascii_values(tokens, position);
+            }
+            ensure!(
+                tokens.get(*position) == Some(&Token::Close),
+                "unclosed array"
+            );
+            *position += 1;
+
+            let is_float = array
+                .iter()
+                .any(|value| matches!(value, FbxProperty::Float(_)));
+            node.properties.push(if is_float {
+                FbxProperty::FloatArray(array.iter().filter_map(FbxProperty::as_f64).collect())
+            } else {
+                FbxProperty::IntArray(array.iter().filter_map(FbxProperty::as_i64).collect())
+            });
+        } else {
+            node.properties = parse_ascii_values(tokens, position);
+        }
+
+        if tokens.get(*position) == Some(&Token::Open) {
+            *position += 1;
+            node.children = parse_ascii_nodes(tokens, position)?;
+            ensure!(
+                tokens.get(*position) == Some(&Token::Close),
+                "unclosed fbx node {}",
+                node.name
+            );
+            *position += 1;
+        }
+
+        nodes.push(node);
+    }
+
+    Ok(nodes)
+}
+
+fn parse_ascii_values(tokens: &[Token], position: &mut usize) -> Vec<FbxProperty> {
+    let mut values = vec![];
+    loop {
+        let value = match tokens.get(*position) {
+            Some(Token::String(v)) => FbxProperty::String(v.clone()),
+            Some(Token::Ident(v)) => FbxProperty::String(v.clone()),
+            Some(Token::Number(v)) => match v.parse::<i64>() {
+                Ok(int) => FbxProperty::Int(int),
+                Err(_) => FbxProperty::Float(v.parse().unwrap_or_default()),
+            },
+            _ => break,
+        };
+        values.push(value);
+        *position += 1;
+
+        if tokens.get(*position) != Some(&Token::Comma) {
+            break;
+        }
+        *position += 1;
+    }
+
+    values
+}
+
+/// An object from the `Objects` section.
+struct FbxObject<'a> {
+    node: &'a FbxNode,
+    name: String,
+}
+
+/// Binary names are `Name\0\x01Class`, ascii ones `Class::Name`.
+fn split_object_name(name: &str) -> (String, String) {
+    if let Some((name, class)) = name.split_once("\0\u{1}") {
+        (name.to_string(), class.to_string())
+    } else if let Some((class, name)) = name.split_once("::") {
+        (name.to_string(), class.to_string())
+    } else {
+        (name.to_string(), String::new())
+    }
+}
+
+struct FbxModel<'a> {
+    objects: HashMap<i64, FbxObject<'a>>,
+    /// Children of each object in file order, with the property they connect to.
+    children: HashMap<i64, Vec<(i64, Option<String>)>>,
+    global_settings: Option<&'a FbxNode>,
+    material_ids: Vec<i64>,
+}
+
+impl<'a> FbxModel<'a> {
+    fn new(document: &'a [FbxNode]) -> Result<Self> {
+        let objects_node = document
+            .iter()
+            .find(|n| n.name == "Objects")
+            .context("fbx has no objects, only version 7 files are supported")?;
+
+        let objects: HashMap<_, _> = objects_node
+            .children
+            .iter()
+            .filter_map(|node| {
+                let id = node.property(0)?.as_i64()?;
+                let (name, _) = split_object_name(node.property(1)?.as_str()?);
+                Some((id, FbxObject { node, name }))
+            })
+            .collect();
+
+        let mut children: HashMap<i64, Vec<(i64, Option<String>)>> = HashMap::new();
+        if let Some(connections) = document.iter().find(|n| n.name == "Connections") {
+            for connection in connections.children_named("C") {
+                let (Some(child), Some(parent)) = (
+                    connection.property(1).and_then(FbxProperty::as_i64),
+                    connection.property(2).and_then(FbxProperty::as_i64),
+                ) else {
+                    continue;
+                };
+                let property = connection
+                    .property(3)
+                    .and_then(FbxProperty::as_str)
+                    .map(str::to_string);
+                children.entry(parent).or_default().push((child, property));
+            }
+        }
+
+        let mut material_ids: Vec<_> = objects
+            .iter()
+            .filter(|(_, o)| o.node.name == "Material")
+            .map(|(id, _)| *id)
+            .collect();
+        material_ids.sort();
+
+        Ok(Self {
+            objects,
+            children,
+            global_settings: document.iter().find(|n| n.name == "GlobalSettings"),
+            material_ids,
+        })
+    }
+
+    fn children_of(
+        &self,
+        id: i64,
+        kind: &'a str,
+    ) -> impl Iterator<Item = (i64, &FbxObject<'_>)> + '_ {
+        self.children
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(move |(child, _)| {
+                let object = self.objects.get(child)?;
+                (object.node.name == kind).then_some((*child, object))
+            })
+    }
+
+    /// The file's axes, and optionally units, to y up meters.
+    fn root_transform(&self, use_file_scale: bool) -> Transform {
+        // fbx defaults to centimeters
+        let Some(settings) = self.global_settings else {
+            let scale = if use_file_scale { 0.01 } else { 1.0 };
+            return Transform::from_scale(Vec3::splat(scale));
+        };
+
+        let axis = |name: &str, default: i64| {
+            settings
+                .property70(name)
+                .and_then(|v| v.first()?.as_i64())
+                .unwrap_or(default)
+        };
+        let direction = |axis_name: &str, sign_name: &str, default: i64| {
+            let sign = axis(sign_name, 1).signum() as f32;
+            Vec3::AXES[axis(axis_name, default).clamp(0, 2) as usize] * sign
+        };
+
+        let right = direction("CoordAxis", "CoordAxisSign", 0);
+        let up = direction("UpAxis", "UpAxisSign", 1);
+        let front = direction("FrontAxis", "FrontAxisSign", 2);
+
+        // maps the file's right, up and front onto x, y and z
+        let axes = Mat3::from_cols(right, up, front).transpose();
+        let scale = if use_file_scale {
+            settings.property70_f64("UnitScaleFactor").unwrap_or(1.0) as f32 / 100.0
+        } else {
+            1.0
+        };
+
+        Transform::from_matrix(Mat4::from_mat3(axes * scale))
+    }
+
+    /// Materials by name.
+    fn materials(
+        &self,
+        mut load_texture: impl FnMut(&str) -> Handle<Image>,
+    ) -> Vec<(String, StandardMaterial)> {
+        self.material_ids
+            .iter()
+            .map(|id| {
+                let node = self.objects[id].node;
+                let color = node
+                    .property70_vec3("DiffuseColor")
+                    .unwrap_or(Vec3::splat(0.8));
+                let factor = node.property70_f64("DiffuseFactor").unwrap_or(1.0) as f32;
+                let opacity = node
+                    .property70_f64("Opacity")
+                    .or_else(|| node.property70_f64("TransparencyFactor").map(|t| 1.0 - t))
+                    .unwrap_or(1.0) as f32;
+
+                let base_color_texture = self
+                    .children
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, property)| property.as_deref() == Some("DiffuseColor"))
+                    .filter_map(|(child, _)| self.objects.get(child))
+                    .filter(|texture| texture.node.name == "Texture")
+                    .find_map(|texture| {
+                        let file = ["RelativeFilename", "FileName"]
+                            .iter()
+                            .find_map(|name| texture.node.child(name)?.property(0)?.as_str())?;
+                        Some(load_texture(&file.replace('\\', "/")))
+                    });
+
+                let color = color * factor;
+                let material = StandardMaterial {
+                    base_color: Color::rgba(color.x, color.y, color.z, opacity),
+                    base_color_texture,
+                    alpha_mode: if opacity < 1.0 {
+                        AlphaMode::Blend
+                    } else {
+                        AlphaMode::Opaque
+                    },
+                    ..default()
+                };
+                (self.objects[id].name.clone(), material)
+            })
+            .collect()
+    }
+
+    fn nodes(&self) -> Result<Vec<ModelNode>> {
+        self.children_of(0, "Model")
+            .map(|(id, _)| self.node(id))
+            .collect()
+    }
+
+    fn node(&self, id: i64) -> Result<ModelNode> {
+        let object = &self.objects[&id];
+        let node = object.node;
+
+        let translation = node.property70_vec3("Lcl Translation").unwrap_or_default();
+        let scale = node.property70_vec3("Lcl Scaling").unwrap_or(Vec3::ONE);
+        let order = node
+            .property70("RotationOrder")
+            .and_then(|v| v.first()?.as_i64())
+            .unwrap_or(0);
+        let euler = |name: &str| {
+            node.property70_vec3(name)
+                .map_or(Quat::IDENTITY, |r| euler_rotation(r, order))
+        };
+        let rotation =
+            euler("PreRotation") * euler("Lcl Rotation") * euler("PostRotation").inverse();
+
+        let materials: Vec<_> = self
+            .children_of(id, "Material")
+            .filter_map(|(material, _)| self.material_ids.iter().position(|m| *m == material))
+            .collect();
+
+        let mut meshes = vec![];
+        for (_, geometry) in self.children_of(id, "Geometry") {
+            meshes.extend(
+                geometry_meshes(geometry, &materials)
+                    .with_context(|| format!("failed to read geometry {}", geometry.name))?,
+            );
+        }
+
+        let children = self
+            .children_of(id, "Model")
+            .map(|(child, _)| self.node(child))
+            .collect::<Result<_>>()?;
+
+        Ok(ModelNode {
+            name: object.name.clone(),
+            transform: Transform {
+                translation,
+                rotation,
+                scale,
+            },
+            meshes,
+            children,
+        })
+    }
+}
+
+/// Rotation from euler degrees, applied in the `RotationOrder`'s order.
+fn euler_rotation(degrees: Vec3, order: i64) -> Quat {
+    let [x, y, z] = [
+        Quat::from_rotation_x(degrees.x.to_radians()),
+        Quat::from_rotation_y(degrees.y.to_radians()),
+        Quat::from_rotation_z(degrees.z.to_radians()),
+    ];
+
+    match order {
+        1 => y * z * x,
+        2 => x * z * y,
+        3 => z * x * y,
+        4 => y * x * z,
+        5 => x * y * z,
+        _ => z * y * x,
+    }
+}
+
+/// Value of a `LayerElement` for a polygon corner.
+struct LayerElement {
+    values: Vec<f64>,
+    indices: Option<Vec<i64>>,
+    mapping: String,
+    stride: usize,
+}
+
+impl LayerElement {
+    fn new(
+        geometry: &FbxNode,
+        element: &str,
+        data: &str,
+        index: &str,
+        stride: usize,
+    ) -> Option<Self> {
+        let layer = geometry.child(element)?;
+        let text = |name: &str| {
+            layer
+                .child(name)
+                .and_then(|n| n.property(0)?.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let indices = if text("ReferenceInformationType") == "Direct" {
+            None
+        } else {
+            layer
+                .child(index)
+                .and_then(|n| n.property(0)?.as_i64_array())
+        };
+
+        Some(Self {
+            values: layer.child(data)?.property(0)?.as_f64_array()?,
+            indices,
+            mapping: text("MappingInformationType"),
+            stride,
+        })
+    }
+
+    fn get(&self, corner: usize, vertex: usize, polygon: usize) -> Option<&[f64]> {
+        let index = match self.mapping.as_str() {
+            "ByPolygonVertex" => corner,
+            "ByVertice" | "ByVertex" => vertex,
+            "ByPolygon" => polygon,
+            _ => 0,
+        };
+        let index = match &self.indices {
+            Some(indices) => *indices.get(index)? as usize,
+            None => index,
+        };
+
+        self.values
+            .get(index * self.stride..(index + 1) * self.stride)
+    }
+}
+
+/// A mesh per material used by the geometry's polygons.
+fn geometry_meshes(geometry: &FbxObject, materials: &[usize]) -> Result<Vec<ModelMesh>> {
+    let node = geometry.node;
+    let positions = node
+        .child("Vertices")
+        .and_then(|n| n.property(0)?.as_f64_array())
+        .context("geometry has no vertices")?;
+    let polygon_vertices = node
+        .child("PolygonVertexIndex")
+        .and_then(|n| n.property(0)?.as_i64_array())
+        .context("geometry has no polygons")?;
+
+    let normals = LayerElement::new(node, "LayerElementNormal", "Normals", "NormalsIndex", 3);
+    let uvs = LayerElement::new(node, "LayerElementUV", "UV", "UVIndex", 2);
+    let polygon_materials = node.child("LayerElementMaterial").map(|layer| {
+        let all_same = layer
+            .child("MappingInformationType")
+            .and_then(|n| n.property(0)?.as_str())
+            == Some("AllSame");
+        let values = layer
+            .child("Materials")
+            .and_then(|n| n.property(0)?.as_i64_array())
+            .unwrap_or_default();
+        (all_same, values)
+    });
+
+    let mut builders: Vec<TriangleBuilder> = vec![];
+    let mut polygon = vec![];
+    let mut polygon_index = 0;
+    for (corner, index) in polygon_vertices.iter().enumerate() {
+        // the last vertex of a polygon is stored as its bitwise not
+        let last = *index < 0;
+        polygon.push((corner, if last { !index } else { *index } as usize));
+        if !last {
+            continue;
+        }
+
+        let slot = match &polygon_materials {
+            Some((true, values)) => values.first().copied().unwrap_or(0),
+            Some((false, values)) => values.get(polygon_index).copied().unwrap_or(0),
+            None => 0,
+        }
+        .max(0) as usize;
+        if builders.len() <= slot {
+            builders.resize_with(slot + 1, Default::default);
+        }
+
+        let corner_data = |&(corner, vertex): &(usize, usize)| {
+            let position = positions.get(vertex * 3..vertex * 3 + 3)?;
+            let vec3 = |v: &[f64]| Vec3::new(v[0] as f32, v[1] as f32, v[2] as f32);
+            let normal = normals
+                .as_ref()
+                .and_then(|n| n.get(corner, vertex, polygon_index))
+                .map(vec3);
+            let uv = uvs
+                .as_ref()
+                .and_then(|uv| uv.get(corner, vertex, polygon_index))
+                .map(|uv| Vec2::new(uv[0] as f32, uv[1] as f32));
+            Some((vec3(position), normal, uv))
+        };
+
+        // fan out from the first corner
+        for i in 1..polygon.len().saturating_sub(1) {
+            for corner in [polygon[0], polygon[i], polygon[i + 1]] {
+                let (position, normal, uv) =
+                    corner_data(&corner).context("polygon vertex out of range")?;
+                builders[slot].corner(position, normal, uv);
+            }
+        }
+
+        polygon.clear();
+        polygon_index += 1;
+    }
+
+    let count = builders.iter().filter(|b| !b.is_empty()).count();
+    Ok(builders
+        .into_iter()
+        .enumerate()
+        .filter(|(_, builder)| !builder.is_empty())
+        .map(|(slot, builder)| ModelMesh {
+            name: if count > 1 {
+                format!("{}.{}", geometry.name, slot)
+            } else {
+                geometry.name.clone()
+            },
+            mesh: builder.build(),
+            material: materials.get(slot).copied(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::mesh::VertexAttributeValues;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_ascii() -> Result<()> {
+        let text = r#"; FBX 7.4.0 project file
+FBXHeaderExtension:  {
+	FBXVersion: 7400
+}
+Objects:  {
+	Geometry: 1000, "Geometry::Cube", "Mesh" {
+		Vertices: *6 {
+			a: 0,0,0,1,0.5,
+			0
+		}
+		PolygonVertexIndex: *3 {
+			a: 0,1,-3
+		}
+	}
+}
+"#;
+        let nodes = parse_ascii(text)?;
+        assert_eq!(nodes.len(), 2);
+
+        let geometry = &nodes[1].children[0];
+        assert_eq!(geometry.property(0), Some(&FbxProperty::Int(1000)));
+        assert_eq!(
+            split_object_name(geometry.property(1).unwrap().as_str().unwrap()),
+            ("Cube".to_string(), "Geometry".to_string())
+        );
+        assert_eq!(
+            geometry.child("Vertices").unwrap().property(0),
+            Some(&FbxProperty::FloatArray(vec![0.0, 0.0, 0.0, 1.0, 0.5, 0.0]))
+        );
+        assert_eq!(
+            geometry.child("PolygonVertexIndex").unwrap().property(0),
+            Some(&FbxProperty::IntArray(vec![0, 1, -3]))
+        );
+
+        Ok(())
+    }
+
+    fn write_offset(bytes: &mut Vec<u8>, value: u64, wide: bool) {
+        if wide {
+            bytes.extend(value.to_le_bytes());
+        } else {
+            bytes.extend((value as u32).to_le_bytes());
+        }
+    }
+
+    fn patch_offset(bytes: &mut [u8], at: usize, value: u64, wide: bool) {
+        if wide {
+            bytes[at..at + 8].copy_from_slice(&value.to_le_bytes());
+        } else {
+            bytes[at..at + 4].copy_from_slice(&(value as u32).to_le_bytes());
+        }
+    }
+
+    fn write_array(bytes: &mut Vec<u8>, kind: u8, count: usize, data: Vec<u8>, compress: bool) {
+        use std::io::Write;
+
+        bytes.push(kind);
+        bytes.extend((count as u32).to_le_bytes());
+        let data = if compress {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+            encoder.write_all(&data).unwrap();
+            encoder.finish().unwrap()
+        } else {
+            data
+        };
+        bytes.extend((compress as u32).to_le_bytes());
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+    }
+
+    fn write_property(bytes: &mut Vec<u8>, property: &FbxProperty, compress: bool) {
+        match property {
+            FbxProperty::Bool(v) => bytes.extend([b'C', *v as u8]),
+            FbxProperty::Int(v) => match i32::try_from(*v) {
+                Ok(v) => {
+                    bytes.push(b'I');
+                    bytes.extend(v.to_le_bytes());
+                }
+                Err(_) => {
+                    bytes.push(b'L');
+                    bytes.extend(v.to_le_bytes());
+                }
+            },
+            FbxProperty::Float(v) => {
+                bytes.push(b'D');
+                bytes.extend(v.to_le_bytes());
+            }
+            FbxProperty::String(v) => {
+                bytes.push(b'S');
+                bytes.extend((v.len() as u32).to_le_bytes());
+                bytes.extend(v.as_bytes());
+            }
+            FbxProperty::Raw(v) => {
+                bytes.push(b'R');
+                bytes.extend((v.len() as u32).to_le_bytes());
+                bytes.extend(v);
+            }
+            FbxProperty::IntArray(v) => {
+                let data = v.iter().flat_map(|v| (*v as i32).to_le_bytes()).collect();
+                write_array(bytes, b'i', v.len(), data, compress);
+            }
+            FbxProperty::FloatArray(v) => {
+                let data = v.iter().flat_map(|v| v.to_le_bytes()).collect();
+                write_array(bytes, b'd', v.len(), data, compress);
+            }
+        }
+    }
+
+    fn write_node(bytes: &mut Vec<u8>, node: &FbxNode, wide: bool, compress: bool) {
+        let start = bytes.len();
+        let offset_len = if wide { 8 } else { 4 };
+        write_offset(bytes, 0, wide);
+        write_offset(bytes, node.properties.len() as u64, wide);
+        write_offset(bytes, 0, wide);
+        bytes.push(node.name.len() as u8);
+        bytes.extend(node.name.as_bytes());
+
+        let properties_start = bytes.len();
+        for property in &node.properties {
+            write_property(bytes, property, compress);
+        }
+        let properties_len = (bytes.len() - properties_start) as u64;
+        patch_offset(bytes, start + 2 * offset_len, properties_len, wide);
+
+        if !node.children.is_empty() {
+            for child in &node.children {
+                write_node(bytes, child, wide, compress);
+            }
+            // null record closing the children
+            bytes.extend(vec![0; 3 * offset_len + 1]);
+        }
+
+        let end = bytes.len() as u64;
+        patch_offset(bytes, start, end, wide);
+    }
+
+    /// Encodes nodes like FBX's own binary writer, minus the footer.
+    fn binary_fbx(version: u32, nodes: &[FbxNode], compress: bool) -> Vec<u8> {
+        let wide = version >= 7500;
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.extend([0x1a, 0x00]);
+        bytes.extend(version.to_le_bytes());
+        for node in nodes {
+            write_node(&mut bytes, node, wide, compress);
+        }
+        bytes.extend(vec![0; if wide { 25 } else { 13 }]);
+        bytes
+    }
+
+    fn binary_document() -> Vec<FbxNode> {
+        let node = |name: &str, properties: Vec<FbxProperty>, children: Vec<FbxNode>| FbxNode {
+            name: name.to_string(),
+            properties,
+            children,
+        };
+
+        vec![
+            node(
+                "FBXHeaderExtension",
+                vec![],
+                vec![node("FBXVersion", vec![FbxProperty::Int(7400)], vec![])],
+            ),
+            node(
+                "Objects",
+                vec![],
+                vec![node(
+                    "Geometry",
+                    vec![
+                        FbxProperty::Int(2_500_000_000),
+                        FbxProperty::String("Cube\0\u{1}Geometry".to_string()),
+                        FbxProperty::String("Mesh".to_string()),
+                    ],
+                    vec![
+                        node(
+                            "Vertices",
+                            vec![FbxProperty::FloatArray(vec![
+                                0.0, 0.0, 0.0, 1.0, 0.5, -2.25,
+                            ])],
+                            vec![],
+                        ),
+                        node(
+                            "PolygonVertexIndex",
+                            vec![FbxProperty::IntArray(vec![0, 1, -3])],
+                            vec![],
+                        ),
+                        node(
+                            "Flags",
+                            vec![
+                                FbxProperty::Bool(true),
+                                FbxProperty::Float(0.125),
+                                FbxProperty::Raw(vec![1, 2, 3]),
+                            ],
+                            vec![],
+                        ),
+                    ],
+                )],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_parse_binary() -> Result<()> {
+        let document = binary_document();
+        for (version, compress) in [(7400, false), (7400, true), (7500, false), (7700, true)] {
+            let bytes = binary_fbx(version, &document, compress);
+            assert_eq!(
+                parse_fbx(&bytes)?,
+                document,
+                "version {} compressed {}",
+                version,
+                compress
+            );
+        }
+
+        let geometry = &document[1].children[0];
+        assert_eq!(
+            split_object_name(geometry.property(1).unwrap().as_str().unwrap()),
+            ("Cube".to_string(), "Geometry".to_string())
+        );
+
+        // 32 bit offsets read as 64 bit ones run off the end
+        let mut bytes = binary_fbx(7400, &document, false);
+        bytes[BINARY_MAGIC.len() + 2..BINARY_MAGIC.len() + 6]
+            .copy_from_slice(&7500u32.to_le_bytes());
+        assert!(parse_fbx(&bytes).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_geometry_meshes() -> Result<()> {
+        let text = r#"Objects:  {
+	Geometry: 1000, "Geometry::Mesh", "Mesh" {
+		Vertices: *15 {
+			a: 0,0,0,1,0,0,1,1,0,0,1,0,2,0,0
+		}
+		PolygonVertexIndex: *7 {
+			a: 0,1,2,-4,1,4,-3
+		}
+		LayerElementUV: 0 {
+			MappingInformationType: "ByPolygonVertex"
+			ReferenceInformationType: "IndexToDirect"
+			UV: *4 {
+				a: 0,0,1,1
+			}
+			UVIndex: *7 {
+				a: 0,1,1,0,0,1,1
+			}
+		}
+		LayerElementMaterial: 0 {
+			MappingInformationType: "ByPolygon"
+			ReferenceInformationType: "IndexToDirect"
+			Materials: *2 {
+				a: 0,1
+			}
+		}
+	}
+}
+"#;
+        let document = parse_ascii(text)?;
+        let model = FbxModel::new(&document)?;
+        let meshes = geometry_meshes(&model.objects[&1000], &[5, 7])?;
+
+        // the quad fans into two triangles, the triangle uses the second material
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(meshes[0].name, "Mesh.0");
+        assert_eq!(meshes[0].material, Some(5));
+        assert_eq!(meshes[1].name, "Mesh.1");
+        assert_eq!(meshes[1].material, Some(7));
+
+        let positions = |mesh: &ModelMesh| {
+            mesh.mesh
+                .attribute(Mesh::ATTRIBUTE_POSITION)
+                .and_then(|p| p.as_float3())
+                .unwrap()
+                .to_vec()
+        };
+        assert_eq!(
+            positions(&meshes[0]),
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+            ]
+        );
+        assert_eq!(
+            positions(&meshes[1]),
+            vec![[1.0, 0.0, 0.0], [2.0, 0.0, 0.0], [1.0, 1.0, 0.0]]
+        );
+
+        // normals come from the winding without a normal layer, uvs flip to top down
+        let Some(VertexAttributeValues::Float32x3(normals)) =
+            meshes[1].mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        else {
+            panic!("mesh has no normals");
+        };
+        assert!(normals.iter().all(|n| *n == [0.0, 0.0, 1.0]));
+        let Some(VertexAttributeValues::Float32x2(uvs)) =
+            meshes[0].mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+        else {
+            panic!("mesh has no uvs");
+        };
+        assert_eq!(uvs[..3], [[0.0, 1.0], [1.0, 0.0], [1.0, 0.0]]);
+
+        Ok(())
+    }
+
+    fn global_settings(up: i64, front: i64, front_sign: i64, unit_scale: f64) -> String {
+        format!(
+            r#"GlobalSettings:  {{
+	Version: 1000
+	Properties70:  {{
+		P: "UpAxis", "int", "Integer", "",{}
+		P: "UpAxisSign", "int", "Integer", "",1
+		P: "FrontAxis", "int", "Integer", "",{}
+		P: "FrontAxisSign", "int", "Integer", "",{}
+		P: "CoordAxis", "int", "Integer", "",0
+		P: "CoordAxisSign", "int", "Integer", "",1
+		P: "UnitScaleFactor", "double", "Number", "",{}
+	}}
+}}
+Objects:  {{
+}}
+"#,
+            up, front, front_sign, unit_scale
+        )
+    }
+
+    #[test]
+    fn test_root_transform_axes_and_units() -> Result<()> {
+        // z up, -y front, in meters, like blender's exports
+        let document = parse_ascii(&global_settings(2, 1, -1, 100.0))?;
+        let root = FbxModel::new(&document)?.root_transform(true);
+        assert!(root.transform_point(Vec3::X).abs_diff_eq(Vec3::X, 1e-6));
+        assert!(root.transform_point(Vec3::Z).abs_diff_eq(Vec3::Y, 1e-6));
+        assert!(root.transform_point(-Vec3::Y).abs_diff_eq(Vec3::Z, 1e-6));
+
+        // y up centimeters, maya's default, only scales
+        let document = parse_ascii(&global_settings(1, 2, 1, 1.0))?;
+        let model = FbxModel::new(&document)?;
+        let root = model.root_transform(true);
+        assert!(root
+            .transform_point(Vec3::new(100.0, 200.0, 300.0))
+            .abs_diff_eq(Vec3::new(1.0, 2.0, 3.0), 1e-5));
+        assert!(model
+            .root_transform(false)
+            .transform_point(Vec3::ONE)
+            .abs_diff_eq(Vec3::ONE, 1e-6));
+
+        // without settings fbx is y up centimeters
+        let document = parse_ascii("Objects:  {\n}\n")?;
+        let root = FbxModel::new(&document)?.root_transform(true);
+        assert_eq!(root.scale, Vec3::splat(0.01));
+        assert_eq!(root.rotation, Quat::IDENTITY);
+
+        Ok(())
+    }
+}
@@ -1,33 +1,21 @@
-use std::{collections::HashMap, path::Path};
+use std::path::Path;
 
 use anyhow::{bail, Context, Result};
 use bevity_primitives::UnityMaterial;
 use bevity_yaml::parse_unity_yaml;
 use serde::{Deserialize, Serialize};
 
-pub fn read_materials(base: &Path, config_file: &Path) -> Result<HashMap<String, UnityMaterial>> {
-    let materials =
-        std::fs::read_to_string(config_file).context("failed to read materials.json")?;
-    let materials: HashMap<String, String> =
-        serde_json::from_str(&materials).context("failed to parse materials.json")?;
+pub fn read_material(path: &Path) -> Result<UnityMaterial> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read material {}", path.display()))?;
 
-    materials
-        .into_iter()
-        .try_fold(HashMap::new(), |mut acc, (k, v)| {
-            let path = base.join("..").join(v);
-            let contents = std::fs::read_to_string(path)?;
-            let mat = read_single_material(&contents).context("failed to read single material")?;
-
-            acc.insert(k, mat);
-
-            Ok(acc)
-        })
+    read_single_material(&contents).context("failed to read single material")
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "object_type")]
 enum MaterialContainer {
-    Material(UnityMaterial),
+    Material(Box<UnityMaterial>),
     #[serde(other)]
     DontCare,
 }
@@ -41,5 +29,5 @@ fn read_single_material(contents: &str) -> Result<UnityMaterial> {
         bail!("invalid material file");
     };
 
-    Ok(mat)
+    Ok(*mat)
 }
@@ -0,0 +1,73 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use bevity_primitives::{BuiltinMesh, UnityMeshAsset};
+use bevity_yaml::parse_unity_yaml;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "object_type")]
+enum MeshContainer {
+    Mesh(Box<UnityMeshAsset>),
+    #[serde(other)]
+    DontCare,
+}
+
+/// The submeshes of the `Mesh` object `file_id` in a `.asset` file.
+pub fn read_mesh_asset(path: &Path, file_id: i64) -> Result<Vec<Mesh>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read mesh {}", path.display()))?;
+    let mut objects = parse_unity_yaml::<MeshContainer>(&quote_hex_data(&contents))?;
+
+    let Some(MeshContainer::Mesh(mesh)) = objects.remove(&file_id) else {
+        anyhow::bail!("no mesh {} in {}", file_id, path.display());
+    };
+
+    mesh.meshes()
+        .with_context(|| format!("failed to decode mesh {}", path.display()))
+}
+
+/// File id of the mesh in a `.asset` holding only a mesh.
+const MESH_ASSET_FILE_ID: i64 = 4300000;
+
+/// Unity's own mesh for a built-in primitive when the editor has exported
+/// it, the generated one otherwise.
+pub fn read_builtin_mesh(builtin: BuiltinMesh) -> Mesh {
+    let path = std::env::current_dir()
+        .unwrap_or_default()
+        .join("builtin_meshes")
+        .join(format!("{}.asset", builtin.name()));
+    if !path.exists() {
+        return builtin.mesh();
+    }
+
+    match read_mesh_asset(&path, MESH_ASSET_FILE_ID).map(|meshes| meshes.into_iter().next()) {
+        Ok(Some(mesh)) => mesh,
+        Ok(None) => {
+            tracing::warn!("exported {} mesh has no submeshes", builtin.name());
+            builtin.mesh()
+        }
+        Err(e) => {
+            tracing::warn!("failed to read exported {} mesh: {:?}", builtin.name(), e);
+            builtin.mesh()
+        }
+    }
+}
+
+/// Hex without letters in it would be read as a number.
+fn quote_hex_data(contents: &str) -> String {
+    contents
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("_typelessdata: ") || trimmed.starts_with("m_IndexBuffer: ") {
+                if let Some((key, value)) = line.split_once(": ") {
+                    return format!("{}: \"{}\"", key, value.trim());
+                }
+            }
+            line.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
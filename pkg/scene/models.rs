@@ -0,0 +1,418 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use bevity_primitives::{FileReference, UnityModelImporter, UnityModelMeta};
+use bevy::{
+    gltf::Gltf,
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    scene::SceneInstance,
+};
+
+use crate::{FbxLoader, ObjLoader};
+
+/// Loads FBX and OBJ models into `Scene`s laid out like bevy's glTF scenes: a
+/// root entity, one entity per node and a child entity per mesh primitive.
+pub struct ModelLoaderPlugin;
+
+impl Plugin for ModelLoaderPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ModelMaterialName>()
+            .register_asset_loader(FbxLoader)
+            .register_asset_loader(ObjLoader);
+    }
+}
+
+/// Name of the model's material a primitive was spawned with, for unity's
+/// material remaps.
+#[derive(Component, Reflect, Debug, Default, Clone)]
+#[reflect(Component)]
+pub struct ModelMaterialName(pub String);
+
+/// A node of a model file, in bevy's right handed y up space.
+#[derive(Debug, Default)]
+pub(crate) struct ModelNode {
+    pub name: String,
+    pub transform: Transform,
+    pub meshes: Vec<ModelMesh>,
+    pub children: Vec<ModelNode>,
+}
+
+/// One material's worth of a node's mesh.
+#[derive(Debug)]
+pub(crate) struct ModelMesh {
+    pub name: String,
+    pub mesh: Mesh,
+    pub material: Option<usize>,
+}
+
+/// Triangles collected one corner at a time.
+#[derive(Debug, Default)]
+pub(crate) struct TriangleBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    has_normals: bool,
+    has_uvs: bool,
+}
+
+impl TriangleBuilder {
+    /// Corners wind counter clockwise and uvs run bottom up, like glTF.
+    pub fn corner(&mut self, position: Vec3, normal: Option<Vec3>, uv: Option<Vec2>) {
+        self.has_normals |= normal.is_some();
+        self.has_uvs |= uv.is_some();
+
+        self.positions.push(position.to_array());
+        self.normals.push(normal.unwrap_or_default().to_array());
+        let uv = uv.unwrap_or_default();
+        self.uvs.push([uv.x, 1.0 - uv.y]);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn build(mut self) -> Mesh {
+        if !self.has_normals {
+            for (positions, normals) in self
+                .positions
+                .chunks_exact(3)
+                .zip(self.normals.chunks_exact_mut(3))
+            {
+                let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(positions[i]));
+                let normal = (b - a).cross(c - a).normalize_or_zero().to_array();
+                normals.fill(normal);
+            }
+        }
+
+        let indices = (0..self.positions.len() as u32).collect();
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals)
+            .with_indices(Some(Indices::U32(indices)));
+        if self.has_uvs {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs);
+        }
+
+        mesh
+    }
+}
+
+pub(crate) fn build_model_scene(
+    load_context: &mut bevy::asset::LoadContext,
+    root: Transform,
+    nodes: Vec<ModelNode>,
+    materials: Vec<(String, StandardMaterial)>,
+) -> Scene {
+    let mut materials: Vec<_> = materials
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, material))| {
+            let handle = load_context.add_labeled_asset(format!("Material{}", index), material);
+            (handle, ModelMaterialName(name))
+        })
+        .collect();
+    // unassigned meshes
+    let default_material = materials.len();
+    materials.push((
+        load_context.add_labeled_asset("MaterialDefault".to_string(), StandardMaterial::default()),
+        ModelMaterialName::default(),
+    ));
+
+    let mut world = World::default();
+    let mut mesh_count = 0;
+    world
+        .spawn((SpatialBundle::from_transform(root), Name::new("Root")))
+        .with_children(|parent| {
+            for node in nodes {
+                spawn_node(
+                    parent,
+                    node,
+                    load_context,
+                    &materials,
+                    default_material,
+                    &mut mesh_count,
+                );
+            }
+        });
+
+    Scene::new(world)
+}
+
+fn spawn_node(
+    parent: &mut WorldChildBuilder,
+    node: ModelNode,
+    load_context: &mut bevy::asset::LoadContext,
+    materials: &[(Handle<StandardMaterial>, ModelMaterialName)],
+    default_material: usize,
+    mesh_count: &mut usize,
+) {
+    let mesh_index = *mesh_count;
+    if !node.meshes.is_empty() {
+        *mesh_count += 1;
+    }
+
+    parent
+        .spawn((
+            SpatialBundle::from_transform(node.transform),
+            Name::new(node.name),
+        ))
+        .with_children(|parent| {
+            for (primitive, model_mesh) in node.meshes.into_iter().enumerate() {
+                let label = format!("Mesh{}/Primitive{}", mesh_index, primitive);
+                let material = model_mesh
+                    .material
+                    .filter(|m| *m < default_material)
+                    .unwrap_or(default_material);
+
+                let (material, material_name) = &materials[material];
+                parent.spawn((
+                    PbrBundle {
+                        mesh: load_context.add_labeled_asset(label, model_mesh.mesh),
+                        material: material.clone(),
+                        ..default()
+                    },
+                    Name::new(model_mesh.name),
+                    material_name.clone(),
+                ));
+            }
+
+            for child in node.children {
+                spawn_node(
+                    parent,
+                    child,
+                    load_context,
+                    materials,
+                    default_material,
+                    mesh_count,
+                );
+            }
+        });
+}
+
+/// The import settings in a model's `.meta` file.
+pub fn read_model_importer(model: &Path) -> Result<UnityModelImporter> {
+    let mut path = model.as_os_str().to_owned();
+    path.push(".meta");
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", Path::new(&path).display()))?;
+
+    parse_model_importer(&contents)
+}
+
+fn parse_model_importer(contents: &str) -> Result<UnityModelImporter> {
+    let meta: UnityModelMeta = serde_yaml::from_str(contents)?;
+    Ok(meta.model_importer.unwrap_or_default())
+}
+
+/// The root of a model prefab's scene, with the importer settings it was
+/// spawned with.
+#[derive(Component, Debug)]
+pub struct UnityModelInstance {
+    pub guid: String,
+    /// The model's file name without extension.
+    pub name: String,
+    pub importer: UnityModelImporter,
+    /// For the names of a glTF's materials.
+    pub gltf: Option<Handle<Gltf>>,
+    pub material_overrides: Vec<UnityMaterialOverride>,
+}
+
+/// A material the prefab instance sets on one of the model's renderers.
+#[derive(Debug, Clone)]
+pub struct UnityMaterialOverride {
+    /// Name of the renderer's node.
+    pub renderer: String,
+    pub slot: usize,
+    pub material: FileReference,
+}
+
+#[derive(Component, Debug)]
+pub struct UnityModelRequiresSetup;
+
+/// A unity material to replace a model primitive's own material with.
+#[derive(Component, Debug)]
+pub struct UnityModelMaterialRequiresLoad(pub FileReference);
+
+/// Once the model's scene has spawned, drops the cameras and lights unity
+/// wouldn't have imported and requests the remapped or overridden materials.
+///
+/// Unity makes a renderer per node with a material slot per primitive, so
+/// primitives are matched by their parent's name and their index among its
+/// primitives.
+#[allow(clippy::type_complexity)]
+pub(crate) fn setup_model_instance_system(
+    instances: Query<(Entity, &SceneInstance, &UnityModelInstance), With<UnityModelRequiresSetup>>,
+    children: Query<&Children>,
+    nodes: Query<(
+        Option<&Name>,
+        Has<Camera>,
+        Has<DirectionalLight>,
+        Has<PointLight>,
+        Has<SpotLight>,
+    )>,
+    primitives: Query<
+        (
+            Option<&Parent>,
+            Option<&Handle<StandardMaterial>>,
+            Option<&ModelMaterialName>,
+        ),
+        With<Handle<Mesh>>,
+    >,
+    gltfs: Res<Assets<Gltf>>,
+    scene_spawner: Res<SceneSpawner>,
+    mut commands: Commands,
+) {
+    for (entity, instance, model) in &instances {
+        if !scene_spawner.instance_is_ready(**instance) {
+            continue;
+        }
+
+        let settings = &model.importer.meshes;
+        let remaps = model.importer.material_remaps();
+        let gltf = model.gltf.as_ref().and_then(|gltf| gltfs.get(gltf));
+        let collapsed_root = (settings.preserve_hierarchy == 0)
+            .then(|| {
+                top_level_nodes(entity, &children, |e| {
+                    nodes.get(e).is_ok_and(|n| n.0.is_some())
+                })
+            })
+            .and_then(|top| match top[..] {
+                [root] => Some(root),
+                _ => None,
+            });
+
+        for descendant in children.iter_descendants(entity) {
+            if let Ok((_, camera, directional, point, spot)) = nodes.get(descendant) {
+                if settings.import_cameras == 0 && camera {
+                    commands
+                        .entity(descendant)
+                        .remove::<(Camera, Camera3d, Projection)>();
+                }
+                if settings.import_lights == 0 && (directional || point || spot) {
+                    commands
+                        .entity(descendant)
+                        .remove::<(DirectionalLight, PointLight, SpotLight)>();
+                }
+            }
+
+            let Ok((parent, material, material_name)) = primitives.get(descendant) else {
+                continue;
+            };
+
+            let renderer = parent.and_then(|p| {
+                if Some(p.get()) == collapsed_root {
+                    return Some(model.name.as_str());
+                }
+                Some(nodes.get(p.get()).ok()?.0?.as_str())
+            });
+            let slot = parent
+                .and_then(|p| children.get(p.get()).ok())
+                .and_then(|siblings| {
+                    siblings
+                        .iter()
+                        .filter(|sibling| primitives.contains(**sibling))
+                        .position(|sibling| *sibling == descendant)
+                })
+                .unwrap_or_default();
+            let material_name = material_name.map(|name| name.0.as_str()).or_else(|| {
+                let (name, _) = gltf?
+                    .named_materials
+                    .iter()
+                    .find(|(_, handle)| Some(*handle) == material)?;
+                Some(name.as_str())
+            });
+
+            let overridden = renderer.and_then(|renderer| {
+                model
+                    .material_overrides
+                    .iter()
+                    .find(|o| o.renderer == renderer && o.slot == slot)
+            });
+            let reference = match overridden {
+                Some(o) => Some(o.material.clone()),
+                None => material_name.and_then(|name| remaps.get(name).cloned()),
+            };
+
+            if let Some(reference) = reference {
+                commands
+                    .entity(descendant)
+                    .insert(UnityModelMaterialRequiresLoad(reference));
+            }
+        }
+
+        commands.entity(entity).remove::<UnityModelRequiresSetup>();
+    }
+}
+
+/// The first named nodes under the model's scene.
+fn top_level_nodes(
+    entity: Entity,
+    children: &Query<&Children>,
+    is_named: impl Fn(Entity) -> bool,
+) -> Vec<Entity> {
+    let mut top = vec![];
+    let mut stack = vec![entity];
+    while let Some(node) = stack.pop() {
+        for &child in children.get(node).into_iter().flatten() {
+            if is_named(child) {
+                top.push(child);
+            } else {
+                stack.push(child);
+            }
+        }
+    }
+    top
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_model_importer() -> Result<()> {
+        let meta = "fileFormatVersion: 2
+guid: 6f6a0f5a1d1b2c4e8f9a0b1c2d3e4f50
+ModelImporter:
+  serializedVersion: 22200
+  internalIDToNameTable: []
+  externalObjects:
+  - first:
+      type: UnityEngine:Material
+      assembly: UnityEngine.CoreModule
+      name: Body
+    second: {fileID: 2100000, guid: 0a1b2c3d4e5f60718293a4b5c6d7e8f9, type: 2}
+  meshes:
+    lODScreenPercentages: []
+    globalScale: 2
+    importCameras: 0
+    importLights: 1
+    useFileScale: 0
+    bakeAxisConversion: 1
+    preserveHierarchy: 1
+  animationType: 0
+";
+        let importer = parse_model_importer(meta)?;
+        assert_eq!(importer.meshes.global_scale, 2.0);
+        assert_eq!(importer.meshes.use_file_scale, 0);
+        assert_eq!(importer.meshes.import_cameras, 0);
+        assert_eq!(importer.meshes.preserve_hierarchy, 1);
+        assert!(!importer.imports_animations());
+        assert_eq!(
+            importer.material_remaps()["Body"].guid.as_deref(),
+            Some("0a1b2c3d4e5f60718293a4b5c6d7e8f9")
+        );
+
+        let empty = parse_model_importer("ModelImporter:\n  externalObjects: {}\n")?;
+        assert!(empty.external_objects.is_empty());
+        assert_eq!(empty.meshes.import_lights, 1);
+        assert_eq!(empty.meshes.preserve_hierarchy, 0);
+        assert!(empty.imports_animations());
+
+        // glTF models are imported by a scripted importer
+        let scripted = parse_model_importer("ScriptedImporter:\n  userData: \n")?;
+        assert_eq!(scripted.meshes.global_scale, 1.0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,370 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{Context, Result};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+
+use crate::{build_model_scene, ModelMesh, ModelNode, TriangleBuilder};
+
+/// Loads wavefront OBJ files, with their MTL materials, as a `Scene`.
+#[derive(Default)]
+pub struct ObjLoader;
+
+impl AssetLoader for ObjLoader {
+    type Asset = Scene;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Scene>> {
+        Box::pin(async move {
+            let mut text = String::new();
+            reader.read_to_string(&mut text).await?;
+
+            let directory = load_context
+                .path()
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+
+            let obj = parse_obj(&text)
+                .with_context(|| format!("failed to parse {}", load_context.path().display()))?;
+
+            let mut materials = vec![];
+            for library in &obj.material_libraries {
+                let path = directory.join(library);
+                let bytes = match load_context.read_asset_bytes(path.clone()).await {
+                    Ok(bytes) => bytes,
+                    Err(err) => {
+                        tracing::warn!("failed to read {}: {}", path.display(), err);
+                        continue;
+                    }
+                };
+                materials.extend(parse_mtl(&String::from_utf8_lossy(&bytes)));
+            }
+
+            let material_index: HashMap<_, _> = materials
+                .iter()
+                .enumerate()
+                .map(|(index, material)| (material.name.clone(), index))
+                .collect();
+            let nodes = obj.nodes(&material_index);
+            let materials = materials
+                .into_iter()
+                .map(|material| {
+                    let name = material.name.clone();
+                    let standard =
+                        material.standard_material(|file| load_context.load(directory.join(file)));
+                    (name, standard)
+                })
+                .collect();
+
+            // obj has no units or axes of its own, it is y up like bevy
+            Ok(build_model_scene(
+                load_context,
+                Transform::IDENTITY,
+                nodes,
+                materials,
+            ))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+}
+
+#[derive(Debug, Default)]
+struct ObjGroup {
+    name: String,
+    /// Faces by material name, in first use order.
+    faces: Vec<(Option<String>, Vec<Vec<ObjCorner>>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ObjCorner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct ObjFile {
+    positions: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    normals: Vec<Vec3>,
+    groups: Vec<ObjGroup>,
+    material_libraries: Vec<String>,
+}
+
+impl ObjFile {
+    fn nodes(&self, materials: &HashMap<String, usize>) -> Vec<ModelNode> {
+        self.groups
+            .iter()
+            .filter(|group| !group.faces.is_empty())
+            .map(|group| {
+                let meshes = group
+                    .faces
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, (material, faces))| {
+                        let mut builder = TriangleBuilder::default();
+                        for face in faces {
+                            // fan out from the first corner
+                            for i in 1..face.len().saturating_sub(1) {
+                                for corner in [face[0], face[i], face[i + 1]] {
+                                    builder.corner(
+                                        self.positions[corner.position],
+                                        corner.normal.map(|n| self.normals[n]),
+                                        corner.uv.map(|uv| self.uvs[uv]),
+                                    );
+                                }
+                            }
+                        }
+
+                        (!builder.is_empty()).then(|| ModelMesh {
+                            name: if group.faces.len() > 1 {
+                                format!("{}.{}", group.name, index)
+                            } else {
+                                group.name.clone()
+                            },
+                            mesh: builder.build(),
+                            material: material
+                                .as_ref()
+                                .and_then(|name| materials.get(name).copied()),
+                        })
+                    })
+                    .collect();
+
+                ModelNode {
+                    name: group.name.clone(),
+                    meshes,
+                    ..default()
+                }
+            })
+            .collect()
+    }
+}
+
+/// Resolves a 1 based, or negative relative, obj index.
+fn obj_index(value: &str, len: usize) -> Result<usize> {
+    let index: i64 = value.parse().context("invalid obj index")?;
+    let index = if index < 0 {
+        len as i64 + index
+    } else {
+        index - 1
+    };
+    anyhow::ensure!(
+        (0..len as i64).contains(&index),
+        "obj index {} out of range",
+        value
+    );
+    Ok(index as usize)
+}
+
+fn parse_obj(text: &str) -> Result<ObjFile> {
+    let mut obj = ObjFile::default();
+    let mut material = None;
+    let floats = |values: std::str::SplitWhitespace| -> Vec<f32> {
+        values.filter_map(|v| v.parse().ok()).collect()
+    };
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut values = line.split_whitespace();
+        let Some(key) = values.next() else {
+            continue;
+        };
+
+        match key {
+            "v" => {
+                let v = floats(values);
+                obj.positions.push(Vec3::new(
+                    v.first().copied().unwrap_or_default(),
+                    v.get(1).copied().unwrap_or_default(),
+                    v.get(2).copied().unwrap_or_default(),
+                ));
+            }
+            "vt" => {
+                let v = floats(values);
+                obj.uvs.push(Vec2::new(
+                    v.first().copied().unwrap_or_default(),
+                    v.get(1).copied().unwrap_or_default(),
+                ));
+            }
+            "vn" => {
+                let v = floats(values);
+                obj.normals.push(Vec3::new(
+                    v.first().copied().unwrap_or_default(),
+                    v.get(1).copied().unwrap_or_default(),
+                    v.get(2).copied().unwrap_or_default(),
+                ));
+            }
+            "f" => {
+                let face = values
+                    .map(|corner| {
+                        let mut parts = corner.split('/');
+                        let position =
+                            obj_index(parts.next().unwrap_or_default(), obj.positions.len())?;
+                        let uv = match parts.next() {
+                            Some(uv) if !uv.is_empty() => Some(obj_index(uv, obj.uvs.len())?),
+                            _ => None,
+                        };
+                        let normal = match parts.next() {
+                            Some(n) if !n.is_empty() => Some(obj_index(n, obj.normals.len())?),
+                            _ => None,
+                        };
+                        Ok(ObjCorner {
+                            position,
+                            uv,
+                            normal,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                if obj.groups.is_empty() {
+                    obj.groups.push(ObjGroup {
+                        name: "default".to_string(),
+                        ..default()
+                    });
+                }
+                let group = obj.groups.last_mut().unwrap();
+                match group.faces.iter_mut().find(|(m, _)| *m == material) {
+                    Some((_, faces)) => faces.push(face),
+                    None => group.faces.push((material.clone(), vec![face])),
+                }
+            }
+            "o" | "g" => {
+                let name = values.collect::<Vec<_>>().join(" ");
+                obj.groups.push(ObjGroup { name, ..default() });
+            }
+            "usemtl" => material = values.next().map(str::to_string),
+            "mtllib" => obj.material_libraries.extend(values.map(str::to_string)),
+            _ => {}
+        }
+    }
+
+    Ok(obj)
+}
+
+#[derive(Debug, Default)]
+struct MtlMaterial {
+    name: String,
+    diffuse: Option<Vec3>,
+    opacity: Option<f32>,
+    shininess: Option<f32>,
+    diffuse_texture: Option<String>,
+}
+
+impl MtlMaterial {
+    fn standard_material(
+        self,
+        load_texture: impl FnOnce(&str) -> Handle<Image>,
+    ) -> StandardMaterial {
+        let diffuse = self.diffuse.unwrap_or(Vec3::ONE);
+        let opacity = self.opacity.unwrap_or(1.0);
+
+        StandardMaterial {
+            base_color: Color::rgba(diffuse.x, diffuse.y, diffuse.z, opacity),
+            base_color_texture: self
+                .diffuse_texture
+                .map(|file| load_texture(&file.replace('\\', "/"))),
+            // blinn-phong exponent to roughness
+            perceptual_roughness: self.shininess.map_or(0.5, |ns| (2.0 / (ns + 2.0)).sqrt()),
+            alpha_mode: if opacity < 1.0 {
+                AlphaMode::Blend
+            } else {
+                AlphaMode::Opaque
+            },
+            ..default()
+        }
+    }
+}
+
+fn parse_mtl(text: &str) -> Vec<MtlMaterial> {
+    let mut materials: Vec<MtlMaterial> = vec![];
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut values = line.split_whitespace();
+        let Some(key) = values.next() else {
+            continue;
+        };
+
+        if key == "newmtl" {
+            materials.push(MtlMaterial {
+                name: values.collect::<Vec<_>>().join(" "),
+                ..default()
+            });
+            continue;
+        }
+
+        let Some(material) = materials.last_mut() else {
+            continue;
+        };
+        let floats: Vec<f32> = line
+            .split_whitespace()
+            .skip(1)
+            .filter_map(|v| v.parse().ok())
+            .collect();
+
+        match key {
+            "Kd" if floats.len() >= 3 => {
+                material.diffuse = Some(Vec3::new(floats[0], floats[1], floats[2]))
+            }
+            "d" => material.opacity = floats.first().copied(),
+            "Tr" => material.opacity = floats.first().map(|tr| 1.0 - tr),
+            "Ns" => material.shininess = floats.first().copied(),
+            // options come before the file name
+            "map_Kd" => material.diffuse_texture = values.last().map(str::to_string),
+            _ => {}
+        }
+    }
+
+    materials
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_obj() -> Result<()> {
+        let text = "mtllib quad.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 1
+o Quad
+usemtl Red
+f 1/1 2/1 3/2 -1/2
+";
+        let obj = parse_obj(text)?;
+        assert_eq!(obj.material_libraries, vec!["quad.mtl"]);
+        assert_eq!(obj.groups.len(), 1);
+        let (material, faces) = &obj.groups[0].faces[0];
+        assert_eq!(material.as_deref(), Some("Red"));
+        assert_eq!(faces[0][3].position, 3);
+
+        let materials = HashMap::from([("Red".to_string(), 0)]);
+        let nodes = obj.nodes(&materials);
+        assert_eq!(nodes[0].name, "Quad");
+        assert_eq!(nodes[0].meshes[0].material, Some(0));
+        assert_eq!(nodes[0].meshes[0].mesh.count_vertices(), 6);
+
+        let mtl = parse_mtl("newmtl Red\nKd 1 0 0\nd 0.5\n");
+        assert_eq!(mtl[0].diffuse, Some(Vec3::X));
+        assert_eq!(mtl[0].opacity, Some(0.5));
+
+        Ok(())
+    }
+}
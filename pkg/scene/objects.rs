@@ -1,7 +1,6 @@
 use std::collections::HashMap;
 
 use bevity_primitives::*;
-use bevy_atmosphere::prelude::AtmosphereCamera;
 use serde::{Deserialize, Serialize};
 
 use crate::{MonoBehaviour, UnityRenderSettings, UnityResource};
@@ -13,6 +12,12 @@ bevity_generator::inbuilt_component_list!((
     Light,
     MeshFilter,
     MeshRenderer,
+    SkinnedMeshRenderer,
+    Animator,
+    // the runtime `UnityAnimation` took the legacy component's name
+    Animation(UnityLegacyAnimation),
+    AudioSource,
+    AudioListener,
     PrefabInstance,
     MeshCollider,
     BoxCollider,
@@ -55,13 +60,17 @@ impl<T: MonoBehaviour + Sync + Send + 'static + Default> UnitySceneObject<T> {
                     .and_then(|tex_id| unity_res.textures.get(&tex_id));
 
                 c.add_camera_bundle(transform, skybox, commands);
-                if skybox.is_none() {
-                    commands.insert(AtmosphereCamera::default());
-                }
             }
             UnitySceneObject::Light(l) => l.add_light_bundle(transform, commands),
             UnitySceneObject::MeshFilter(mf) => mf.add_mesh_filter_meta(commands),
             UnitySceneObject::MeshRenderer(mr) => mr.add_mesh_renderer_meta(commands),
+            UnitySceneObject::SkinnedMeshRenderer(smr) => {
+                smr.add_skinned_mesh_renderer_meta(commands)
+            }
+            UnitySceneObject::Animator(animator) => animator.add_animator(commands),
+            UnitySceneObject::Animation(animation) => animation.add_legacy_animation(commands),
+            UnitySceneObject::AudioSource(source) => source.add_audio_source(commands),
+            UnitySceneObject::AudioListener(listener) => listener.add_audio_listener(commands),
             UnitySceneObject::BoxCollider(b) => b.add_box_collider(&transform, commands),
             UnitySceneObject::SphereCollider(sphere_collider) => {
                 sphere_collider.add_sphere_collider(&transform, commands)
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
+use bevity_primitives::UnityLight;
 use bevity_yaml::{parse_unity_yaml, parse_unity_yaml_file};
 
 use crate::objects::UnitySceneObject;
@@ -16,6 +17,13 @@ impl<T> UnityScene<T> {
             _ => None,
         })
     }
+
+    pub fn get_lights(&self) -> impl Iterator<Item = &UnityLight> {
+        self.1.values().filter_map(|c| match c {
+            UnitySceneObject::Light(l) => Some(l),
+            _ => None,
+        })
+    }
 }
 
 pub fn parse_scene_file<T: serde::de::DeserializeOwned>(
@@ -98,6 +106,61 @@ MeshFilter:
         Ok(())
     }
 
+    #[test]
+    fn test_parse_audio_source() -> Result<()> {
+        let yaml_input = r#"%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!82 &1043210947
+AudioSource:
+  m_ObjectHideFlags: 0
+  m_GameObject: {fileID: 1043210945}
+  m_Enabled: 1
+  serializedVersion: 4
+  OutputAudioMixerGroup: {fileID: 0}
+  m_audioClip: {fileID: 8300000, guid: 4f1b7a3e2c8d4a0b9e6f5d3c2b1a0f9e, type: 3}
+  m_PlayOnAwake: 1
+  m_Volume: 0.5
+  m_Pitch: 1
+  Loop: 1
+  Mute: 0
+  Priority: 128
+  MinDistance: 2
+  MaxDistance: 20
+  rolloffMode: 1
+  panLevelCustomCurve:
+    serializedVersion: 2
+    m_Curve:
+    - serializedVersion: 3
+      time: 0
+      value: 1
+      inSlope: 0
+      outSlope: 0
+      tangentMode: 0
+      weightedMode: 0
+    m_PreInfinity: 2
+    m_PostInfinity: 2
+    m_RotationOrder: 4
+"#;
+
+        let parsed = parse_scene::<()>("", yaml_input)?;
+        let Some(UnitySceneObject::AudioSource(source)) = parsed.1.get(&1043210947) else {
+            bail!("Expected an AudioSource object")
+        };
+
+        assert_eq!(source.volume, 0.5);
+        assert_eq!(source.spatial_blend(), 1.0);
+        let settings = source.playback_settings();
+        assert!(matches!(settings.mode, bevy::audio::PlaybackMode::Loop));
+        assert!(settings.spatial && !settings.paused);
+
+        // linear rolloff between the min and max distance
+        assert_eq!(source.rolloff(1.0), 1.0);
+        assert_eq!(source.rolloff(11.0), 0.5);
+        assert_eq!(source.rolloff(30.0), 0.0);
+
+        Ok(())
+    }
+
     #[test]
     fn test_malformed_transform() -> Result<()> {
         let yaml_input = r#"%YAML 1.1
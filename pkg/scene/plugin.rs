@@ -1,15 +1,25 @@
+use anyhow::{bail, Result};
 use bevity_primitives::*;
 use bevy::{
     ecs::{system::EntityCommands, world::EntityMut},
     prelude::*,
+    render::{
+        mesh::VertexAttributeValues,
+        view::{RenderLayers, VisibilitySystems},
+    },
     utils::HashMap,
 };
 use serde::de::DeserializeOwned;
 use std::marker::PhantomData;
 
 use crate::{
-    get_transform, parse_scene_file, ResourcesPlugin, UnityRenderSettings, UnityResource,
-    UnityScene, UnitySceneObject, UnityTransformMeta,
+    get_transform, load_audio_source_system, load_requested_materials_system, parse_scene_file,
+    read_builtin_mesh, read_mesh_asset, read_model_importer, setup_animator_controller_system,
+    setup_legacy_animation_system, setup_model_instance_system, AnimatorPlugin, AudioImportPlugin,
+    CameraImportPlugin, FbxLoaderSettings, ModelLoaderPlugin, ResourcesPlugin, UnityMaterialHandle,
+    UnityMaterialOverride, UnityModelInstance, UnityModelMaterialRequiresLoad,
+    UnityModelRequiresSetup, UnityRenderSettings, UnityResource, UnityScene, UnitySceneObject,
+    UnityTransformMeta, VolumePlugin,
 };
 
 #[derive(Default)]
@@ -41,16 +51,39 @@ impl<T: serde::de::DeserializeOwned + Sync + Send + 'static + Default + MonoBeha
         app.insert_resource::<SceneResource<T>>(SceneResource::default())
             .add_plugins(ResourcesPlugin::<T>::default())
             .insert_resource(UnityEntityMap::default())
+            .add_plugins((
+                AnimatorPlugin,
+                AudioImportPlugin,
+                CameraImportPlugin,
+                ModelLoaderPlugin,
+                VolumePlugin,
+            ))
             .add_systems(Update, load_scene_if_changed::<T>)
-            .insert_resource(Msaa::Off)
             .add_systems(
                 Update,
                 (
                     load_camera_skybox_system,
-                    load_unity_mesh_system::<T>,
-                    fix_gltf_mesh,
+                    camera_order_system,
+                    camera_viewport_system,
+                    (
+                        load_requested_materials_system::<T>,
+                        (
+                            load_unity_mesh_system::<T>,
+                            load_model_materials_system::<T>,
+                        ),
+                    )
+                        .chain(),
+                    light_render_layers_system,
+                    setup_model_instance_system,
+                    setup_animator_controller_system::<T>,
+                    setup_legacy_animation_system::<T>,
+                    load_audio_source_system::<T>,
                     load_mesh_collider_system,
                 ),
+            )
+            .add_systems(
+                PostUpdate,
+                hide_shadows_only_system.after(VisibilitySystems::CheckVisibility),
             );
     }
 }
@@ -109,10 +142,26 @@ fn load_scene<T: Sync + Send + 'static + Default + MonoBehaviour + Clone>(
     T: MonoBehaviour + DeserializeOwned,
 {
     let render_settings = scene.get_render_settings();
+
+    // cameras look the skybox up while spawning
+    if let Some(guid) = render_settings.and_then(|r| r.skybox_material.guid.as_ref()) {
+        unity_res.skybox_texture(guid, asset_server);
+    }
+
     if let Some(render_settings) = render_settings {
+        let ambient: Color = render_settings.indirect_specular_color.into();
+        let ambient = scene
+            .get_lights()
+            .filter_map(|light| light.bounce_ambient())
+            .fold(
+                ambient * render_settings.ambient_intensity,
+                |acc, bounce| acc + bounce,
+            );
+
+        // brightness is already folded into the colour
         commands.insert_resource(AmbientLight {
-            color: render_settings.indirect_specular_color.into(),
-            brightness: render_settings.ambient_intensity,
+            color: ambient,
+            brightness: 1.0,
         });
     }
 
@@ -142,6 +191,9 @@ fn load_scene<T: Sync + Send + 'static + Default + MonoBehaviour + Clone>(
                 Name::new(game_object.name.clone()),
             ));
             entity.insert(UnityTransformMeta { object_id: comp_id });
+            if game_object.layer != 0 {
+                entity.insert(RenderLayers::layer(game_object.layer));
+            }
             entity.insert(VisibilityBundle {
                 visibility: if game_object.is_active() {
                     Visibility::Inherited
@@ -262,21 +314,23 @@ fn spawn_prefab<T: Sync + Send + 'static + Default + DeserializeOwned + MonoBeha
         return;
     };
 
-    if referenced_prefab.ends_with(".glb") || referenced_prefab.ends_with(".gltf") {
-        instantiate_gltf(
+    let extension = std::path::Path::new(referenced_prefab)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "glb" | "gltf" | "fbx" | "obj" => instantiate_model(
             guid,
+            prefab,
             &referenced_prefab.clone(),
             transform,
             cmd,
             res,
             asset_server,
-        );
-
-        return;
-    }
-
-    if referenced_prefab.ends_with(".prefab") {
-        instantiate_prefab(
+        ),
+        "prefab" => instantiate_prefab(
             id,
             guid,
             &referenced_prefab.clone(),
@@ -285,7 +339,8 @@ fn spawn_prefab<T: Sync + Send + 'static + Default + DeserializeOwned + MonoBeha
             res,
             asset_server,
             render_settings,
-        );
+        ),
+        _ => tracing::warn!("unsupported prefab model: {}", referenced_prefab),
     }
 }
 
@@ -363,66 +418,108 @@ fn instantiate_prefab<
         });
 }
 
-fn instantiate_gltf<T: Sync + Send + 'static + Default>(
+fn instantiate_model<T: Sync + Send + 'static + Default>(
     guid: &str,
+    prefab: &UnityPrefabInstance,
     path: &str,
     transform: Transform,
     cmd: &mut EntityCommands,
     res: &mut UnityResource<T>,
     asset_server: &Res<AssetServer>,
 ) {
+    let path = res.base_path.join("..").join(path);
+    let importer = res
+        .model_importers
+        .entry(guid.to_string())
+        .or_insert_with(|| match read_model_importer(&path) {
+            Ok(importer) => importer,
+            Err(e) => {
+                tracing::warn!("failed to read model import settings: {:?}", e);
+                UnityModelImporter::default()
+            }
+        })
+        .clone();
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    let is_gltf = extension == "glb" || extension == "gltf";
+
     let scene = if let Some(scene) = res.models.get(guid) {
         scene.clone()
     } else {
-        let path = res.base_path.join("..").join(path);
-        let path = format!("{}#Scene0", path.to_string_lossy());
-        let handle = asset_server.load(path);
+        let use_file_scale = importer.meshes.use_file_scale != 0;
+        let handle = match extension.as_str() {
+            "glb" | "gltf" => asset_server.load(format!("{}#Scene0", path.display())),
+            "fbx" => asset_server
+                .load_with_settings(path.clone(), move |s: &mut FbxLoaderSettings| {
+                    s.use_file_scale = use_file_scale
+                }),
+            _ => asset_server.load(path.clone()),
+        };
         res.models.insert(guid.to_string(), handle.clone());
         handle
     };
 
-    let visibility = Visibility::Hidden;
-
-    cmd.insert(SceneBundle {
-        scene: scene.clone(),
-        transform,
-        visibility,
-        ..default()
+    let gltf = is_gltf.then(|| {
+        res.gltfs
+            .entry(guid.to_string())
+            .or_insert_with(|| asset_server.load(path.clone()))
+            .clone()
     });
 
-    cmd.insert(FixGltfTransforms);
-}
-
-#[derive(Component)]
-struct FixGltfTransforms;
-
-fn fix_gltf_mesh(
-    query: Query<Entity, With<FixGltfTransforms>>,
-    children: Query<&Children>,
-    mut transforms: Query<&mut Transform, With<Handle<Mesh>>>,
-    mut commands: Commands,
-) {
-    for gltf_entity in &query {
-        let mut cmd = commands.entity(gltf_entity);
-
-        if children.iter_descendants(gltf_entity).count() == 0 {
-            // not loaded yet
-            continue;
-        }
-
-        for entity in children.iter_descendants(gltf_entity) {
-            if let Ok(mut transform) = transforms.get_mut(entity) {
-                transform.rotate_axis(Vec3::AXES[1], std::f32::consts::PI);
-            }
-        }
+    let renderers = res.model_objects.get(guid);
+    let material_overrides = prefab
+        .material_overrides()
+        .into_iter()
+        .filter_map(|(file_id, slot, material)| {
+            let Some(renderer) = renderers.and_then(|r| r.get(&file_id)) else {
+                tracing::warn!("unknown renderer {} in model {}", file_id, guid);
+                return None;
+            };
+            Some(UnityMaterialOverride {
+                renderer: renderer.clone(),
+                slot,
+                material,
+            })
+        })
+        .collect();
 
-        println!("handling gltf model: {:?}", gltf_entity);
-        cmd.insert(VisibilityBundle {
-            visibility: Visibility::Inherited,
+    // only glTF animations are loaded, fbx takes aren't read yet
+    if is_gltf && importer.imports_animations() {
+        let mut animator = UnityAnimator {
+            enabled: 1,
             ..default()
-        });
-        cmd.remove::<FixGltfTransforms>();
+        };
+        prefab.apply_animator_modifications(&mut animator);
+        animator.add_animator(cmd);
     }
+
+    // the unity transform stays on the instance, the import conversion goes
+    // on a child holding the model's scene
+    cmd.insert(SpatialBundle::from_transform(transform))
+        .with_children(|parent| {
+            parent.spawn((
+                SceneBundle {
+                    scene,
+                    transform: importer.root_transform(),
+                    ..default()
+                },
+                UnityModelInstance {
+                    guid: guid.to_string(),
+                    name: path
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    importer,
+                    gltf,
+                    material_overrides,
+                },
+                UnityModelRequiresSetup,
+            ));
+        });
 }
 
 // fn spawn_gltf(
@@ -451,6 +548,25 @@ fn fix_gltf_mesh(
 //     });
 // }
 
+/// Bevy lights only shadow entities sharing a layer with them, unity lights
+/// shadow every layer.
+#[allow(clippy::type_complexity)]
+fn light_render_layers_system(
+    lights: Query<Entity, Or<(Added<DirectionalLight>, Added<PointLight>, Added<SpotLight>)>>,
+    mut commands: Commands,
+) {
+    for entity in &lights {
+        commands.entity(entity).insert(RenderLayers::all());
+    }
+}
+
+/// A submesh of a renderer with more than one material, spawned as its child.
+#[derive(Component, Debug)]
+pub struct UnitySubmesh {
+    pub index: usize,
+}
+
+#[allow(clippy::type_complexity)]
 pub fn load_unity_mesh_system<T: Sync + Send + 'static + Default>(
     meshes: Query<
         (
@@ -458,6 +574,8 @@ pub fn load_unity_mesh_system<T: Sync + Send + 'static + Default>(
             &Transform,
             &UnityMeshFilterExtra,
             &UnityMeshRendererExtra,
+            Option<&Visibility>,
+            Option<&RenderLayers>,
         ),
         With<UnityMeshRequiresLoad>,
     >,
@@ -465,85 +583,202 @@ pub fn load_unity_mesh_system<T: Sync + Send + 'static + Default>(
     mut mesh_assets: ResMut<Assets<Mesh>>,
     mut unity_res: ResMut<UnityResource<T>>,
 ) {
-    for (entity, transform, mesh_filter, mesh_renderer) in &meshes {
+    for (entity, transform, mesh_filter, mesh_renderer, visibility, layers) in &meshes {
         let mf = &mesh_filter.mesh;
-        let mesh = load_primitve_mesh(mf, &mut mesh_assets, &mut unity_res);
+        let submeshes = match load_mesh(mf, &mut mesh_assets, &mut unity_res) {
+            Ok(submeshes) => submeshes,
+            Err(e) => {
+                tracing::error!("failed to load mesh: {:?}", e);
+                commands.entity(entity).remove::<UnityMeshRequiresLoad>();
+                continue;
+            }
+        };
 
-        let Some(material) = load_material(mesh_renderer, &unity_res) else {
+        // like unity, materials past the last submesh draw that submesh again
+        let slots: Vec<_> = mesh_renderer
+            .materials
+            .iter()
+            .enumerate()
+            .filter_map(|(index, material)| {
+                let guid = material.unique_id()?;
+                let handle = load_material(&guid, &unity_res)?;
+                let submesh = submeshes.get(index).or(submeshes.last())?.clone();
+                let mesh = apply_uv_transform(submesh, &guid, &mut mesh_assets, &mut unity_res);
+                Some((index, mesh, handle))
+            })
+            .collect();
+
+        if slots.is_empty() {
+            tracing::warn!("mesh renderer on {:?} has no materials to draw", entity);
+            commands.entity(entity).remove::<UnityMeshRequiresLoad>();
             continue;
-        };
+        }
+
+        // the bundles reset visibility, so it's put back after
+        let renderer = &mesh_renderer.renderer;
+        let visibility = visibility.copied().unwrap_or_default();
+        let layers = layers.copied().unwrap_or_default();
 
         let mut cmd = commands.entity(entity);
-        cmd.insert(PbrBundle {
-            mesh,
-            material,
-            transform: *transform,
-            ..default()
-        });
+        if let [(_, mesh, material)] = &slots[..] {
+            material.insert_bundle(&mut cmd, mesh.clone(), *transform);
+        } else {
+            cmd.insert(SpatialBundle::from_transform(*transform))
+                .with_children(|parent| {
+                    for (index, mesh, material) in slots {
+                        let mut child = parent.spawn(UnitySubmesh { index });
+                        material.insert_bundle(&mut child, mesh, Transform::IDENTITY);
+                        renderer.insert_render_flags(&mut child, Visibility::Inherited, layers);
+                    }
+                });
+        }
+        renderer.insert_render_flags(&mut cmd, visibility, layers);
 
         cmd.remove::<UnityMeshRequiresLoad>();
     }
 }
 
+/// Swaps model primitives' own materials for the unity materials remapped or
+/// overridden onto them.
+fn load_model_materials_system<T: Sync + Send + 'static + Default>(
+    primitives: Query<(
+        Entity,
+        &Handle<Mesh>,
+        &Transform,
+        &UnityModelMaterialRequiresLoad,
+    )>,
+    mut commands: Commands,
+    mut mesh_assets: ResMut<Assets<Mesh>>,
+    mut unity_res: ResMut<UnityResource<T>>,
+) {
+    for (entity, mesh, transform, request) in &primitives {
+        let Some(guid) = request.0.unique_id() else {
+            commands
+                .entity(entity)
+                .remove::<UnityModelMaterialRequiresLoad>();
+            continue;
+        };
+
+        let Some(material) = load_material(&guid, &unity_res) else {
+            if unity_res.requested_materials.contains(&guid) {
+                // requested but failed to convert, keep the model's own
+                commands
+                    .entity(entity)
+                    .remove::<UnityModelMaterialRequiresLoad>();
+            }
+            continue;
+        };
+
+        let mesh = apply_uv_transform(mesh.clone(), &guid, &mut mesh_assets, &mut unity_res);
+        let mut cmd = commands.entity(entity);
+        match material {
+            UnityMaterialHandle::Standard(material) => {
+                cmd.insert((mesh, material));
+            }
+            UnityMaterialHandle::Custom(custom) => {
+                cmd.remove::<Handle<StandardMaterial>>();
+                custom.insert_bundle(&mut cmd, mesh, *transform);
+            }
+        }
+        cmd.remove::<UnityModelMaterialRequiresLoad>();
+    }
+}
+
 fn load_material<T: Sync + Send + 'static + Default>(
-    mesh_renderer: &UnityMeshRendererExtra,
+    guid: &str,
     unity_res: &ResMut<UnityResource<T>>,
-) -> Option<Handle<StandardMaterial>> {
-    let mr = mesh_renderer.materials.first()?.guid.clone()?;
-    let mat = unity_res.standard_materials.get(&mr)?;
+) -> Option<UnityMaterialHandle> {
+    if let Some(custom) = unity_res.custom_materials.get(guid) {
+        return Some(UnityMaterialHandle::Custom(custom.clone()));
+    }
+
+    let mat = unity_res.standard_materials.get(guid)?;
+    Some(UnityMaterialHandle::Standard(mat.clone()))
+}
+
+/// Bevy's `StandardMaterial` has no uv transform, so a material's tiling and
+/// offset get baked into its own copy of the mesh.
+fn apply_uv_transform<T: Sync + Send + 'static + Default>(
+    mesh: Handle<Mesh>,
+    material_guid: &str,
+    mesh_assets: &mut ResMut<Assets<Mesh>>,
+    unity_res: &mut ResMut<UnityResource<T>>,
+) -> Handle<Mesh> {
+    let Some((scale, offset)) = unity_res
+        .materials_map
+        .get(material_guid)
+        .and_then(|m| m.uv_transform())
+    else {
+        return mesh;
+    };
+
+    let key = (mesh.id(), material_guid.to_string());
+    if let Some(existing) = unity_res.uv_meshes.get(&key) {
+        return existing.clone();
+    }
+
+    let Some(mut transformed) = mesh_assets.get(&mesh).cloned() else {
+        return mesh;
+    };
+
+    if let Some(VertexAttributeValues::Float32x2(uvs)) =
+        transformed.attribute_mut(Mesh::ATTRIBUTE_UV_0)
+    {
+        // unity's v runs bottom up, bevy's top down
+        for uv in uvs.iter_mut() {
+            uv[0] = uv[0] * scale.x + offset.x;
+            uv[1] = 1.0 - ((1.0 - uv[1]) * scale.y + offset.y);
+        }
+    }
+
+    let handle = mesh_assets.add(transformed);
+    unity_res.uv_meshes.insert(key, handle.clone());
 
-    Some(mat.clone())
+    handle
 }
 
-fn load_primitve_mesh<T: Sync + Send + 'static + Default>(
+/// Built-in primitives and `Mesh` `.asset` files, one handle per submesh.
+fn load_mesh<T: Sync + Send + 'static + Default>(
     mf: &FileReference,
     mesh_assets: &mut ResMut<Assets<Mesh>>,
     loaded: &mut ResMut<UnityResource<T>>,
-) -> Handle<Mesh> {
+) -> Result<Vec<Handle<Mesh>>> {
     let unique_id = format!("{}_{}", mf.guid.clone().unwrap_or_default(), mf.file_id);
     if let Some(existing) = loaded.meshes.get(&unique_id) {
-        return existing.clone();
+        return Ok(existing.clone());
     }
 
-    let handle = match mf.file_id {
-        // cube
-        10202 => mesh_assets.add(Mesh::from(shape::Cube { size: 1.0 })),
-
-        // plane
-        10209 => mesh_assets.add(Mesh::from(shape::Plane::from_size(10.0))),
-        // cylinder
-        10206 => mesh_assets.add(
-            shape::Cylinder {
-                radius: 0.5,
-                height: 2.0,
-                ..default()
-            }
-            .into(),
-        ),
-        // sphere
-        10207 => mesh_assets.add(
-            shape::UVSphere {
-                radius: 0.5,
-                ..default()
-            }
-            .into(),
-        ),
-        // capsule
-        10208 => mesh_assets.add(
-            shape::Capsule {
-                radius: 0.5,
-                depth: 1.0,
-                ..default()
-            }
-            .into(),
-        ),
-        _ => mesh_assets.add(Mesh::from(shape::UVSphere {
-            radius: 1.0,
-            ..default()
-        })),
+    let meshes = if let Some(builtin) = BuiltinMesh::from_reference(mf) {
+        vec![read_builtin_mesh(builtin)]
+    } else {
+        let Some(path) = mf
+            .guid
+            .as_ref()
+            .and_then(|guid| loaded.meshes_paths.get(guid))
+        else {
+            bail!("unsupported mesh {}", unique_id);
+        };
+        if !path.ends_with(".asset") {
+            bail!("unsupported mesh file {}", path);
+        }
+
+        read_mesh_asset(&loaded.base_path.join("..").join(path), mf.file_id)?
     };
 
-    loaded.meshes.insert(unique_id, handle.clone());
+    let handles: Vec<_> = meshes
+        .into_iter()
+        .map(|mut mesh| {
+            // normal mapped materials need tangents
+            if mesh.attribute(Mesh::ATTRIBUTE_TANGENT).is_none() {
+                if let Err(e) = mesh.generate_tangents() {
+                    tracing::warn!("failed to generate tangents for {}: {:?}", unique_id, e);
+                }
+            }
+            mesh_assets.add(mesh)
+        })
+        .collect();
 
-    handle
+    loaded.meshes.insert(unique_id, handles.clone());
+
+    Ok(handles)
 }
@@ -1,14 +1,25 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     marker::PhantomData,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use anyhow::Result;
-use bevity_primitives::UnityMaterial;
-use bevy::{gltf::Gltf, prelude::*};
+use bevity_primitives::{
+    convert_textures_system, AnimatorControllerGraph, BuiltinMaterial, FileReference,
+    TextureConversions, UnityAnimationClip, UnityMaterial, UnityMeshRendererExtra,
+    UnityMeshRequiresLoad, UnityModelImporter, BUILTIN_EXTRA_GUID, DEFAULT_RESOURCES_GUID,
+};
+use bevy::{
+    gltf::Gltf,
+    prelude::*,
+    render::texture::{
+        ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor,
+    },
+};
 
-use crate::UnityScene;
+use crate::{CustomMaterial, ShaderHandlerRegistry, UnityModelMaterialRequiresLoad, UnityScene};
 
 #[derive(Default)]
 pub struct ResourcesPlugin<T>(PhantomData<T>);
@@ -18,13 +29,38 @@ pub struct UnityResource<T: Default> {
     pub base_path: PathBuf,
     pub textures: HashMap<String, Handle<Image>>,
     pub standard_materials: HashMap<String, Handle<StandardMaterial>>,
+    /// Materials made by a [`ShaderHandlerRegistry`] handler, keyed by material guid.
+    pub custom_materials: HashMap<String, CustomMaterial>,
     pub gltfs: HashMap<String, Handle<Gltf>>,
     pub models: HashMap<String, Handle<Scene>>,
+    /// Import settings from each model's `.meta`, keyed by model guid.
+    pub model_importers: HashMap<String, UnityModelImporter>,
+    /// Names of renderer nodes and animation clips by their file id, keyed
+    /// by model guid.
+    pub model_objects: HashMap<String, HashMap<i64, String>>,
+    /// Parsed so far, see [`UnityResource::animator_controller`].
+    pub animator_controllers: HashMap<String, Arc<AnimatorControllerGraph>>,
+    pub controllers_paths: HashMap<String, String>,
+    /// `.anim` clips parsed so far, see [`UnityResource::animation_clip`].
+    pub animation_clips: HashMap<String, UnityAnimationClip>,
+    pub animation_clips_paths: HashMap<String, String>,
+    /// Audio clip paths by guid.
+    pub audio_paths: HashMap<String, String>,
 
-    pub meshes: HashMap<String, Handle<Mesh>>,
+    /// One mesh per submesh.
+    pub meshes: HashMap<String, Vec<Handle<Mesh>>>,
+    pub meshes_paths: HashMap<String, String>,
+    /// Copies of meshes with a material's uv tiling and offset baked in.
+    pub uv_meshes: HashMap<(AssetId<Mesh>, String), Handle<Mesh>>,
 
+    /// Materials parsed so far, see [`UnityResource::unity_material`].
     pub materials_map: HashMap<String, UnityMaterial>,
+    pub materials_paths: HashMap<String, String>,
+    /// Material guids converted, or that failed to, so far.
+    pub requested_materials: HashSet<String>,
     pub textures_map: HashMap<String, String>,
+    /// Shader names by shader guid, for [`crate::ShaderKey::Name`].
+    pub shader_names: HashMap<String, String>,
     pub prefabs: HashMap<String, UnityScene<T>>,
 
     pub all_map: HashMap<String, String>,
@@ -35,13 +71,9 @@ impl<T: Sync + Send + 'static + Default> Plugin for ResourcesPlugin<T> {
         let path = std::env::current_dir().unwrap();
         let path = Path::new(&path);
         let materials_json = path.join("materials.json");
-
-        let materials = match crate::read_materials(path, &materials_json) {
-            Ok(m) => m,
-            Err(e) => {
-                tracing::error!("failed to read materials: {:?}", e);
-                return;
-            }
+        let Ok(materials_paths) = read_guid_path_map(&materials_json) else {
+            tracing::error!("failed to parse materials json");
+            return;
         };
 
         let textures_json = path.join("textures.json");
@@ -56,78 +88,244 @@ impl<T: Sync + Send + 'static + Default> Plugin for ResourcesPlugin<T> {
             return;
         };
 
+        // only written by newer versions of the unity sdk
+        let shader_names = read_guid_path_map(&path.join("shaders.json")).unwrap_or_default();
+        let meshes_paths = read_guid_path_map(&path.join("meshes.json")).unwrap_or_default();
+        let controllers_paths =
+            read_guid_path_map(&path.join("controllers.json")).unwrap_or_default();
+        let animation_clips_paths =
+            read_guid_path_map(&path.join("clips.json")).unwrap_or_default();
+        let audio_paths = read_guid_path_map(&path.join("audio.json")).unwrap_or_default();
+        let model_objects = std::fs::read_to_string(path.join("models.json"))
+            .ok()
+            .and_then(|file| serde_json::from_str(&file).ok())
+            .unwrap_or_default();
+
         app.insert_resource(UnityResource::<T> {
             base_path: path.into(),
-            materials_map: materials,
+            materials_paths,
             textures_map,
+            shader_names,
+            meshes_paths,
+            model_objects,
+            controllers_paths,
+            animation_clips_paths,
+            audio_paths,
             all_map,
             ..default()
         })
-        .add_systems(
-            Startup,
-            (
-                load_textures_system::<T>,
-                load_materials::<T>.after(load_textures_system::<T>),
-            ),
-        );
+        .init_resource::<TextureConversions>()
+        .init_resource::<ShaderHandlerRegistry>()
+        .add_systems(Update, convert_textures_system);
     }
 }
 
-fn read_guid_path_map(path: &Path) -> Result<HashMap<String, String>> {
+pub(crate) fn read_guid_path_map(path: &Path) -> Result<HashMap<String, String>> {
     let file = std::fs::read_to_string(path)?;
     let texture_pathmap: HashMap<String, String> = serde_json::from_str(&file)?;
 
     Ok(texture_pathmap)
 }
 
-fn load_textures_system<T: Default + Sync + Send + 'static>(
-    asset_server: Res<AssetServer>,
-    mut unity_resources: ResMut<UnityResource<T>>,
-) {
-    let Ok(textures) = load_textures(
-        &unity_resources.base_path,
-        &unity_resources.textures_map,
-        asset_server,
-    ) else {
-        tracing::error!("failed to load textures from the unity side");
-        return;
-    };
+impl<T: Default> UnityResource<T> {
+    /// The parsed material, read from disk the first time it's asked for.
+    /// Built-in materials, like the default skybox, have no file to read.
+    pub fn unity_material(&mut self, guid: &str) -> Option<&UnityMaterial> {
+        if matches!(guid, DEFAULT_RESOURCES_GUID | BUILTIN_EXTRA_GUID) {
+            return None;
+        }
 
-    unity_resources.textures = textures;
-}
+        if !self.materials_map.contains_key(guid) {
+            let Some(path) = self.materials_paths.get(guid) else {
+                tracing::warn!("no material with guid {}", guid);
+                return None;
+            };
+
+            match crate::read_material(&self.base_path.join("..").join(path)) {
+                Ok(material) => {
+                    self.materials_map.insert(guid.to_string(), material);
+                }
+                Err(e) => {
+                    tracing::error!("failed to load material: {:?}", e);
+                    return None;
+                }
+            }
+        }
 
-fn load_textures(
-    base: &Path,
-    texture_pathmap: &HashMap<String, String>,
-    asset_server: Res<AssetServer>,
-) -> Result<HashMap<String, Handle<Image>>> {
-    texture_pathmap
-        .iter()
-        .try_fold(HashMap::new(), |mut acc, (k, v)| {
-            let path = base.join("..").join(v);
-            let handle = asset_server.load(path);
+        self.materials_map.get(guid)
+    }
 
-            acc.insert(k.clone(), handle);
+    /// The controller's base layer, read from disk the first time it's asked
+    /// for.
+    pub fn animator_controller(&mut self, guid: &str) -> Option<Arc<AnimatorControllerGraph>> {
+        if let Some(existing) = self.animator_controllers.get(guid) {
+            return Some(existing.clone());
+        }
 
-            Ok(acc)
-        })
+        let Some(path) = self.controllers_paths.get(guid) else {
+            tracing::warn!("no animator controller with guid {}", guid);
+            return None;
+        };
+
+        match crate::read_animator_controller(&self.base_path.join("..").join(path)) {
+            Ok(graph) => {
+                let graph = Arc::new(graph);
+                self.animator_controllers
+                    .insert(guid.to_string(), graph.clone());
+                Some(graph)
+            }
+            Err(e) => {
+                tracing::error!("failed to load animator controller: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// The parsed `.anim` clip, read from disk the first time it's asked for.
+    pub fn animation_clip(&mut self, guid: &str) -> Option<&UnityAnimationClip> {
+        if !self.animation_clips.contains_key(guid) {
+            let path = self.animation_clips_paths.get(guid)?;
+
+            match crate::read_animation_clip(&self.base_path.join("..").join(path)) {
+                Ok(clip) => {
+                    self.animation_clips.insert(guid.to_string(), clip);
+                }
+                Err(e) => {
+                    tracing::error!("failed to load animation clip: {:?}", e);
+                    return None;
+                }
+            }
+        }
+
+        self.animation_clips.get(guid)
+    }
+
+    /// The texture's handle, loaded the first time it's asked for. Built-in
+    /// textures become bevy's white default image.
+    pub fn texture(&mut self, guid: &str, asset_server: &AssetServer) -> Option<Handle<Image>> {
+        if matches!(guid, DEFAULT_RESOURCES_GUID | BUILTIN_EXTRA_GUID) {
+            return Some(Handle::default());
+        }
+
+        if let Some(existing) = self.textures.get(guid) {
+            return Some(existing.clone());
+        }
+
+        let path = self.base_path.join("..").join(self.textures_map.get(guid)?);
+        // unity's default wrap mode, needed for tiled materials
+        let handle = asset_server.load_with_settings(path, |s: &mut ImageLoaderSettings| {
+            s.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+                address_mode_u: ImageAddressMode::Repeat,
+                address_mode_v: ImageAddressMode::Repeat,
+                ..ImageSamplerDescriptor::linear()
+            });
+        });
+
+        self.textures.insert(guid.to_string(), handle.clone());
+        Some(handle)
+    }
+
+    pub fn skybox_texture(
+        &mut self,
+        material_guid: &str,
+        asset_server: &AssetServer,
+    ) -> Option<Handle<Image>> {
+        let texture = self
+            .unity_material(material_guid)?
+            .get_skybox_texture_id()?;
+        self.texture(&texture, asset_server)
+    }
+
+    /// Converts a material and loads its textures, once per guid. A shader
+    /// handler's material wins over a `StandardMaterial`.
+    fn load_material(&mut self, reference: &FileReference, world: &mut World) {
+        let Some(key) = reference.unique_id() else {
+            return;
+        };
+        if !self.requested_materials.insert(key.clone()) {
+            return;
+        }
+
+        if reference.is_builtin() {
+            let Some(standard) =
+                BuiltinMaterial::from_reference(reference).and_then(|b| b.standard_material())
+            else {
+                tracing::warn!("unsupported built-in material {}", reference.file_id);
+                return;
+            };
+
+            let handle = world
+                .resource_mut::<Assets<StandardMaterial>>()
+                .add(standard);
+            self.standard_materials.insert(key, handle);
+            return;
+        }
+
+        let guid = key.as_str();
+
+        let Some(material) = self.unity_material(guid) else {
+            return;
+        };
+        let texture_guids: Vec<_> = material
+            .properties
+            .tex_envs
+            .iter()
+            .flat_map(|t| t.values())
+            .filter_map(|t| t.texture.guid.clone())
+            .collect();
+
+        let asset_server = world.resource::<AssetServer>().clone();
+        for texture in texture_guids {
+            self.texture(&texture, &asset_server);
+        }
+
+        let material = &self.materials_map[guid];
+        let custom = world.resource_scope(|world, registry: Mut<ShaderHandlerRegistry>| {
+            registry.convert(material, &self.shader_names, &self.textures, world)
+        });
+        if let Some(custom) = custom {
+            self.custom_materials.insert(guid.to_string(), custom);
+            return;
+        }
+
+        let standard = world.resource_scope(|world, mut conversions: Mut<TextureConversions>| {
+            let images = world.resource::<Assets<Image>>();
+            material.get_standard_material(&self.textures, &mut conversions, images)
+        });
+        let Some(standard) = standard else {
+            tracing::warn!("unsupported shader in material {}", material.name);
+            return;
+        };
+
+        let handle = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(standard);
+        self.standard_materials.insert(guid.to_string(), handle);
+    }
 }
 
-fn load_materials<T: Sync + Send + 'static + Default>(
-    mut unity_res: ResMut<UnityResource<T>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+/// Loads the materials of renderers waiting on them, before
+/// `load_unity_mesh_system` spawns their meshes.
+pub(crate) fn load_requested_materials_system<T: Sync + Send + 'static + Default>(
+    world: &mut World,
 ) {
-    unity_res.standard_materials = unity_res
-        .materials_map
-        .iter()
-        .filter_map(|(guid, f)| {
-            let standard_material = f.get_standard_material()?;
-            Some((guid, standard_material))
-        })
-        .fold(HashMap::new(), |mut acc, (guid, mat)| {
-            let mat = materials.add(mat);
-            acc.insert(guid.clone(), mat);
+    let mut renderers =
+        world.query_filtered::<&UnityMeshRendererExtra, With<UnityMeshRequiresLoad>>();
+    let mut references: Vec<_> = renderers
+        .iter(world)
+        .flat_map(|renderer| renderer.materials.iter())
+        .cloned()
+        .collect();
+    let mut model_materials = world.query::<&UnityModelMaterialRequiresLoad>();
+    references.extend(model_materials.iter(world).map(|m| m.0.clone()));
 
-            acc
-        });
+    if references.is_empty() {
+        return;
+    }
+
+    world.resource_scope(|world, mut unity_res: Mut<UnityResource<T>>| {
+        for reference in &references {
+            unity_res.load_material(reference, world);
+        }
+    });
 }
@@ -1,15 +1,43 @@
+mod animation;
+mod animation_clip;
+mod animator_controller;
+mod audio;
+mod audio_mixer;
+mod builtin;
+mod camera;
+mod fbx;
 mod materials;
+mod meshes;
+mod models;
+mod obj;
 mod objects;
 mod parse;
 mod plugin;
 mod render;
 mod resources;
+mod scriptable;
+mod shaders;
 mod utils;
+mod volume;
 
+pub use animation::*;
+pub use animation_clip::*;
+pub use animator_controller::*;
+pub use audio::*;
+pub use audio_mixer::*;
+pub use builtin::*;
+pub use camera::*;
+pub use fbx::*;
 pub use materials::*;
+pub use meshes::*;
+pub use models::*;
+pub use obj::*;
 pub use objects::*;
 pub use parse::*;
 pub use plugin::*;
 pub use render::*;
 pub use resources::*;
+pub use scriptable::*;
+pub use shaders::*;
 pub use utils::*;
+pub use volume::*;
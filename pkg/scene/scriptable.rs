@@ -0,0 +1,308 @@
+use std::{collections::HashMap, marker::PhantomData, path::Path};
+
+use anyhow::{Context, Result};
+use bevity_yaml::parse_unity_yaml_file;
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+
+use crate::read_guid_path_map;
+
+/// A rust struct backing a generated unity `ScriptableObject` class.
+/// Implemented with `#[derive(ScriptableObject)]`.
+pub trait ScriptableObject: DeserializeOwned + Send + Sync + 'static {
+    fn script_guid() -> Option<&'static str>;
+}
+
+/// All `.asset` files holding an `A`, keyed by their unity guid.
+#[derive(Resource)]
+pub struct UnityAssets<A: Asset> {
+    pub assets: HashMap<String, Handle<A>>,
+    pending: Vec<(String, A)>,
+}
+
+impl<A: Asset> Default for UnityAssets<A> {
+    fn default() -> Self {
+        Self {
+            assets: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<A: Asset> UnityAssets<A> {
+    pub fn get(&self, guid: &str) -> Option<&Handle<A>> {
+        self.assets.get(guid)
+    }
+}
+
+/// Loads every `A` scriptable object into `Assets<A>`, see [`UnityAssets`].
+pub struct ScriptableObjectPlugin<A>(PhantomData<A>);
+
+impl<A> Default for ScriptableObjectPlugin<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: ScriptableObject + Asset> Plugin for ScriptableObjectPlugin<A> {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<Assets<A>>() {
+            app.init_asset::<A>();
+        }
+
+        let pending = match read_scriptable_objects::<A>() {
+            Ok(objects) => objects,
+            Err(e) => {
+                tracing::error!("failed to read scriptable objects: {:?}", e);
+                vec![]
+            }
+        };
+
+        app.insert_resource(UnityAssets::<A> {
+            pending,
+            ..default()
+        })
+        .add_systems(Startup, add_scriptable_objects::<A>);
+    }
+}
+
+fn add_scriptable_objects<A: Asset>(
+    mut unity_assets: ResMut<UnityAssets<A>>,
+    mut assets: ResMut<Assets<A>>,
+) {
+    let pending = std::mem::take(&mut unity_assets.pending);
+    for (guid, object) in pending {
+        let handle = assets.add(object);
+        unity_assets.assets.insert(guid, handle);
+    }
+}
+
+/// Inserts a scriptable object as the `R` resource. Uses the asset with the
+/// given guid, or the only `R` in the project when none is given. With more
+/// than one and no guid, the first by asset path is used.
+pub struct ScriptableResourcePlugin<R> {
+    guid: Option<String>,
+    marker: PhantomData<R>,
+}
+
+impl<R> Default for ScriptableResourcePlugin<R> {
+    fn default() -> Self {
+        Self {
+            guid: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R> ScriptableResourcePlugin<R> {
+    pub fn from_guid(guid: &str) -> Self {
+        Self {
+            guid: Some(guid.to_string()),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: ScriptableObject + Resource> Plugin for ScriptableResourcePlugin<R> {
+    fn build(&self, app: &mut App) {
+        let objects = match read_scriptable_objects::<R>() {
+            Ok(objects) => objects,
+            Err(e) => {
+                tracing::error!("failed to read scriptable objects: {:?}", e);
+                return;
+            }
+        };
+
+        let count = objects.len();
+        let found = objects.into_iter().find(|(guid, _)| match &self.guid {
+            Some(wanted) => wanted == guid,
+            None => true,
+        });
+
+        let Some((guid, resource)) = found else {
+            tracing::error!(
+                "no scriptable object found for {}",
+                std::any::type_name::<R>()
+            );
+            return;
+        };
+
+        if self.guid.is_none() && count > 1 {
+            tracing::warn!(
+                "found {} scriptable objects for {}, using {}. pick one with ScriptableResourcePlugin::from_guid",
+                count,
+                std::any::type_name::<R>(),
+                guid
+            );
+        }
+
+        app.insert_resource(resource);
+    }
+}
+
+fn read_scriptable_objects<A: ScriptableObject>() -> Result<Vec<(String, A)>> {
+    let Some(script_guid) = A::script_guid() else {
+        anyhow::bail!(
+            "no script guid for {}, is bevity::build called from build.rs?",
+            std::any::type_name::<A>()
+        );
+    };
+
+    let path = std::env::current_dir()?;
+    let scriptables = read_guid_path_map(&path.join("scriptables.json"))
+        .context("failed to parse scriptables json")?;
+
+    Ok(read_listed_scriptable_objects(
+        &path,
+        &scriptables,
+        script_guid,
+    ))
+}
+
+/// Reads the listed `.asset` files holding the script, ordered by their path.
+/// Files that fail to parse are skipped, the list covers every asset in the
+/// project.
+fn read_listed_scriptable_objects<A: DeserializeOwned>(
+    path: &Path,
+    scriptables: &HashMap<String, String>,
+    script_guid: &str,
+) -> Vec<(String, A)> {
+    let mut scriptables: Vec<_> = scriptables.iter().collect();
+    scriptables.sort_by_key(|(_, asset_path)| *asset_path);
+
+    scriptables
+        .into_iter()
+        .filter(|(_, asset_path)| asset_path.ends_with(".asset"))
+        .filter_map(|(guid, asset_path)| {
+            let file = path.join("..").join(asset_path);
+            match read_scriptable_object(&file, script_guid) {
+                Ok(object) => Some((guid.clone(), object?)),
+                Err(e) => {
+                    tracing::warn!("skipping scriptable object: {:?}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn read_scriptable_object<A: DeserializeOwned>(
+    path: &Path,
+    script_guid: &str,
+) -> Result<Option<A>> {
+    let objects = parse_unity_yaml_file::<serde_json::Value>(&path.to_string_lossy())
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    let Some(object) = objects.into_values().find(|object| {
+        object
+            .get("m_Script")
+            .and_then(|script| script.get("guid"))
+            .and_then(|guid| guid.as_str())
+            == Some(script_guid)
+    }) else {
+        return Ok(None);
+    };
+
+    let object = serde_json::from_value(object)
+        .with_context(|| format!("failed to read scriptable object {}", path.display()))?;
+
+    Ok(Some(object))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Settings {
+        speed: f32,
+    }
+
+    /// A project directory of its own for each test, removed afterwards.
+    struct TestProject(PathBuf);
+
+    impl TestProject {
+        fn new(name: &str) -> Result<Self> {
+            let dir = std::env::temp_dir().join(format!(
+                "bevity_scriptable_{}_{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(dir.join("Assets"))?;
+            std::fs::create_dir_all(dir.join("game"))?;
+            Ok(Self(dir))
+        }
+
+        fn write_settings(&self, name: &str, speed: f32) -> Result<()> {
+            std::fs::write(
+                self.0.join("Assets").join(name),
+                format!(
+                    "%YAML 1.1\n--- !u!114 &11400000\nMonoBehaviour:\n  m_Script: {{fileID: 11500000, guid: abc, type: 3}}\n  speed: {}\n",
+                    speed
+                ),
+            )?;
+            Ok(())
+        }
+
+        fn read(&self, scriptables: &[(&str, &str)]) -> Vec<(String, Settings)> {
+            let scriptables = scriptables
+                .iter()
+                .map(|(guid, path)| (guid.to_string(), path.to_string()))
+                .collect();
+            read_listed_scriptable_objects(&self.0.join("game"), &scriptables, "abc")
+        }
+    }
+
+    impl Drop for TestProject {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_broken_asset_is_skipped() -> Result<()> {
+        let project = TestProject::new("broken")?;
+        project.write_settings("Good.asset", 2.0)?;
+        std::fs::write(
+            project.0.join("Assets/Broken.asset"),
+            "%YAML 1.1\n--- !u!114 &1\n: [\n",
+        )?;
+
+        let objects = project.read(&[
+            ("good", "Assets/Good.asset"),
+            ("broken", "Assets/Broken.asset"),
+            ("missing", "Assets/Missing.asset"),
+        ]);
+
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].0, "good");
+        assert_eq!(objects[0].1.speed, 2.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_objects_ordered_by_path() -> Result<()> {
+        let project = TestProject::new("order")?;
+        for (name, speed) in [("A.asset", 1.0), ("B.asset", 2.0), ("C.asset", 3.0)] {
+            project.write_settings(name, speed)?;
+        }
+
+        let objects = project.read(&[
+            ("c", "Assets/C.asset"),
+            ("a", "Assets/A.asset"),
+            ("b", "Assets/B.asset"),
+        ]);
+
+        let guids: Vec<_> = objects.iter().map(|(guid, _)| guid.as_str()).collect();
+        assert_eq!(guids, ["a", "b", "c"]);
+        assert_eq!(objects[0].1.speed, 1.0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+use std::{collections::HashMap, sync::Arc};
+
+use bevity_primitives::UnityMaterial;
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+/// How a handler is matched to a material's shader.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ShaderKey {
+    /// Guid of the `.shader`/`.shadergraph` asset.
+    Guid(String),
+    /// Shader name as shown in unity's material inspector, e.g. `Custom/Water`.
+    /// Resolved through `shaders.json`.
+    Name(String),
+}
+
+/// What a shader handler gets to build its material from.
+pub struct ShaderContext<'a> {
+    pub material: &'a UnityMaterial,
+    pub textures: &'a HashMap<String, Handle<Image>>,
+}
+
+impl ShaderContext<'_> {
+    pub fn float(&self, name: &str) -> Option<f32> {
+        self.material.properties.float(name)
+    }
+
+    pub fn color(&self, name: &str) -> Option<Color> {
+        self.material.properties.color(name).map(Color::from)
+    }
+
+    pub fn texture(&self, name: &str) -> Option<Handle<Image>> {
+        let info = self.material.properties.texture_info(name)?;
+        self.textures.get(info.texture.guid.as_ref()?).cloned()
+    }
+}
+
+/// A material made by a registered shader handler. Puts a
+/// `MaterialMeshBundle` of the handler's material type on an entity.
+#[derive(Clone)]
+pub struct CustomMaterial(Arc<InsertBundle>);
+
+type InsertBundle = dyn Fn(&mut EntityCommands, Handle<Mesh>, Transform) + Send + Sync;
+
+impl CustomMaterial {
+    pub fn insert_bundle(
+        &self,
+        cmd: &mut EntityCommands,
+        mesh: Handle<Mesh>,
+        transform: Transform,
+    ) {
+        (self.0)(cmd, mesh, transform)
+    }
+}
+
+/// A converted unity material, from bevity's own conversion or a shader handler.
+#[derive(Clone)]
+pub enum UnityMaterialHandle {
+    Standard(Handle<StandardMaterial>),
+    Custom(CustomMaterial),
+}
+
+impl UnityMaterialHandle {
+    pub fn insert_bundle(
+        &self,
+        cmd: &mut EntityCommands,
+        mesh: Handle<Mesh>,
+        transform: Transform,
+    ) {
+        match self {
+            UnityMaterialHandle::Standard(material) => {
+                cmd.insert(PbrBundle {
+                    mesh,
+                    material: material.clone(),
+                    transform,
+                    ..default()
+                });
+            }
+            UnityMaterialHandle::Custom(custom) => custom.insert_bundle(cmd, mesh, transform),
+        }
+    }
+}
+
+type ShaderHandler =
+    Box<dyn Fn(&ShaderContext, &mut World) -> Option<CustomMaterial> + Send + Sync>;
+
+/// Converts materials using custom shaders into bevy materials. Handlers take
+/// precedence over bevity's own Standard/URP conversion. The material type's
+/// `MaterialPlugin` has to be added by the game.
+#[derive(Resource, Default)]
+pub struct ShaderHandlerRegistry {
+    handlers: HashMap<ShaderKey, ShaderHandler>,
+}
+
+impl ShaderHandlerRegistry {
+    pub fn register<M: Material>(
+        &mut self,
+        key: ShaderKey,
+        handler: impl Fn(&ShaderContext) -> Option<M> + Send + Sync + 'static,
+    ) {
+        let handler = move |context: &ShaderContext, world: &mut World| {
+            let material = handler(context)?;
+            let handle = world.resource_mut::<Assets<M>>().add(material);
+
+            Some(CustomMaterial(Arc::new(
+                move |cmd: &mut EntityCommands, mesh, transform| {
+                    cmd.insert(MaterialMeshBundle {
+                        mesh,
+                        material: handle.clone(),
+                        transform,
+                        ..default()
+                    });
+                },
+            )))
+        };
+
+        self.handlers.insert(key, Box::new(handler));
+    }
+
+    /// The handler's material, `None` when no handler matches or it declined.
+    /// A handler registered by guid is picked over one registered by name.
+    pub fn convert(
+        &self,
+        material: &UnityMaterial,
+        shader_names: &HashMap<String, String>,
+        textures: &HashMap<String, Handle<Image>>,
+        world: &mut World,
+    ) -> Option<CustomMaterial> {
+        let guid = material.shader.guid.as_ref()?;
+        let handler = self
+            .handlers
+            .get(&ShaderKey::Guid(guid.clone()))
+            .or_else(|| {
+                let name = shader_names.get(guid)?;
+                self.handlers.get(&ShaderKey::Name(name.clone()))
+            })?;
+
+        let context = ShaderContext { material, textures };
+        handler(&context, world)
+    }
+}
+
+/// Registers a [`ShaderHandlerRegistry`] handler straight on the app.
+pub trait RegisterUnityShader {
+    fn register_unity_shader<M: Material>(
+        &mut self,
+        key: ShaderKey,
+        handler: impl Fn(&ShaderContext) -> Option<M> + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl RegisterUnityShader for App {
+    fn register_unity_shader<M: Material>(
+        &mut self,
+        key: ShaderKey,
+        handler: impl Fn(&ShaderContext) -> Option<M> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.init_resource::<ShaderHandlerRegistry>();
+        self.world
+            .resource_mut::<ShaderHandlerRegistry>()
+            .register(key, handler);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADER_GUID: &str = "0f1e2d3c4b5a69788796a5b4c3d2e1f0";
+
+    fn material(shader_guid: &str, smoothness: f32) -> UnityMaterial {
+        let mut material = UnityMaterial::default();
+        material.shader.guid = Some(shader_guid.to_string());
+        material.properties.floats = vec![HashMap::from([("_Smoothness".to_string(), smoothness)])];
+        material
+    }
+
+    fn colored(color: Color) -> impl Fn(&ShaderContext) -> Option<StandardMaterial> {
+        move |context: &ShaderContext| {
+            Some(StandardMaterial {
+                base_color: color,
+                perceptual_roughness: 1.0 - context.float("_Smoothness")?,
+                ..default()
+            })
+        }
+    }
+
+    /// Converts the material and returns the color of the material the
+    /// handler added.
+    fn convert(
+        registry: &ShaderHandlerRegistry,
+        material: &UnityMaterial,
+        shader_names: &HashMap<String, String>,
+    ) -> Option<Color> {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+
+        registry.convert(material, shader_names, &HashMap::new(), &mut world)?;
+
+        let materials = world.resource::<Assets<StandardMaterial>>();
+        assert_eq!(materials.len(), 1);
+        let (_, converted) = materials.iter().next().unwrap();
+        assert_eq!(converted.perceptual_roughness, 0.25);
+        Some(converted.base_color)
+    }
+
+    #[test]
+    fn test_convert_dispatch() {
+        let shader_names = HashMap::from([(SHADER_GUID.to_string(), "Custom/Water".to_string())]);
+
+        let mut registry = ShaderHandlerRegistry::default();
+        registry.register(
+            ShaderKey::Name("Custom/Water".to_string()),
+            colored(Color::BLUE),
+        );
+        assert_eq!(
+            convert(&registry, &material(SHADER_GUID, 0.75), &shader_names),
+            Some(Color::BLUE)
+        );
+
+        // guid goes before name
+        registry.register(
+            ShaderKey::Guid(SHADER_GUID.to_string()),
+            colored(Color::RED),
+        );
+        assert_eq!(
+            convert(&registry, &material(SHADER_GUID, 0.75), &shader_names),
+            Some(Color::RED)
+        );
+        assert_eq!(
+            convert(&registry, &material(SHADER_GUID, 0.75), &HashMap::new()),
+            Some(Color::RED)
+        );
+
+        // no handler, left to the StandardMaterial conversion
+        let other = material("00112233445566778899aabbccddeeff", 0.75);
+        assert_eq!(convert(&registry, &other, &shader_names), None);
+        assert_eq!(
+            convert(&registry, &UnityMaterial::default(), &shader_names),
+            None
+        );
+    }
+
+    #[test]
+    fn test_declined_handler() {
+        let mut registry = ShaderHandlerRegistry::default();
+        registry.register(
+            ShaderKey::Guid(SHADER_GUID.to_string()),
+            colored(Color::RED),
+        );
+
+        // no _Smoothness, the handler gives up
+        let mut material = material(SHADER_GUID, 0.75);
+        material.properties.floats.clear();
+        assert_eq!(convert(&registry, &material, &HashMap::new()), None);
+    }
+}
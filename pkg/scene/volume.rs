@@ -0,0 +1,557 @@
+//! URP/HDRP post processing volumes, blended per camera into bevy's
+//! tonemapping, color grading and bloom.
+//!
+//! Only overrides bevy 0.12 has a counterpart for are read: bloom,
+//! tonemapping and the exposure and saturation of color adjustments.
+//! Vignette and depth of field have no built-in bevy effect to map onto, so
+//! like any other override they're skipped with a warning.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use bevity_primitives::FileReference;
+use bevity_yaml::parse_unity_yaml;
+use bevy::{
+    core_pipeline::{
+        bloom::{BloomPrefilterSettings, BloomSettings},
+        tonemapping::Tonemapping,
+    },
+    prelude::*,
+    render::view::{ColorGrading, RenderLayers},
+};
+use bevy_rapier3d::prelude::Collider;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{read_guid_path_map, UniversalAdditionalCameraData};
+
+/// URP/HDRP `Volume`, blended onto cameras by [`volume_blend_system`].
+#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+pub struct UnityVolume {
+    #[serde(default = "default_true", alias = "m_Enabled")]
+    pub enabled: i32,
+
+    #[serde(default = "default_true", alias = "m_IsGlobal")]
+    pub is_global: i32,
+
+    #[serde(default)]
+    pub priority: f32,
+
+    #[serde(default, alias = "blendDistance")]
+    pub blend_distance: f32,
+
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+
+    #[serde(default, alias = "sharedProfile")]
+    pub shared_profile: FileReference,
+}
+
+fn default_true() -> i32 {
+    1
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// The overrides of a `VolumeProfile` asset bevy has an equivalent for.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VolumeProfile {
+    pub bloom_intensity: Option<f32>,
+    pub bloom_threshold: Option<f32>,
+    pub bloom_scatter: Option<f32>,
+    pub tonemapping: Option<i32>,
+    pub post_exposure: Option<f32>,
+    pub saturation: Option<f32>,
+}
+
+/// Parsed volume profiles keyed by guid, looked up through `scriptables.json`.
+#[derive(Resource, Default)]
+pub struct VolumeProfiles {
+    pub profiles: HashMap<String, VolumeProfile>,
+    paths: Option<HashMap<String, String>>,
+}
+
+impl VolumeProfiles {
+    fn load(&mut self, guid: &str) -> Result<()> {
+        if self.profiles.contains_key(guid) {
+            return Ok(());
+        }
+
+        let base = std::env::current_dir()?;
+        let paths = match &self.paths {
+            Some(paths) => paths,
+            None => self.paths.insert(
+                read_guid_path_map(&base.join("scriptables.json"))
+                    .context("failed to parse scriptables json")?,
+            ),
+        };
+
+        let path = paths
+            .get(guid)
+            .with_context(|| format!("no volume profile with guid {}", guid))?;
+        let profile = parse_volume_profile(&base.join("..").join(path).to_string_lossy())?;
+
+        self.profiles.insert(guid.to_string(), profile);
+        Ok(())
+    }
+}
+
+pub fn parse_volume_profile(path: &str) -> Result<VolumeProfile> {
+    let file = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read volume profile {}", path))?;
+    volume_profile_from_yaml(&file, path)
+}
+
+/// The profile in a volume profile asset's yaml, `path` names it in warnings.
+pub fn volume_profile_from_yaml(yaml: &str, path: &str) -> Result<VolumeProfile> {
+    let objects = parse_unity_yaml::<serde_json::Value>(yaml)
+        .with_context(|| format!("failed to parse volume profile {}", path))?;
+
+    let mut profile = VolumeProfile::default();
+    for component in objects.values() {
+        if component.get("active").and_then(|a| a.as_i64()) == Some(0) {
+            continue;
+        }
+
+        let Some(name) = component.get("m_Name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+
+        match name {
+            "Bloom" => {
+                profile.bloom_intensity = parameter(component, "intensity");
+                profile.bloom_threshold = parameter(component, "threshold");
+                profile.bloom_scatter = parameter(component, "scatter");
+            }
+            "Tonemapping" => profile.tonemapping = parameter(component, "mode"),
+            "ColorAdjustments" => {
+                profile.post_exposure = parameter(component, "postExposure");
+                profile.saturation = parameter(component, "saturation");
+            }
+            // the profile asset itself
+            _ if component.get("components").is_some() => {}
+            _ => tracing::warn!("unsupported volume component {} in {}", name, path),
+        }
+    }
+
+    Ok(profile)
+}
+
+/// A volume parameter's value, if its override is ticked.
+fn parameter<T: DeserializeOwned>(component: &serde_json::Value, name: &str) -> Option<T> {
+    let parameter = component.get(name)?;
+    if parameter.get("m_OverrideState")?.as_i64()? == 0 {
+        return None;
+    }
+
+    serde_json::from_value(parameter.get("m_Value")?.clone()).ok()
+}
+
+/// Blended volume values, starting from unity's defaults.
+#[derive(Debug, Clone, Copy)]
+struct VolumeState {
+    bloom_intensity: f32,
+    bloom_threshold: f32,
+    bloom_scatter: f32,
+    tonemapping: i32,
+    post_exposure: f32,
+    saturation: f32,
+}
+
+impl Default for VolumeState {
+    fn default() -> Self {
+        Self {
+            bloom_intensity: 0.0,
+            bloom_threshold: 0.9,
+            bloom_scatter: 0.7,
+            tonemapping: TONEMAPPING_NONE,
+            post_exposure: 0.0,
+            saturation: 0.0,
+        }
+    }
+}
+
+impl VolumeState {
+    fn blend(&mut self, profile: &VolumeProfile, t: f32) {
+        let lerp = |current: &mut f32, target: Option<f32>| {
+            if let Some(target) = target {
+                *current += (target - *current) * t;
+            }
+        };
+
+        lerp(&mut self.bloom_intensity, profile.bloom_intensity);
+        lerp(&mut self.bloom_threshold, profile.bloom_threshold);
+        lerp(&mut self.bloom_scatter, profile.bloom_scatter);
+        lerp(&mut self.post_exposure, profile.post_exposure);
+        lerp(&mut self.saturation, profile.saturation);
+
+        // enums can't blend, unity switches as soon as the volume has any influence
+        if let Some(mode) = profile.tonemapping.filter(|_| t > 0.0) {
+            self.tonemapping = mode;
+        }
+    }
+
+    /// Unity's default looking bloom intensity of 1 is bevy's default of 0.15,
+    /// and both default their scatter/low frequency boost to 0.7.
+    fn bloom(&self) -> Option<BloomSettings> {
+        if self.bloom_intensity <= 0.0 {
+            return None;
+        }
+
+        Some(BloomSettings {
+            intensity: (self.bloom_intensity * 0.15).min(1.0),
+            low_frequency_boost: self.bloom_scatter,
+            prefilter_settings: BloomPrefilterSettings {
+                threshold: self.bloom_threshold,
+                threshold_softness: 0.5,
+            },
+            ..default()
+        })
+    }
+
+    fn tonemapping(&self) -> Tonemapping {
+        match self.tonemapping {
+            TONEMAPPING_NEUTRAL => Tonemapping::ReinhardLuminance,
+            TONEMAPPING_ACES => Tonemapping::AcesFitted,
+            _ => Tonemapping::None,
+        }
+    }
+
+    fn color_grading(&self) -> ColorGrading {
+        ColorGrading {
+            exposure: self.post_exposure,
+            post_saturation: 1.0 + self.saturation / 100.0,
+            ..default()
+        }
+    }
+}
+
+// https://docs.unity3d.com/Packages/com.unity.render-pipelines.universal@latest/index.html?subfolder=/api/UnityEngine.Rendering.Universal.TonemappingMode.html
+const TONEMAPPING_NONE: i32 = 0;
+const TONEMAPPING_NEUTRAL: i32 = 1;
+const TONEMAPPING_ACES: i32 = 2;
+
+pub struct VolumePlugin;
+
+impl Plugin for VolumePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VolumeProfiles>().add_systems(
+            Update,
+            (load_volume_profiles_system, volume_blend_system).chain(),
+        );
+    }
+}
+
+fn load_volume_profiles_system(
+    volumes: Query<&UnityVolume, Added<UnityVolume>>,
+    mut profiles: ResMut<VolumeProfiles>,
+) {
+    for volume in &volumes {
+        let Some(guid) = &volume.shared_profile.guid else {
+            continue;
+        };
+
+        if let Err(e) = profiles.load(guid) {
+            tracing::error!("failed to load volume profile: {:?}", e);
+            profiles
+                .profiles
+                .insert(guid.clone(), VolumeProfile::default());
+        }
+    }
+}
+
+/// Influence of a local volume on a camera `distance` away from its
+/// colliders, unity's quadratic fade over the blend distance.
+fn local_influence(distance: f32, blend_distance: f32) -> Option<f32> {
+    if distance > blend_distance {
+        return None;
+    }
+
+    if blend_distance > 0.0 {
+        Some(1.0 - (distance * distance) / (blend_distance * blend_distance))
+    } else {
+        Some(1.0)
+    }
+}
+
+/// Blends every volume onto each camera the way unity does: by ascending
+/// priority, with local volumes fading out over their blend distance from
+/// their colliders.
+#[allow(clippy::type_complexity)]
+pub fn volume_blend_system(
+    mut cameras: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut Tonemapping,
+            &mut ColorGrading,
+            Option<&BloomSettings>,
+            Option<&UniversalAdditionalCameraData>,
+        ),
+        With<Camera3d>,
+    >,
+    volumes: Query<(&UnityVolume, Option<&RenderLayers>, Option<&Children>)>,
+    colliders: Query<(&Collider, &GlobalTransform)>,
+    profiles: Res<VolumeProfiles>,
+    mut commands: Commands,
+) {
+    let mut volumes: Vec<_> = volumes
+        .iter()
+        .filter(|(volume, _, _)| volume.enabled != 0 && volume.weight > 0.0)
+        .filter_map(|(volume, layers, children)| {
+            let profile = profiles
+                .profiles
+                .get(volume.shared_profile.guid.as_ref()?)?;
+            Some((
+                volume,
+                profile,
+                layers.cloned().unwrap_or_default(),
+                children,
+            ))
+        })
+        .collect();
+
+    if volumes.is_empty() {
+        return;
+    }
+
+    volumes.sort_by(|(a, ..), (b, ..)| a.priority.total_cmp(&b.priority));
+
+    for (entity, camera_transform, mut tonemapping, mut color_grading, bloom, urp) in &mut cameras {
+        // like unity, cameras with post processing off ignore volumes
+        if urp.is_some_and(|urp| urp.render_post_processing == 0) {
+            continue;
+        }
+
+        let camera_position = camera_transform.translation();
+        let volume_mask = urp.map(|urp| urp.volume_layer_mask.render_layers());
+
+        let mut state = VolumeState::default();
+        for (volume, profile, layers, children) in &volumes {
+            if volume_mask.is_some_and(|mask| !mask.intersects(layers)) {
+                continue;
+            }
+
+            let influence = if volume.is_global != 0 {
+                1.0
+            } else {
+                let Some(distance) = children.and_then(|children| {
+                    children
+                        .iter()
+                        .filter_map(|child| colliders.get(*child).ok())
+                        .map(|(collider, transform)| {
+                            let (_, rotation, translation) =
+                                transform.to_scale_rotation_translation();
+                            collider.distance_to_point(translation, rotation, camera_position, true)
+                        })
+                        .reduce(f32::min)
+                }) else {
+                    continue;
+                };
+
+                let Some(influence) = local_influence(distance, volume.blend_distance) else {
+                    continue;
+                };
+                influence
+            };
+
+            state.blend(profile, (influence * volume.weight).clamp(0.0, 1.0));
+        }
+
+        let new_tonemapping = state.tonemapping();
+        if *tonemapping != new_tonemapping {
+            *tonemapping = new_tonemapping;
+        }
+
+        let new_grading = state.color_grading();
+        if color_grading.exposure != new_grading.exposure
+            || color_grading.post_saturation != new_grading.post_saturation
+        {
+            *color_grading = new_grading;
+        }
+
+        match (state.bloom(), bloom) {
+            (Some(new_bloom), Some(bloom))
+                if new_bloom.intensity == bloom.intensity
+                    && new_bloom.low_frequency_boost == bloom.low_frequency_boost
+                    && new_bloom.prefilter_settings.threshold
+                        == bloom.prefilter_settings.threshold => {}
+            (Some(new_bloom), _) => {
+                commands.entity(entity).insert(new_bloom);
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<BloomSettings>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLOBAL_PROFILE: &str = r#"%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!114 &-7893295128165547882
+MonoBehaviour:
+  m_ObjectHideFlags: 3
+  m_Enabled: 1
+  m_Name: Bloom
+  active: 1
+  threshold:
+    m_OverrideState: 1
+    m_Value: 1.1
+  intensity:
+    m_OverrideState: 1
+    m_Value: 1
+  scatter:
+    m_OverrideState: 0
+    m_Value: 0.5
+--- !u!114 &-2316428362415735214
+MonoBehaviour:
+  m_ObjectHideFlags: 3
+  m_Enabled: 1
+  m_Name: Tonemapping
+  active: 1
+  mode:
+    m_OverrideState: 1
+    m_Value: 2
+--- !u!114 &-1092543176513093641
+MonoBehaviour:
+  m_ObjectHideFlags: 3
+  m_Enabled: 1
+  m_Name: ColorAdjustments
+  active: 1
+  postExposure:
+    m_OverrideState: 0
+    m_Value: 1
+  saturation:
+    m_OverrideState: 1
+    m_Value: 20
+--- !u!114 &-515476324135683211
+MonoBehaviour:
+  m_ObjectHideFlags: 3
+  m_Enabled: 1
+  m_Name: Vignette
+  active: 1
+  intensity:
+    m_OverrideState: 1
+    m_Value: 0.4
+--- !u!114 &11400000
+MonoBehaviour:
+  m_ObjectHideFlags: 0
+  m_Enabled: 1
+  m_Name: GlobalProfile
+  components:
+  - {fileID: -7893295128165547882}
+  - {fileID: -2316428362415735214}
+  - {fileID: -1092543176513093641}
+  - {fileID: -515476324135683211}
+"#;
+
+    const LOCAL_PROFILE: &str = r#"%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!114 &-1092543176513093641
+MonoBehaviour:
+  m_Name: ColorAdjustments
+  active: 1
+  saturation:
+    m_OverrideState: 1
+    m_Value: -40
+--- !u!114 &11400000
+MonoBehaviour:
+  m_Name: LocalProfile
+  components:
+  - {fileID: -1092543176513093641}
+"#;
+
+    #[test]
+    fn test_parse_volume_profile() {
+        let profile = volume_profile_from_yaml(GLOBAL_PROFILE, "GlobalProfile.asset").unwrap();
+        assert_eq!(profile.bloom_intensity, Some(1.0));
+        assert_eq!(profile.bloom_threshold, Some(1.1));
+        assert_eq!(profile.bloom_scatter, None);
+        assert_eq!(profile.tonemapping, Some(TONEMAPPING_ACES));
+        assert_eq!(profile.post_exposure, None);
+        assert_eq!(profile.saturation, Some(20.0));
+    }
+
+    #[test]
+    fn test_local_influence() {
+        assert_eq!(local_influence(0.0, 4.0), Some(1.0));
+        assert_eq!(local_influence(2.0, 4.0), Some(0.75));
+        assert_eq!(local_influence(4.0, 4.0), Some(0.0));
+        assert_eq!(local_influence(5.0, 4.0), None);
+        assert_eq!(local_influence(0.0, 0.0), Some(1.0));
+        assert_eq!(local_influence(0.5, 0.0), None);
+    }
+
+    #[test]
+    fn test_local_volume_blends_over_global_volume() {
+        let global = volume_profile_from_yaml(GLOBAL_PROFILE, "GlobalProfile.asset").unwrap();
+        let local = volume_profile_from_yaml(LOCAL_PROFILE, "LocalProfile.asset").unwrap();
+
+        // by ascending priority, the local volume 2m out of its 4m blend distance
+        let mut state = VolumeState::default();
+        state.blend(&global, 1.0);
+        state.blend(&local, local_influence(2.0, 4.0).unwrap());
+
+        // 20 + (-40 - 20) * 0.75, overrides the local volume lacks stay global
+        assert_eq!(state.saturation, -25.0);
+        assert!((state.color_grading().post_saturation - 0.75).abs() < 1e-5);
+        assert_eq!(state.tonemapping(), Tonemapping::AcesFitted);
+        assert_eq!(state.bloom_intensity, 1.0);
+
+        // a volume without influence doesn't switch enums either
+        let mut state = VolumeState::default();
+        state.blend(&global, 0.0);
+        assert_eq!(state.tonemapping(), Tonemapping::None);
+    }
+
+    #[test]
+    fn test_global_volume_applied_to_camera() {
+        let mut world = World::new();
+
+        let mut profiles = VolumeProfiles::default();
+        let profile = volume_profile_from_yaml(GLOBAL_PROFILE, "GlobalProfile.asset").unwrap();
+        profiles.profiles.insert("global".to_string(), profile);
+        world.insert_resource(profiles);
+
+        world.spawn(UnityVolume {
+            enabled: 1,
+            is_global: 1,
+            priority: 0.0,
+            blend_distance: 0.0,
+            weight: 1.0,
+            shared_profile: FileReference {
+                file_id: 11400000,
+                guid: Some("global".to_string()),
+            },
+        });
+        let camera = world
+            .spawn((
+                Camera3d::default(),
+                GlobalTransform::default(),
+                Tonemapping::None,
+                ColorGrading::default(),
+            ))
+            .id();
+
+        let mut system = IntoSystem::into_system(volume_blend_system);
+        system.initialize(&mut world);
+        system.run((), &mut world);
+        system.apply_deferred(&mut world);
+
+        let camera = world.entity(camera);
+        assert_eq!(camera.get::<Tonemapping>(), Some(&Tonemapping::AcesFitted));
+        let grading = camera.get::<ColorGrading>().unwrap();
+        assert!((grading.post_saturation - 1.2).abs() < 1e-5);
+
+        let bloom = camera.get::<BloomSettings>().unwrap();
+        assert!((bloom.intensity - 0.15).abs() < 1e-5);
+        assert_eq!(bloom.prefilter_settings.threshold, 1.1);
+        assert_eq!(bloom.low_frequency_boost, 0.7);
+    }
+}
@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
 
+use crate::QualitySettings;
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "object_type")]
 pub enum ProjectSettings {
     PlayerSettings(PlayerSettings),
+    QualitySettings(QualitySettings),
     #[serde(other)]
     DontCare,
 }
@@ -0,0 +1,152 @@
+use bevy::pbr::CascadeShadowConfig;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QualitySettings {
+    #[serde(alias = "m_CurrentQuality")]
+    pub current_quality: usize,
+
+    #[serde(alias = "m_QualitySettings")]
+    pub levels: Vec<QualityLevel>,
+}
+
+impl QualitySettings {
+    pub fn current(&self) -> Option<&QualityLevel> {
+        self.levels.get(self.current_quality)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct QualityLevel {
+    pub name: String,
+
+    /// 0 disabled, 1 hard only, 2 hard and soft
+    #[serde(default)]
+    pub shadows: i32,
+
+    #[serde(default, alias = "shadowResolution")]
+    pub shadow_resolution: i32,
+
+    #[serde(default = "default_shadow_cascades", alias = "shadowCascades")]
+    pub shadow_cascades: i32,
+
+    #[serde(default = "default_shadow_distance", alias = "shadowDistance")]
+    pub shadow_distance: f32,
+
+    #[serde(default = "default_cascade_2_split", alias = "shadowCascade2Split")]
+    pub shadow_cascade_2_split: f32,
+
+    #[serde(default, alias = "shadowCascade4Split")]
+    pub shadow_cascade_4_split: CascadeSplit,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct CascadeSplit {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Default for CascadeSplit {
+    fn default() -> Self {
+        Self {
+            x: 0.06666667,
+            y: 0.2,
+            z: 0.46666667,
+        }
+    }
+}
+
+fn default_shadow_cascades() -> i32 {
+    1
+}
+
+fn default_shadow_distance() -> f32 {
+    150.0
+}
+
+fn default_cascade_2_split() -> f32 {
+    0.33333334
+}
+
+impl QualityLevel {
+    pub fn shadows_enabled(&self) -> bool {
+        self.shadows != 0
+    }
+
+    /// Directional shadow map size unity uses for `shadowResolution`.
+    pub fn shadow_map_size(&self) -> usize {
+        512 << self.shadow_resolution.clamp(0, 3)
+    }
+
+    /// Cascade bounds from the shadow distance and split ratios, keeping
+    /// bevy's defaults for everything unity doesn't have.
+    pub fn cascade_shadow_config(&self, base: &CascadeShadowConfig) -> CascadeShadowConfig {
+        let distance = self.shadow_distance;
+        let splits = match self.shadow_cascades {
+            2 => vec![self.shadow_cascade_2_split],
+            4 => {
+                let split = self.shadow_cascade_4_split;
+                vec![split.x, split.y, split.z]
+            }
+            _ => vec![],
+        };
+
+        let bounds = splits
+            .into_iter()
+            .map(|split| split * distance)
+            .chain(std::iter::once(distance))
+            .collect();
+
+        CascadeShadowConfig {
+            bounds,
+            overlap_proportion: base.overlap_proportion,
+            minimum_distance: base.minimum_distance.min(distance),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(cascades: i32) -> QualityLevel {
+        QualityLevel {
+            name: "Test".to_string(),
+            shadows: 2,
+            shadow_resolution: 2,
+            shadow_cascades: cascades,
+            shadow_distance: 100.0,
+            shadow_cascade_2_split: 0.25,
+            shadow_cascade_4_split: CascadeSplit::default(),
+        }
+    }
+
+    #[test]
+    fn test_shadow_map_size() {
+        let mut level = level(1);
+        assert_eq!(level.shadow_map_size(), 2048);
+        level.shadow_resolution = 0;
+        assert_eq!(level.shadow_map_size(), 512);
+        // very high is the largest unity has
+        level.shadow_resolution = 7;
+        assert_eq!(level.shadow_map_size(), 4096);
+    }
+
+    #[test]
+    fn test_cascade_bounds() {
+        let base = CascadeShadowConfig::default();
+
+        let single = level(1).cascade_shadow_config(&base);
+        assert_eq!(single.bounds, vec![100.0]);
+
+        let two = level(2).cascade_shadow_config(&base);
+        assert_eq!(two.bounds, vec![25.0, 100.0]);
+
+        let four = level(4).cascade_shadow_config(&base);
+        assert_eq!(four.bounds.len(), 4);
+        assert!((four.bounds[0] - 6.666667).abs() < 1e-4);
+        assert!((four.bounds[2] - 46.666667).abs() < 1e-4);
+        assert_eq!(four.overlap_proportion, base.overlap_proportion);
+    }
+}
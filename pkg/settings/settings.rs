@@ -1,11 +1,16 @@
 use anyhow::{bail, Context, Result};
 use bevity_yaml::parse_unity_yaml;
-use bevy::prelude::*;
+use bevy::{
+    pbr::{CascadeShadowConfig, DirectionalLightShadowMap},
+    prelude::*,
+};
 use std::path::Path;
 
 mod player;
+mod quality;
 
 pub use player::*;
+pub use quality::*;
 
 pub fn parse_project_settings(settings: &str) -> Result<player::PlayerSettings> {
     let map = parse_unity_yaml(settings)?;
@@ -29,12 +34,36 @@ pub fn parse_project_settings_file(base: &Path) -> Result<player::PlayerSettings
     parse_project_settings(&contents)
 }
 
+pub fn parse_quality_settings(settings: &str) -> Result<QualitySettings> {
+    let map = parse_unity_yaml(settings)?;
+
+    let (_, output) = map
+        .into_iter()
+        .next()
+        .context("0 items in quality settings")?;
+
+    let ProjectSettings::QualitySettings(settings) = output else {
+        bail!("invalid quality settings found")
+    };
+
+    Ok(settings)
+}
+
+pub fn parse_quality_settings_file(base: &Path) -> Result<QualitySettings> {
+    let file = base.join("ProjectSettings/QualitySettings.asset");
+    let contents = std::fs::read_to_string(file)?;
+
+    parse_quality_settings(&contents)
+}
+
 #[derive(Default)]
 pub struct SettingsPlugin;
 
 #[derive(Resource, Debug, Default)]
 pub struct UnitySettings {
     pub player: PlayerSettings,
+    /// The active quality level, if the quality settings could be read.
+    pub quality: Option<QualityLevel>,
 }
 
 impl Plugin for SettingsPlugin {
@@ -47,8 +76,23 @@ impl Plugin for SettingsPlugin {
             return;
         };
 
-        app.insert_resource(UnitySettings { player })
-            .add_systems(Startup, window_settings_system);
+        let quality = match parse_quality_settings_file(&path) {
+            Ok(quality) => quality.current().cloned(),
+            Err(e) => {
+                tracing::error!("failed to parse quality settings: {:?}", e);
+                None
+            }
+        };
+
+        if let Some(quality) = &quality {
+            app.insert_resource(DirectionalLightShadowMap {
+                size: quality.shadow_map_size(),
+            });
+        }
+
+        app.insert_resource(UnitySettings { player, quality })
+            .add_systems(Startup, window_settings_system)
+            .add_systems(Update, shadow_settings_system);
     }
 }
 
@@ -60,3 +104,17 @@ fn window_settings_system(mut windows: Query<&mut Window>, settings: Res<UnitySe
 
     window.title = settings.player.product_name.clone();
 }
+
+fn shadow_settings_system(
+    mut lights: Query<(&mut DirectionalLight, &mut CascadeShadowConfig), Added<DirectionalLight>>,
+    settings: Res<UnitySettings>,
+) {
+    let Some(quality) = &settings.quality else {
+        return;
+    };
+
+    for (mut light, mut cascades) in lights.iter_mut() {
+        light.shadows_enabled &= quality.shadows_enabled();
+        *cascades = quality.cascade_shadow_config(&cascades);
+    }
+}
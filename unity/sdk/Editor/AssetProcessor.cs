@@ -10,64 +10,258 @@ public class UnityAssetLister : AssetPostprocessor
     {
         ProcessMaterials();
         ProcessTextures();
+        ProcessScriptableObjects();
+        ProcessShaders();
+        ProcessMeshes();
+        ProcessBuiltinMeshes();
+        ProcessModels();
+        ProcessAnimatorControllers();
+        ProcessAnimationClips();
+        ProcessAudioClips();
+        ProcessAudioMixers();
         ProcessAllAssets();
     }
 
     private static void ProcessMaterials()
     {
-        var guids = AssetDatabase.FindAssets("t:material", new[] { "Assets/Materials" });
+        var guids = AssetDatabase.FindAssets("t:material", new[] { "Assets" });
         var json = new Dictionary<string, string>();
         foreach (string guid in guids)
         {
             json.Add(guid, AssetDatabase.GUIDToAssetPath(guid));
         }
 
-        var output = JsonConvert.SerializeObject(json);
-        var cargoPath = Path.Combine(Application.dataPath, BevitySettings.Instance.CargoToml);
-        var workingDirectory = Path.GetDirectoryName(cargoPath);
-        File.WriteAllText(Path.Combine(workingDirectory, "materials.json"), output);
+        WriteJson("materials.json", json);
     }
 
     private static void ProcessTextures()
     {
-        var guids = AssetDatabase.FindAssets("t:texture2D", new[] { "Assets/Textures" });
+        var guids = AssetDatabase.FindAssets("t:texture2D", new[] { "Assets" });
         var json = new Dictionary<string, string>();
         foreach (string guid in guids)
         {
             json.Add(guid, AssetDatabase.GUIDToAssetPath(guid));
         }
 
-        var output = JsonConvert.SerializeObject(json);
-        var cargoPath = Path.Combine(Application.dataPath, BevitySettings.Instance.CargoToml);
-        var workingDirectory = Path.GetDirectoryName(cargoPath);
-        File.WriteAllText(Path.Combine(workingDirectory, "textures.json"), output);
+        WriteJson("textures.json", json);
+    }
+
+    private static void ProcessScriptableObjects()
+    {
+        var guids = AssetDatabase.FindAssets("t:ScriptableObject", new[] { "Assets" });
+        var json = new Dictionary<string, string>();
+        foreach (string guid in guids)
+        {
+            json.Add(guid, AssetDatabase.GUIDToAssetPath(guid));
+        }
+
+        WriteJson("scriptables.json", json);
+    }
+
+    private static void ProcessShaders()
+    {
+        var guids = AssetDatabase.FindAssets("t:Shader", new[] { "Assets" });
+        var json = new Dictionary<string, string>();
+        foreach (string guid in guids)
+        {
+            var shader = AssetDatabase.LoadAssetAtPath<Shader>(AssetDatabase.GUIDToAssetPath(guid));
+            if (shader != null)
+            {
+                json.Add(guid, shader.name);
+            }
+        }
+
+        WriteJson("shaders.json", json);
+    }
+
+    private static void ProcessMeshes()
+    {
+        var guids = AssetDatabase.FindAssets("t:Mesh", new[] { "Assets" });
+        var json = new Dictionary<string, string>();
+        foreach (string guid in guids)
+        {
+            var path = AssetDatabase.GUIDToAssetPath(guid);
+            if (path.EndsWith(".asset"))
+            {
+                json.Add(guid, path);
+            }
+        }
+
+        WriteJson("meshes.json", json);
     }
 
     private static void ProcessAllAssets()
     {
-        var guids = AssetDatabase.FindAssets("", new[] { "Assets/Models", "Assets/Textures", "Assets/Materials", "Assets/Prefabs" });
+        var guids = AssetDatabase.FindAssets("", new[] { "Assets" });
         var json = new Dictionary<string, string>();
         foreach (string guid in guids)
         {
             json.Add(guid, AssetDatabase.GUIDToAssetPath(guid));
         }
 
-        var output = JsonConvert.SerializeObject(json);
+        WriteJson("all.json", json);
+    }
+
+    private static readonly string[] BuiltinMeshes = { "Cube", "Cylinder", "Sphere", "Capsule", "Plane", "Quad" };
+    private static bool builtinMeshExportScheduled;
+
+    private static void ProcessBuiltinMeshes()
+    {
+        // creating assets here would start another import, so the export runs
+        // once the editor is done importing
+        if (builtinMeshExportScheduled || !MissingBuiltinMeshes())
+        {
+            return;
+        }
+
+        builtinMeshExportScheduled = true;
+        EditorApplication.delayCall += () =>
+        {
+            builtinMeshExportScheduled = false;
+            ExportBuiltinMeshes(false);
+        };
+    }
+
+    [MenuItem("Bevity/Export Built-in Meshes")]
+    private static void ExportAllBuiltinMeshes()
+    {
+        ExportBuiltinMeshes(true);
+    }
+
+    private static string BuiltinMeshesDirectory()
+    {
+        return Path.Combine(WorkingDirectory(), "builtin_meshes");
+    }
+
+    private static bool MissingBuiltinMeshes()
+    {
+        var directory = BuiltinMeshesDirectory();
+        foreach (var name in BuiltinMeshes)
+        {
+            if (!File.Exists(Path.Combine(directory, name + ".asset")))
+            {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    private static void ExportBuiltinMeshes(bool overwrite)
+    {
+        // unity's own primitives, saved as text mesh assets so bevy doesn't
+        // have to approximate them
+        if (EditorSettings.serializationMode != SerializationMode.ForceText)
+        {
+            Debug.LogWarning("Built-in meshes are only exported with text serialization, bevy will approximate them");
+            return;
+        }
+
+        var directory = BuiltinMeshesDirectory();
+        Directory.CreateDirectory(directory);
+
+        foreach (var name in BuiltinMeshes)
+        {
+            var target = Path.Combine(directory, name + ".asset");
+            if (!overwrite && File.Exists(target))
+            {
+                continue;
+            }
+
+            var temporary = "Assets/BevityBuiltin" + name + ".asset";
+            var mesh = Object.Instantiate(Resources.GetBuiltinResource<Mesh>(name + ".fbx"));
+            AssetDatabase.CreateAsset(mesh, temporary);
+            File.Copy(temporary, target, true);
+            AssetDatabase.DeleteAsset(temporary);
+        }
+    }
+
+    private static void ProcessModels()
+    {
+        // renderer and clip names by file id, to resolve material overrides on
+        // model prefabs and animator controller motions
+        var guids = AssetDatabase.FindAssets("t:Model", new[] { "Assets" });
+        var json = new Dictionary<string, Dictionary<string, string>>();
+        foreach (string guid in guids)
+        {
+            var objects = new Dictionary<string, string>();
+            foreach (var asset in AssetDatabase.LoadAllAssetsAtPath(AssetDatabase.GUIDToAssetPath(guid)))
+            {
+                if (asset is Renderer renderer && AssetDatabase.TryGetGUIDAndLocalFileIdentifier(renderer, out string _, out long fileId))
+                {
+                    objects[fileId.ToString()] = renderer.gameObject.name;
+                }
+                else if (asset is AnimationClip clip && !clip.name.StartsWith("__preview__") && AssetDatabase.TryGetGUIDAndLocalFileIdentifier(clip, out string _, out long clipId))
+                {
+                    objects[clipId.ToString()] = clip.name;
+                }
+            }
+            json.Add(guid, objects);
+        }
+
+        WriteJson("models.json", json);
+    }
+
+    private static void ProcessAnimatorControllers()
+    {
+        var guids = AssetDatabase.FindAssets("t:AnimatorController", new[] { "Assets" });
+        var json = new Dictionary<string, string>();
+        foreach (string guid in guids)
+        {
+            json.Add(guid, AssetDatabase.GUIDToAssetPath(guid));
+        }
+
+        WriteJson("controllers.json", json);
+    }
+
+    private static void ProcessAnimationClips()
+    {
+        // clips inside models are listed with their model instead
+        var guids = AssetDatabase.FindAssets("t:AnimationClip", new[] { "Assets" });
+        var json = new Dictionary<string, string>();
+        foreach (string guid in guids)
+        {
+            var path = AssetDatabase.GUIDToAssetPath(guid);
+            if (path.EndsWith(".anim"))
+            {
+                json.Add(guid, path);
+            }
+        }
+
+        WriteJson("clips.json", json);
+    }
+
+    private static void ProcessAudioClips()
+    {
+        var guids = AssetDatabase.FindAssets("t:AudioClip", new[] { "Assets" });
+        var json = new Dictionary<string, string>();
+        foreach (string guid in guids)
+        {
+            json.Add(guid, AssetDatabase.GUIDToAssetPath(guid));
+        }
+
+        WriteJson("audio.json", json);
+    }
+
+    private static void ProcessAudioMixers()
+    {
+        var guids = AssetDatabase.FindAssets("t:AudioMixer", new[] { "Assets" });
+        var json = new Dictionary<string, string>();
+        foreach (string guid in guids)
+        {
+            json.Add(guid, AssetDatabase.GUIDToAssetPath(guid));
+        }
+
+        WriteJson("mixers.json", json);
+    }
+
+    private static string WorkingDirectory()
+    {
         var cargoPath = Path.Combine(Application.dataPath, BevitySettings.Instance.CargoToml);
-        var workingDirectory = Path.GetDirectoryName(cargoPath);
-        File.WriteAllText(Path.Combine(workingDirectory, "all.json"), output);
-    }
-
-    // private static void ProcessModels()
-    // {
-    //     var guids = AssetDatabase.FindAssets("t:", new[] { "Assets/Textures" });
-    //     var json = new Dictionary<string, string>();
-    //     foreach (string guid in guids)
-    //     {
-    //         json.Add(guid, AssetDatabase.GUIDToAssetPath(guid));
-    //     }
-
-    //     var output = JsonConvert.SerializeObject(json);
-    //     File.WriteAllText(Path.Combine(Application.dataPath, "textures.json"), output);
-    // }
+        return Path.GetDirectoryName(cargoPath);
+    }
+
+    private static void WriteJson(string fileName, object json)
+    {
+        File.WriteAllText(Path.Combine(WorkingDirectory(), fileName), JsonConvert.SerializeObject(json));
+    }
 }
@@ -4,6 +4,7 @@ using System.Collections.Concurrent;
 using UnityEngine;
 using System.Text;
 using Newtonsoft.Json;
+using Newtonsoft.Json.Linq;
 using System.IO;
 using UnityEngine.SceneManagement;
 
@@ -166,6 +167,8 @@ public static class Watcher
                     serialized = EditorJsonUtility.ToJson(component);
                 }
 
+                serialized = WithScriptGuid(component, serialized);
+
                 changeList.Add(new ChangeObject { object_id = GlobalObjectId.GetGlobalObjectIdSlow(component.gameObject).targetObjectId, serialized = serialized });
                 Debug.Log($"Got global object id of gameobject: {GlobalObjectId.GetGlobalObjectIdSlow(component.gameObject)}");
                 if (pendingChanges.TryGetValue(componentId, out var count))
@@ -193,6 +196,29 @@ public static class Watcher
         oldValues.Clear();
     }
 
+    // bevity matches monobehaviours on their script guid, EditorJsonUtility only writes an instance id
+    private static string WithScriptGuid(Component component, string serialized)
+    {
+        if (!(component is MonoBehaviour behaviour))
+        {
+            return serialized;
+        }
+
+        var script = MonoScript.FromMonoBehaviour(behaviour);
+        if (script == null || !AssetDatabase.TryGetGUIDAndLocalFileIdentifier(script, out var guid, out long fileId))
+        {
+            return serialized;
+        }
+
+        var json = JObject.Parse(serialized);
+        if (json["MonoBehaviour"] is JObject body)
+        {
+            body["m_Script"] = new JObject { ["fileID"] = fileId, ["guid"] = guid, ["type"] = 3 };
+        }
+
+        return json.ToString(Formatting.None);
+    }
+
     private static void ChangesPublished(ref ObjectChangeEventStream stream)
     {
         for (int i = 0; i < stream.length; ++i)